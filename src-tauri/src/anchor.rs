@@ -0,0 +1,212 @@
+// Where each day's ultradian cycles start counting from: local midnight, or the
+// user's wake time (configured, or detected for the current day).
+use chrono::{DateTime, NaiveDate, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+
+use crate::settings::SettingsState;
+use crate::timezone;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnchorMode {
+    Midnight,
+    WakeTime,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CycleAnchor {
+    pub mode: AnchorMode,
+    /// Usual wake time, used on days without a detected wake.
+    pub wake_time: NaiveTime,
+    /// Wake time observed for a specific day; only applies to that day.
+    pub detected_wake: Option<DateTime<Utc>>,
+}
+
+impl CycleAnchor {
+    pub fn new(mode: AnchorMode, wake_time: NaiveTime) -> Self {
+        Self {
+            mode,
+            wake_time,
+            detected_wake: None,
+        }
+    }
+
+    /// Start of cycle 1 for the day `now` belongs to. Before today's wake time
+    /// we are still in yesterday's waking day.
+    pub fn anchor_for(&self, now: DateTime<Tz>) -> DateTime<Tz> {
+        let zone = now.timezone();
+        let today = now.date_naive();
+        let reset = self.reset_on(zone, today);
+        if reset <= now {
+            return reset;
+        }
+        match today.pred_opt() {
+            Some(yesterday) => self.reset_on(zone, yesterday),
+            None => reset,
+        }
+    }
+
+    /// Whether the current day's cycles start from an observed rather than
+    /// configured or midnight anchor.
+    pub fn is_observed(&self, now: DateTime<Tz>) -> bool {
+        self.mode == AnchorMode::WakeTime
+            && self.detected_wake == Some(self.anchor_for(now).with_timezone(&Utc))
+    }
+
+    /// The next moment after `now` at which cycle counting restarts.
+    pub fn next_reset_after(&self, now: DateTime<Tz>) -> DateTime<Tz> {
+        let zone = now.timezone();
+        let today = now.date_naive();
+        let reset = self.reset_on(zone, today);
+        if reset > now {
+            return reset;
+        }
+        match today.succ_opt() {
+            Some(tomorrow) => self.reset_on(zone, tomorrow),
+            None => reset,
+        }
+    }
+
+    fn reset_on(&self, zone: Tz, date: NaiveDate) -> DateTime<Tz> {
+        match self.mode {
+            AnchorMode::Midnight => timezone::resolve_on(zone, date, NaiveTime::MIN),
+            AnchorMode::WakeTime => self.wake_on(zone, date),
+        }
+    }
+
+    fn wake_on(&self, zone: Tz, date: NaiveDate) -> DateTime<Tz> {
+        match self.detected_wake.map(|detected| detected.with_timezone(&zone)) {
+            Some(detected) if detected.date_naive() == date => detected,
+            _ => timezone::resolve_on(zone, date, self.wake_time),
+        }
+    }
+}
+
+pub type AnchorState = Arc<Mutex<CycleAnchor>>;
+
+#[tauri::command]
+pub fn get_cycle_anchor(state: tauri::State<'_, AnchorState>) -> Result<CycleAnchor, String> {
+    let anchor = state.lock().map_err(|e| format!("Failed to lock anchor: {}", e))?;
+    Ok(anchor.clone())
+}
+
+/// Sets the anchoring mode and usual wake time (`HH:MM`, wall clock) and saves
+/// them with the rest of the settings.
+#[tauri::command]
+pub fn set_cycle_anchor(
+    state: tauri::State<'_, AnchorState>,
+    settings_state: tauri::State<'_, SettingsState>,
+    mode: AnchorMode,
+    wake_time: String,
+) -> Result<CycleAnchor, String> {
+    let wake_time = NaiveTime::parse_from_str(&wake_time, "%H:%M")
+        .map_err(|e| format!("Invalid wake time '{}': {}", wake_time, e))?;
+    {
+        let mut store = settings_state.lock().map_err(|e| format!("Failed to lock settings: {}", e))?;
+        let mut settings = store.current.clone();
+        settings.anchor_mode = mode;
+        settings.wake_time = wake_time;
+        store.replace(settings)?;
+    }
+    let mut anchor = state.lock().map_err(|e| format!("Failed to lock anchor: {}", e))?;
+    anchor.mode = mode;
+    anchor.wake_time = wake_time;
+    Ok(anchor.clone())
+}
+
+/// Records today's actual wake time (epoch ms, defaults to now). Cycle 1
+/// restarts from it until the next day.
+#[tauri::command]
+pub fn record_wake_time(
+    state: tauri::State<'_, AnchorState>,
+    timestamp: Option<i64>,
+) -> Result<CycleAnchor, String> {
+    let woke_at = match timestamp {
+        Some(ms) => Utc
+            .timestamp_millis_opt(ms)
+            .single()
+            .ok_or_else(|| format!("Invalid wake timestamp: {}", ms))?,
+        None => Utc::now(),
+    };
+    let mut anchor = state.lock().map_err(|e| format!("Failed to lock anchor: {}", e))?;
+    anchor.detected_wake = Some(woke_at);
+    Ok(anchor.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use chrono_tz::Europe::Berlin;
+
+    fn at(d: u32, h: u32, min: u32) -> DateTime<Tz> {
+        Berlin.with_ymd_and_hms(2026, 3, d, h, min, 0).unwrap()
+    }
+
+    fn wake_anchor() -> CycleAnchor {
+        CycleAnchor::new(AnchorMode::WakeTime, NaiveTime::from_hms_opt(7, 0, 0).unwrap())
+    }
+
+    #[test]
+    fn midnight_mode_resets_daily() {
+        let anchor = CycleAnchor::new(AnchorMode::Midnight, NaiveTime::from_hms_opt(7, 0, 0).unwrap());
+        assert_eq!(anchor.anchor_for(at(2, 0, 0)), at(2, 0, 0));
+        assert_eq!(anchor.anchor_for(at(2, 6, 30)), at(2, 0, 0));
+        assert_eq!(anchor.anchor_for(at(2, 23, 59)), at(2, 0, 0));
+        assert_eq!(anchor.anchor_for(at(3, 0, 1)), at(3, 0, 0));
+        assert_eq!(anchor.next_reset_after(at(2, 15, 0)), at(3, 0, 0));
+        assert!(!anchor.is_observed(at(2, 15, 0)));
+    }
+
+    #[test]
+    fn wake_mode_belongs_to_yesterday_before_the_wake_time() {
+        let anchor = wake_anchor();
+        assert_eq!(anchor.anchor_for(at(2, 6, 59)), at(1, 7, 0));
+        assert_eq!(anchor.anchor_for(at(2, 7, 0)), at(2, 7, 0));
+        assert_eq!(anchor.anchor_for(at(2, 23, 30)), at(2, 7, 0));
+        // After midnight is still the previous waking day
+        assert_eq!(anchor.anchor_for(at(3, 1, 0)), at(2, 7, 0));
+    }
+
+    #[test]
+    fn detected_wake_applies_only_to_its_own_date() {
+        let mut anchor = wake_anchor();
+        // Woke early at 06:10 local on 2 March, stored in UTC as record_wake_time does
+        anchor.detected_wake = Some(at(2, 6, 10).with_timezone(&Utc));
+
+        assert_eq!(anchor.anchor_for(at(2, 6, 5)), at(1, 7, 0));
+        assert!(!anchor.is_observed(at(2, 6, 5)));
+        assert_eq!(anchor.anchor_for(at(2, 6, 30)), at(2, 6, 10));
+        assert!(anchor.is_observed(at(2, 6, 30)));
+        assert!(anchor.is_observed(at(3, 6, 0)));
+
+        // The next day goes back to the usual wake time
+        assert_eq!(anchor.anchor_for(at(3, 8, 0)), at(3, 7, 0));
+        assert!(!anchor.is_observed(at(3, 8, 0)));
+        assert_eq!(anchor.next_reset_after(at(2, 6, 30)), at(3, 7, 0));
+
+        // A late wake pushes today's reset back
+        anchor.detected_wake = Some(at(2, 9, 15).with_timezone(&Utc));
+        assert_eq!(anchor.anchor_for(at(2, 8, 0)), at(1, 7, 0));
+        assert_eq!(anchor.next_reset_after(at(2, 8, 0)), at(2, 9, 15));
+
+        // Only wake-time anchoring uses it
+        anchor.mode = AnchorMode::Midnight;
+        assert_eq!(anchor.anchor_for(at(2, 10, 0)), at(2, 0, 0));
+        assert!(!anchor.is_observed(at(2, 10, 0)));
+    }
+
+    #[test]
+    fn next_reset_at_and_just_before_the_boundary() {
+        let anchor = wake_anchor();
+        let boundary = at(2, 7, 0);
+        assert_eq!(anchor.next_reset_after(boundary - Duration::seconds(1)), boundary);
+        assert_eq!(anchor.next_reset_after(boundary), at(3, 7, 0));
+
+        let midnight = CycleAnchor::new(AnchorMode::Midnight, NaiveTime::MIN);
+        assert_eq!(midnight.next_reset_after(at(2, 23, 59) + Duration::seconds(59)), at(3, 0, 0));
+        assert_eq!(midnight.next_reset_after(at(3, 0, 0)), at(4, 0, 0));
+    }
+}
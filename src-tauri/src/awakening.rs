@@ -0,0 +1,378 @@
+// Morning awakening detection: the first sustained rise of activity above a
+// threshold inside a morning window, for each local day of data and at any
+// sample rate, with an explicit result when no awakening is found.
+use chrono::{NaiveDate, NaiveTime};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+use crate::settings::SettingsState;
+use crate::timeseries::TimeSeries;
+use crate::timezone;
+
+/// Activity before and after an onset compared when scoring its quality.
+const QUALITY_CONTEXT_MINUTES: f64 = 60.0;
+/// Windows, and sustained stretches, with samples covering less than this
+/// share are treated as unrecorded.
+const MIN_WINDOW_COVERAGE: f64 = 0.5;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AwakeningConfig {
+    /// Local time window an awakening may start in.
+    pub window_start: NaiveTime,
+    pub window_end: NaiveTime,
+    /// How long activity must stay up to count as getting up; at least 1.
+    pub sustained_minutes: f64,
+    /// Share of samples in the sustained stretch that must be above the
+    /// threshold, so a brief pause does not reset it.
+    pub min_active_fraction: f64,
+    /// Activity level, in the series' units, that counts as active; derived
+    /// from the data's rest and active levels when not given.
+    pub threshold: Option<f64>,
+    /// Defaults to the wake time in settings.
+    pub target_wake: Option<NaiveTime>,
+}
+
+impl Default for AwakeningConfig {
+    fn default() -> Self {
+        Self {
+            window_start: NaiveTime::from_hms_opt(4, 0, 0).unwrap(),
+            window_end: NaiveTime::from_hms_opt(11, 0, 0).unwrap(),
+            sustained_minutes: 15.0,
+            min_active_fraction: 0.8,
+            threshold: None,
+            target_wake: None,
+        }
+    }
+}
+
+impl AwakeningConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.window_end <= self.window_start {
+            return Err("The awakening window must end after it starts, within one morning".to_string());
+        }
+        // Written as positive checks so NaN fails them
+        let sustained = self.sustained_minutes >= 1.0;
+        let fraction = self.min_active_fraction > 0.0 && self.min_active_fraction <= 1.0;
+        if !(sustained && fraction) {
+            return Err("Sustained minutes must be at least 1 and the active fraction in (0, 1]".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AwakeningStatus {
+    Detected,
+    /// The window was recorded but activity never rose and stayed up.
+    NotDetected,
+    /// Too little of the window was recorded to tell.
+    NoData,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MorningAwakening {
+    pub date: NaiveDate,
+    pub status: AwakeningStatus,
+    /// Epoch ms of the activity onset.
+    pub awakening_time: Option<i64>,
+    /// 0-100: how sharply activity rose, how well it held and how restful the
+    /// hour before was.
+    pub quality: Option<f64>,
+    /// Hours after the target wake time; negative when earlier.
+    pub phase_delay_hours: Option<f64>,
+    /// Epoch ms.
+    pub target_wake: i64,
+    /// Share of the window covered by samples.
+    pub coverage: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AwakeningAnalysis {
+    pub threshold: f64,
+    pub sample_interval_minutes: f64,
+    /// One entry per local date whose window overlaps the data, oldest first.
+    pub mornings: Vec<MorningAwakening>,
+}
+
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    let position = p * (sorted.len() - 1) as f64;
+    let lower = position.floor() as usize;
+    let upper = position.ceil() as usize;
+    sorted[lower] + (position - lower as f64) * (sorted[upper] - sorted[lower])
+}
+
+fn mean_of(values: &[f64]) -> Option<f64> {
+    (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64)
+}
+
+/// Samples with timestamps in [from, to).
+fn between(activity: &TimeSeries, from: i64, to: i64) -> std::ops::Range<usize> {
+    activity.timestamps.partition_point(|&t| t < from)..activity.timestamps.partition_point(|&t| t < to)
+}
+
+pub fn detect_awakenings(
+    activity: &TimeSeries,
+    config: &AwakeningConfig,
+    target_wake: NaiveTime,
+    zone: Tz,
+) -> Result<AwakeningAnalysis, String> {
+    if activity.len() < 2 {
+        return Err("Awakening detection needs at least two activity samples".to_string());
+    }
+    let interval_ms = (activity.median_interval_minutes().unwrap_or_default() * 60_000.0).max(1.0);
+    let sustained_ms = (config.sustained_minutes * 60_000.0) as i64;
+    let context_ms = (QUALITY_CONTEXT_MINUTES * 60_000.0) as i64;
+
+    let mut sorted = activity.values.clone();
+    sorted.sort_by(f64::total_cmp);
+    let (rest, active) = (percentile(&sorted, 0.1), percentile(&sorted, 0.9));
+    let threshold = config.threshold.unwrap_or(rest + (active - rest) / 2.0);
+    let range = (active - rest).max(f64::EPSILON);
+
+    let (first, last) = (activity.timestamps[0], activity.timestamps[activity.len() - 1]);
+    let last_date = activity.time_at(activity.len() - 1, zone).date_naive();
+    let mut mornings = Vec::new();
+    for date in activity.time_at(0, zone).date_naive().iter_days().take_while(|d| *d <= last_date) {
+        let window_start = timezone::resolve_on(zone, date, config.window_start).timestamp_millis();
+        let window_end = timezone::resolve_on(zone, date, config.window_end).timestamp_millis();
+        if window_end <= first || window_start > last {
+            continue;
+        }
+        let target = timezone::resolve_on(zone, date, target_wake).timestamp_millis();
+        let in_window = between(activity, window_start, window_end);
+        let coverage = (in_window.len() as f64 * interval_ms / (window_end - window_start) as f64).min(1.0);
+        let mut morning = MorningAwakening {
+            date,
+            status: AwakeningStatus::NoData,
+            awakening_time: None,
+            quality: None,
+            phase_delay_hours: None,
+            target_wake: target,
+            coverage,
+        };
+        if coverage < MIN_WINDOW_COVERAGE {
+            mornings.push(morning);
+            continue;
+        }
+        morning.status = AwakeningStatus::NotDetected;
+
+        for i in in_window.filter(|&i| activity.values[i] > threshold) {
+            let onset = activity.timestamps[i];
+            let stretch = between(activity, onset, onset + sustained_ms);
+            // The stretch must be recorded through to its end, without big holes
+            let reaches_end = activity.timestamps[stretch.end - 1] + (interval_ms as i64) >= onset + sustained_ms;
+            if !reaches_end || (stretch.len() as f64 * interval_ms) < MIN_WINDOW_COVERAGE * sustained_ms as f64 {
+                continue;
+            }
+            let values = &activity.values[stretch];
+            let active_fraction = values.iter().filter(|&&v| v > threshold).count() as f64 / values.len() as f64;
+            if active_fraction < config.min_active_fraction {
+                continue;
+            }
+
+            let before = &activity.values[between(activity, onset - context_ms, onset)];
+            let after = &activity.values[between(activity, onset, onset + context_ms)];
+            let contrast = match (mean_of(before), mean_of(after)) {
+                (Some(b), Some(a)) => ((a - b) / range).clamp(0.0, 1.0),
+                _ => 0.0,
+            };
+            // Without data before the onset the preceding rest cannot be confirmed
+            let prior_rest = if before.is_empty() {
+                0.0
+            } else {
+                before.iter().filter(|&&v| v <= threshold).count() as f64 / before.len() as f64
+            };
+            morning.status = AwakeningStatus::Detected;
+            morning.awakening_time = Some(onset);
+            morning.quality = Some(100.0 * (0.5 * contrast + 0.25 * active_fraction + 0.25 * prior_rest));
+            morning.phase_delay_hours = Some((onset - target) as f64 / 3_600_000.0);
+            break;
+        }
+        mornings.push(morning);
+    }
+
+    Ok(AwakeningAnalysis {
+        threshold,
+        sample_interval_minutes: interval_ms / 60_000.0,
+        mornings,
+    })
+}
+
+#[tauri::command]
+pub async fn detect_awakening(
+    settings_state: tauri::State<'_, SettingsState>,
+    activity: TimeSeries,
+    config: Option<AwakeningConfig>,
+) -> Result<AwakeningAnalysis, String> {
+    let (zone, wake_time) = {
+        let store = settings_state.lock().map_err(|e| format!("Failed to lock settings: {}", e))?;
+        (store.current.zone(), store.current.wake_time)
+    };
+    let config = config.unwrap_or_default();
+    config.validate()?;
+    activity.validate()?;
+    let target_wake = config.target_wake.unwrap_or(wake_time);
+
+    tauri::async_runtime::spawn_blocking(move || detect_awakenings(&activity, &config, target_wake, zone))
+        .await
+        .map_err(|e| format!("Failed to detect awakening: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chrono::TimeZone;
+
+    /// 2026-03-02 00:00 UTC.
+    const START: i64 = 1_772_409_600_000;
+    const MINUTE: i64 = 60_000;
+
+    fn at(hour: u32, minute: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    /// Samples every `interval` minutes from `from` to `to` (epoch ms), with
+    /// `level` giving the activity at each.
+    fn series(from: i64, to: i64, interval: i64, level: impl Fn(i64) -> f64) -> TimeSeries {
+        let timestamps: Vec<i64> = (from..to).step_by((interval * MINUTE) as usize).collect();
+        let values = timestamps.iter().map(|&t| level(t)).collect();
+        TimeSeries { timestamps, values }
+    }
+
+    /// Minutes since local midnight in UTC.
+    fn minute_of_day(timestamp: i64) -> i64 {
+        (timestamp - START).rem_euclid(1440 * MINUTE) / MINUTE
+    }
+
+    /// Up from 07:00 until 23:00.
+    fn day(timestamp: i64) -> f64 {
+        if (7 * 60..23 * 60).contains(&minute_of_day(timestamp)) { 100.0 } else { 0.0 }
+    }
+
+    fn detect(activity: &TimeSeries, config: &AwakeningConfig) -> AwakeningAnalysis {
+        detect_awakenings(activity, config, at(7, 0), Tz::UTC).unwrap()
+    }
+
+    #[test]
+    fn reports_each_morning_as_detected_not_detected_or_no_data() {
+        let lie_in = START + 1440 * MINUTE;
+        let gap = (START + 2 * 1440 * MINUTE + 2 * 60 * MINUTE)..(START + 2 * 1440 * MINUTE + 12 * 60 * MINUTE);
+        let recorded = series(START, START + 3 * 1440 * MINUTE, 1, |t| {
+            // Up only at noon on the second day
+            let second_morning = (lie_in..lie_in + 12 * 60 * MINUTE).contains(&t);
+            if second_morning { 0.0 } else { day(t) }
+        });
+        let (timestamps, values) =
+            recorded.timestamps.iter().zip(&recorded.values).filter(|(t, _)| !gap.contains(t)).unzip();
+        let activity = TimeSeries { timestamps, values };
+
+        let analysis = detect(&activity, &AwakeningConfig::default());
+        assert_eq!(analysis.threshold, 50.0);
+        assert_eq!(analysis.sample_interval_minutes, 1.0);
+        let statuses: Vec<AwakeningStatus> = analysis.mornings.iter().map(|m| m.status).collect();
+        assert_eq!(statuses, [AwakeningStatus::Detected, AwakeningStatus::NotDetected, AwakeningStatus::NoData]);
+
+        let first = &analysis.mornings[0];
+        assert_eq!(first.date, NaiveDate::from_ymd_opt(2026, 3, 2).unwrap());
+        assert_eq!(first.awakening_time, Some(START + 7 * 60 * MINUTE));
+        assert_eq!(first.phase_delay_hours, Some(0.0));
+        // Sharp rise, held throughout, after an hour of rest
+        assert_eq!(first.quality, Some(100.0));
+        assert_eq!(first.coverage, 1.0);
+
+        let second = &analysis.mornings[1];
+        assert_eq!((second.awakening_time, second.quality, second.phase_delay_hours), (None, None, None));
+        assert_eq!(second.coverage, 1.0);
+        assert_eq!(second.target_wake, lie_in + 7 * 60 * MINUTE);
+
+        assert_eq!(analysis.mornings[2].coverage, 0.0);
+    }
+
+    #[test]
+    fn sample_rate_does_not_move_the_onset() {
+        for interval in [1, 5] {
+            let activity = series(START, START + 1440 * MINUTE, interval, day);
+            let analysis = detect(&activity, &AwakeningConfig::default());
+            assert_eq!(analysis.sample_interval_minutes, interval as f64);
+            let morning = &analysis.mornings[0];
+            assert_eq!(morning.status, AwakeningStatus::Detected, "{} min", interval);
+            assert_eq!(morning.awakening_time, Some(START + 7 * 60 * MINUTE), "{} min", interval);
+            assert_eq!(morning.coverage, 1.0);
+        }
+
+        // A stretch shorter than the sampling interval still holds the onset sample
+        let coarse = series(START, START + 1440 * MINUTE, 5, day);
+        let config = AwakeningConfig { sustained_minutes: 1.0, ..Default::default() };
+        assert_eq!(detect(&coarse, &config).mornings[0].awakening_time, Some(START + 7 * 60 * MINUTE));
+    }
+
+    #[test]
+    fn a_brief_pause_does_not_reset_the_stretch() {
+        let with_pause = |minutes: i64| {
+            move |t: i64| {
+                let minute = minute_of_day(t);
+                // An early stir that does not last, then a pause just after getting up
+                let stir = (5 * 60..5 * 60 + 3).contains(&minute);
+                let pause = (7 * 60 + 5..7 * 60 + 5 + minutes).contains(&minute);
+                if stir { 100.0 } else if pause { 0.0 } else { day(t) }
+            }
+        };
+        let config = AwakeningConfig { threshold: Some(50.0), ..Default::default() };
+
+        // 2 of 15 minutes below threshold keeps 87% active
+        let activity = series(START, START + 1440 * MINUTE, 1, with_pause(2));
+        assert_eq!(detect(&activity, &config).mornings[0].awakening_time, Some(START + 7 * 60 * MINUTE));
+
+        // 4 of 15 drops to 73%, so the onset moves past the pause
+        let activity = series(START, START + 1440 * MINUTE, 1, with_pause(4));
+        let morning = &detect(&activity, &config).mornings[0];
+        assert_eq!(morning.awakening_time, Some(START + (7 * 60 + 9) * MINUTE));
+        assert!((morning.phase_delay_hours.unwrap() - 0.15).abs() < 1e-9);
+    }
+
+    #[test]
+    fn the_window_follows_local_time_across_a_dst_change() {
+        // New York springs forward at 02:00 on 8 March 2026
+        let zone = Tz::America__New_York;
+        let local = |date: u32, hour: u32| zone.with_ymd_and_hms(2026, 3, date, hour, 0, 0).unwrap().timestamp_millis();
+        let from = local(7, 0);
+        let to = local(9, 0);
+        let up = |t: i64| {
+            let up_7th = (local(7, 7)..local(7, 23)).contains(&t);
+            let up_8th = (local(8, 7)..local(8, 23)).contains(&t);
+            if up_7th || up_8th { 100.0 } else { 0.0 }
+        };
+        let activity = series(from, to, 1, up);
+        let analysis = detect_awakenings(&activity, &AwakeningConfig::default(), at(7, 0), zone).unwrap();
+
+        assert_eq!(analysis.mornings.len(), 2);
+        for (morning, date) in analysis.mornings.iter().zip([7, 8]) {
+            assert_eq!(morning.status, AwakeningStatus::Detected);
+            assert_eq!(morning.awakening_time, Some(local(date, 7)));
+            assert_eq!(morning.target_wake, local(date, 7));
+            assert_eq!(morning.phase_delay_hours, Some(0.0));
+            assert_eq!(morning.coverage, 1.0);
+        }
+        // Local 07:00 comes an hour earlier in UTC once daylight saving starts
+        assert_eq!(local(8, 7) - local(7, 7), 23 * 60 * MINUTE);
+    }
+
+    #[test]
+    fn validation_requires_a_sustained_minute() {
+        assert!(AwakeningConfig::default().validate().is_ok());
+        for sustained_minutes in [0.0, 0.5, -1.0, f64::NAN] {
+            let config = AwakeningConfig { sustained_minutes, ..Default::default() };
+            assert!(config.validate().is_err(), "{}", sustained_minutes);
+        }
+        assert!(AwakeningConfig { sustained_minutes: 1.0, ..Default::default() }.validate().is_ok());
+        assert!(AwakeningConfig { min_active_fraction: 0.0, ..Default::default() }.validate().is_err());
+        let reversed = AwakeningConfig { window_start: at(11, 0), window_end: at(4, 0), ..Default::default() };
+        assert!(reversed.validate().is_err());
+
+        let single = TimeSeries { timestamps: vec![START], values: vec![0.0] };
+        assert!(detect_awakenings(&single, &AwakeningConfig::default(), at(7, 0), Tz::UTC).is_err());
+    }
+}
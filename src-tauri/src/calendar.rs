@@ -0,0 +1,375 @@
+// RFC 5545 calendar export of predicted peak and rest windows, built from the
+// phase forecast so calendars match the tray and widget.
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+use crate::anchor::{AnchorState, CycleAnchor};
+use crate::forecast::{self, PhaseSegment};
+use crate::settings::SettingsState;
+use crate::timezone;
+use crate::ultradian::{EnergyPhase, UltradianClock};
+
+/// Longest date range a single export may cover.
+const MAX_EXPORT_DAYS: i64 = 62;
+/// Content lines longer than this many octets are folded (RFC 5545 3.1).
+const MAX_LINE_OCTETS: usize = 75;
+const PRODUCT_ID: &str = "-//Circada//Phase Forecast//EN";
+const UID_DOMAIN: &str = "circada.app";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WindowKind {
+    /// The high-energy focus window.
+    Peak,
+    /// The low-energy rest window at the end of a cycle.
+    Rest,
+}
+
+impl WindowKind {
+    fn of(phase: EnergyPhase) -> Option<Self> {
+        match phase {
+            EnergyPhase::High => Some(WindowKind::Peak),
+            EnergyPhase::Low => Some(WindowKind::Rest),
+            EnergyPhase::Transition => None,
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            WindowKind::Peak => "peak",
+            WindowKind::Rest => "rest",
+        }
+    }
+
+    fn summary(&self) -> &'static str {
+        match self {
+            WindowKind::Peak => "Focus window",
+            WindowKind::Rest => "Rest break",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CalendarExportOptions {
+    /// Which windows become events.
+    pub windows: Vec<WindowKind>,
+    /// Minutes before each event to alert; one VALARM per entry, 0 alerts at the start.
+    pub alarm_minutes: Vec<u32>,
+}
+
+impl Default for CalendarExportOptions {
+    fn default() -> Self {
+        Self {
+            windows: vec![WindowKind::Peak, WindowKind::Rest],
+            alarm_minutes: Vec::new(),
+        }
+    }
+}
+
+/// A peak or rest window, merged across the display bands it spans.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PhaseWindow {
+    pub kind: WindowKind,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    /// Day whose cycle numbering the window belongs to.
+    pub cycle_day: NaiveDate,
+    pub cycle_number: i32,
+}
+
+impl PhaseWindow {
+    /// Identifies the same window across exports, so re-importing replaces the
+    /// event rather than adding a copy even if its times moved.
+    pub fn uid(&self) -> String {
+        format!(
+            "{}-cycle{}-{}@{}",
+            self.cycle_day.format("%Y%m%d"),
+            self.cycle_number,
+            self.kind.as_str(),
+            UID_DOMAIN
+        )
+    }
+}
+
+/// Peak and rest windows overlapping the local dates `first..=last` in `zone`.
+/// Windows crossing the ends of the range are kept whole, so an overlapping
+/// export produces the same event for the same UID.
+pub fn phase_windows(
+    clock: &UltradianClock,
+    anchor: &CycleAnchor,
+    zone: Tz,
+    first: NaiveDate,
+    last: NaiveDate,
+) -> Vec<PhaseWindow> {
+    let start = timezone::resolve_on(zone, first, NaiveTime::MIN);
+    let end = match last.succ_opt() {
+        Some(next) => timezone::resolve_on(zone, next, NaiveTime::MIN),
+        None => start,
+    };
+    if end <= start {
+        return Vec::new();
+    }
+
+    // A window is shorter than a cycle, so a cycle either side covers any
+    // window crossing the range edges
+    let margin = Duration::milliseconds((clock.cycle_length * 60_000.0).round() as i64);
+    let segments = forecast::forecast(clock, anchor, start - margin, end - start + margin * 2, 1.0, 0.0);
+    let mut windows: Vec<PhaseWindow> = Vec::new();
+    for segment in &segments {
+        let Some(kind) = WindowKind::of(segment.energy_phase) else {
+            continue;
+        };
+        let segment_start = segment.start.with_timezone(&Utc);
+        let segment_end = segment.end.with_timezone(&Utc);
+        match windows.last_mut() {
+            Some(window)
+                if window.kind == kind && window.cycle_number == segment.cycle_number && window.end == segment_start =>
+            {
+                window.end = segment_end;
+            }
+            _ => windows.push(PhaseWindow {
+                kind,
+                start: segment_start,
+                end: segment_end,
+                cycle_day: cycle_day(anchor, segment, zone),
+                cycle_number: segment.cycle_number,
+            }),
+        }
+    }
+    let (start, end) = (start.with_timezone(&Utc), end.with_timezone(&Utc));
+    windows.retain(|window| window.end > start && window.start < end);
+    windows
+}
+
+fn cycle_day(anchor: &CycleAnchor, segment: &PhaseSegment, zone: Tz) -> NaiveDate {
+    anchor.anchor_for(segment.start.with_timezone(&zone)).date_naive()
+}
+
+/// Serializes `windows` as a VCALENDAR stamped with `generated_at`.
+pub fn to_ics(windows: &[PhaseWindow], options: &CalendarExportOptions, generated_at: DateTime<Utc>) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        format!("PRODID:{}", PRODUCT_ID),
+        "CALSCALE:GREGORIAN".to_string(),
+        "METHOD:PUBLISH".to_string(),
+        "X-WR-CALNAME:Circada".to_string(),
+    ];
+
+    for window in windows.iter().filter(|w| options.windows.contains(&w.kind)) {
+        let minutes = (window.end - window.start).num_minutes();
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:{}", window.uid()));
+        lines.push(format!("DTSTAMP:{}", ics_time(generated_at)));
+        lines.push(format!("LAST-MODIFIED:{}", ics_time(generated_at)));
+        lines.push(format!("DTSTART:{}", ics_time(window.start)));
+        lines.push(format!("DTEND:{}", ics_time(window.end)));
+        lines.push(format!("SUMMARY:{}", escape_text(window.kind.summary())));
+        lines.push(format!(
+            "DESCRIPTION:{}",
+            escape_text(&format!(
+                "Predicted {} window of cycle {}, about {} min.",
+                window.kind.as_str(),
+                window.cycle_number,
+                minutes
+            ))
+        ));
+        lines.push(format!("CATEGORIES:{}", window.kind.as_str().to_uppercase()));
+        lines.push("TRANSP:TRANSPARENT".to_string());
+        for alarm in &options.alarm_minutes {
+            lines.push("BEGIN:VALARM".to_string());
+            lines.push("ACTION:DISPLAY".to_string());
+            lines.push(format!("DESCRIPTION:{}", escape_text(window.kind.summary())));
+            lines.push(format!("TRIGGER:-PT{}M", alarm));
+            lines.push("END:VALARM".to_string());
+        }
+        lines.push("END:VEVENT".to_string());
+    }
+
+    lines.push("END:VCALENDAR".to_string());
+    lines.iter().map(|line| fold_line(line)).collect()
+}
+
+fn ics_time(time: DateTime<Utc>) -> String {
+    time.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// Escapes a TEXT value (RFC 5545 3.3.11).
+fn escape_text(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Folds a content line at 75 octets without splitting a UTF-8 character, and
+/// terminates it with CRLF.
+fn fold_line(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len() + 4);
+    let mut octets = 0;
+    for c in line.chars() {
+        let width = c.len_utf8();
+        if octets + width > MAX_LINE_OCTETS {
+            folded.push_str("\r\n ");
+            // The leading space of a continuation line counts towards its length.
+            octets = 1;
+        }
+        folded.push(c);
+        octets += width;
+    }
+    folded.push_str("\r\n");
+    folded
+}
+
+fn parse_date(value: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|e| format!("Failed to parse date '{}': {}", value, e))
+}
+
+/// Calendar text for the local dates `start_date..=end_date` (YYYY-MM-DD).
+#[tauri::command]
+pub fn export_phase_calendar(
+    settings_state: tauri::State<'_, SettingsState>,
+    anchor_state: tauri::State<'_, AnchorState>,
+    start_date: String,
+    end_date: String,
+    options: Option<CalendarExportOptions>,
+) -> Result<String, String> {
+    let first = parse_date(&start_date)?;
+    let last = parse_date(&end_date)?;
+    if last < first {
+        return Err("Calendar export must end on or after its start date".to_string());
+    }
+    if last - first >= Duration::days(MAX_EXPORT_DAYS) {
+        return Err(format!("Calendar export can cover at most {} days", MAX_EXPORT_DAYS));
+    }
+    let options = options.unwrap_or_default();
+
+    let (clock, zone) = {
+        let store = settings_state.lock().map_err(|e| format!("Failed to lock settings: {}", e))?;
+        (store.current.clock(), store.current.zone())
+    };
+    let anchor = anchor_state
+        .lock()
+        .map_err(|e| format!("Failed to lock anchor: {}", e))?
+        .clone();
+
+    let windows = phase_windows(&clock, &anchor, zone, first, last);
+    Ok(to_ics(&windows, &options, Utc::now()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::anchor::AnchorMode;
+
+    fn date(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 3, d).unwrap()
+    }
+
+    fn wake_anchor() -> CycleAnchor {
+        CycleAnchor::new(AnchorMode::WakeTime, NaiveTime::from_hms_opt(7, 0, 0).unwrap())
+    }
+
+    #[test]
+    fn windows_crossing_the_range_stay_whole() {
+        let clock = UltradianClock::default();
+        // Cycle 12 after a 07:00 wake starts at 23:30, so its peak runs 23:35-00:30.
+        let crossing = |windows: &[PhaseWindow]| {
+            windows
+                .iter()
+                .find(|window| window.uid() == "20260302-cycle12-peak@circada.app")
+                .cloned()
+                .unwrap()
+        };
+        let first_day = crossing(&phase_windows(&clock, &wake_anchor(), Tz::UTC, date(2), date(2)));
+        let second_day = crossing(&phase_windows(&clock, &wake_anchor(), Tz::UTC, date(3), date(3)));
+        let both = crossing(&phase_windows(&clock, &wake_anchor(), Tz::UTC, date(2), date(3)));
+        assert_eq!(first_day, second_day);
+        assert_eq!(first_day, both);
+        assert_eq!(first_day.start.format("%d %H:%M").to_string(), "02 23:35");
+        assert_eq!(first_day.end.format("%d %H:%M").to_string(), "03 00:30");
+    }
+
+    #[test]
+    fn windows_stay_within_overlap_of_range() {
+        let clock = UltradianClock::default();
+        let windows = phase_windows(&clock, &wake_anchor(), Tz::UTC, date(2), date(2));
+        let (start, end) = (date(2).and_time(NaiveTime::MIN).and_utc(), date(3).and_time(NaiveTime::MIN).and_utc());
+        assert!(windows.iter().all(|window| window.end > start && window.start < end));
+        assert!(windows.windows(2).all(|pair| pair[0].end <= pair[1].start));
+        // The peak before 07:00 belongs to the previous day's cycles.
+        assert!(windows.iter().any(|window| window.cycle_day == date(1)));
+    }
+
+    #[test]
+    fn uid_is_stable_across_exports() {
+        let clock = UltradianClock::default();
+        let anchor = CycleAnchor::new(AnchorMode::Midnight, NaiveTime::MIN);
+        let week = phase_windows(&clock, &anchor, Tz::UTC, date(1), date(7));
+        let day = phase_windows(&clock, &anchor, Tz::UTC, date(4), date(4));
+        for window in &day {
+            assert!(week.contains(window), "{}", window.uid());
+        }
+        let mut uids: Vec<String> = week.iter().map(PhaseWindow::uid).collect();
+        uids.sort();
+        uids.dedup();
+        assert_eq!(uids.len(), week.len());
+        assert_eq!(day[0].uid(), "20260304-cycle1-peak@circada.app");
+    }
+
+    #[test]
+    fn escapes_text_values() {
+        assert_eq!(escape_text("a,b;c\\d"), "a\\,b\\;c\\\\d");
+        assert_eq!(escape_text("line one\r\nline two"), "line one\\nline two");
+        assert_eq!(escape_text("Focus window"), "Focus window");
+    }
+
+    #[test]
+    fn folds_long_lines_at_75_octets() {
+        assert_eq!(fold_line("SUMMARY:short"), "SUMMARY:short\r\n");
+
+        let long = format!("DESCRIPTION:{}", "x".repeat(200));
+        let folded = fold_line(&long);
+        let lines: Vec<&str> = folded.trim_end_matches("\r\n").split("\r\n").collect();
+        assert!(lines.iter().all(|line| line.len() <= MAX_LINE_OCTETS));
+        assert!(lines[1..].iter().all(|line| line.starts_with(' ')));
+        let unfolded: String = lines[0].to_string() + &lines[1..].iter().map(|line| &line[1..]).collect::<String>();
+        assert_eq!(unfolded, long);
+    }
+
+    #[test]
+    fn folding_keeps_multibyte_characters_whole() {
+        let long = format!("SUMMARY:{}", "🔥".repeat(40));
+        let folded = fold_line(&long);
+        for line in folded.split("\r\n") {
+            assert!(line.len() <= MAX_LINE_OCTETS);
+        }
+        assert_eq!(folded.replace("\r\n ", "").trim_end(), long);
+    }
+
+    #[test]
+    fn ics_wraps_events_in_calendar() {
+        let clock = UltradianClock::default();
+        let anchor = CycleAnchor::new(AnchorMode::Midnight, NaiveTime::MIN);
+        let windows = phase_windows(&clock, &anchor, Tz::UTC, date(2), date(2));
+        let options = CalendarExportOptions { windows: vec![WindowKind::Peak], alarm_minutes: vec![5] };
+        let generated = date(1).and_time(NaiveTime::MIN).and_utc();
+        let ics = to_ics(&windows, &options, generated);
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+        let peaks = windows.iter().filter(|window| window.kind == WindowKind::Peak).count();
+        assert_eq!(ics.matches("BEGIN:VEVENT").count(), peaks);
+        assert_eq!(ics.matches("TRIGGER:-PT5M").count(), peaks);
+        assert!(ics.contains("DTSTART:20260302T000500Z\r\n"));
+    }
+}
@@ -0,0 +1,294 @@
+// Chronotype from multi-day sleep, following the Munich ChronoType
+// Questionnaire: midsleep on free days corrected for sleep debt (MSFsc) and
+// social jet lag between work and free days.
+use chrono::{Datelike, Duration, NaiveDate};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+use crate::settings::SettingsState;
+use crate::sleep_metrics::ScoredEpochs;
+use crate::sleep_regularity::{self, SleepDayStatus, SleepRecord};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChronotypeConfig {
+    /// Workdays from Monday to Sunday. A night counts as a work night when the
+    /// day after it is a workday.
+    pub workday_mask: [bool; 7],
+    /// Calendar dates that are free regardless of the mask, e.g. holidays.
+    pub free_dates: Vec<NaiveDate>,
+    /// Calendar dates that are workdays regardless of the mask.
+    pub work_dates: Vec<NaiveDate>,
+}
+
+impl Default for ChronotypeConfig {
+    fn default() -> Self {
+        Self {
+            workday_mask: [true, true, true, true, true, false, false],
+            free_dates: Vec::new(),
+            work_dates: Vec::new(),
+        }
+    }
+}
+
+impl ChronotypeConfig {
+    fn is_workday(&self, date: NaiveDate) -> bool {
+        if self.work_dates.contains(&date) {
+            return true;
+        }
+        !self.free_dates.contains(&date) && self.workday_mask[date.weekday().num_days_from_monday() as usize]
+    }
+}
+
+/// MSFsc bands, roughly matching population percentiles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChronotypeCategory {
+    /// MSFsc before 02:00.
+    ExtremelyEarly,
+    /// 02:00 to 03:30.
+    Early,
+    /// 03:30 to 05:00.
+    Intermediate,
+    /// 05:00 to 06:30.
+    Late,
+    /// 06:30 or later.
+    ExtremelyLate,
+}
+
+impl ChronotypeCategory {
+    fn from_minutes_after_noon(msf_sc: f64) -> Self {
+        match msf_sc / 60.0 - 12.0 {
+            h if h < 2.0 => Self::ExtremelyEarly,
+            h if h < 3.5 => Self::Early,
+            h if h < 5.0 => Self::Intermediate,
+            h if h < 6.5 => Self::Late,
+            _ => Self::ExtremelyLate,
+        }
+    }
+}
+
+/// Clock times are local hours in [0, 24); durations are hours.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Chronotype {
+    /// Midsleep on work nights.
+    pub msw: f64,
+    /// Midsleep on free nights.
+    pub msf: f64,
+    /// MSF corrected for oversleep on free days.
+    pub msf_sc: f64,
+    pub sleep_duration_work: f64,
+    pub sleep_duration_free: f64,
+    /// Work and free durations weighted by the nights actually recorded, so
+    /// holidays and missing nights count as they fell rather than as 5:2.
+    pub sleep_duration_week: f64,
+    /// MSF - MSW; positive when free-day sleep runs later.
+    pub social_jet_lag: f64,
+    pub category: ChronotypeCategory,
+    /// Sleep window centred on MSFsc with the weekly average duration.
+    pub natural_sleep_onset: f64,
+    pub natural_wake_time: f64,
+    pub work_nights: usize,
+    pub free_nights: usize,
+}
+
+fn clock_hours(minutes_after_noon: f64) -> f64 {
+    (minutes_after_noon / 60.0 + 12.0).rem_euclid(24.0)
+}
+
+fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+pub fn chronotype(scoring: &ScoredEpochs, config: &ChronotypeConfig, zone: Tz) -> Result<Chronotype, String> {
+    // (midsleep minutes after noon, sleep duration minutes) per night
+    let mut work = Vec::new();
+    let mut free = Vec::new();
+    for (day, _) in sleep_regularity::sleep_days(scoring, zone) {
+        if day.status != SleepDayStatus::Observed {
+            continue;
+        }
+        let (Some(bed), Some(wake), Some(mid)) = (day.bed_time, day.wake_time, day.midsleep) else {
+            continue;
+        };
+        let night = (
+            sleep_regularity::minutes_after_noon(mid.timestamp_millis(), zone),
+            (wake - bed).num_seconds() as f64 / 60.0,
+        );
+        if config.is_workday(day.date + Duration::days(1)) {
+            work.push(night);
+        } else {
+            free.push(night);
+        }
+    }
+    if work.is_empty() || free.is_empty() {
+        return Err(format!(
+            "Chronotype needs at least one work night and one free night, got {} and {}",
+            work.len(),
+            free.len()
+        ));
+    }
+
+    let msw = mean(&work.iter().map(|n| n.0).collect::<Vec<_>>());
+    let msf = mean(&free.iter().map(|n| n.0).collect::<Vec<_>>());
+    let sd_work = mean(&work.iter().map(|n| n.1).collect::<Vec<_>>());
+    let sd_free = mean(&free.iter().map(|n| n.1).collect::<Vec<_>>());
+    let (work_nights, free_nights) = (work.len() as f64, free.len() as f64);
+    let sd_week = (sd_work * work_nights + sd_free * free_nights) / (work_nights + free_nights);
+    // Only longer free-day sleep signals debt being paid back
+    let msf_sc = if sd_free > sd_work { msf - (sd_free - sd_week) / 2.0 } else { msf };
+
+    Ok(Chronotype {
+        msw: clock_hours(msw),
+        msf: clock_hours(msf),
+        msf_sc: clock_hours(msf_sc),
+        sleep_duration_work: sd_work / 60.0,
+        sleep_duration_free: sd_free / 60.0,
+        sleep_duration_week: sd_week / 60.0,
+        social_jet_lag: (msf - msw) / 60.0,
+        category: ChronotypeCategory::from_minutes_after_noon(msf_sc),
+        natural_sleep_onset: clock_hours(msf_sc - sd_week / 2.0),
+        natural_wake_time: clock_hours(msf_sc + sd_week / 2.0),
+        work_nights: work.len(),
+        free_nights: free.len(),
+    })
+}
+
+/// Takes either scored epochs spanning several days or HealthKit sleep records.
+#[tauri::command]
+pub async fn calculate_chronotype(
+    settings_state: tauri::State<'_, SettingsState>,
+    scoring: Option<ScoredEpochs>,
+    sleep_records: Option<Vec<SleepRecord>>,
+    config: Option<ChronotypeConfig>,
+) -> Result<Chronotype, String> {
+    let zone = settings_state
+        .lock()
+        .map_err(|e| format!("Failed to lock settings: {}", e))?
+        .current
+        .zone();
+    if let Some(scoring) = &scoring {
+        scoring.validate()?;
+    }
+    let config = config.unwrap_or_default();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let scoring = match (scoring, sleep_records) {
+            (Some(scoring), None) => scoring,
+            (None, Some(records)) => sleep_regularity::records_to_epochs(&records, zone)?,
+            _ => return Err("Provide either scored epochs or sleep records".to_string()),
+        };
+        chronotype(&scoring, &config, zone)
+    })
+    .await
+    .map_err(|e| format!("Failed to calculate chronotype: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::sleep_regularity::SleepState;
+
+    /// 2026-03-02 00:00 UTC, a Monday.
+    const START: i64 = 1_772_409_600_000;
+    const HOUR: i64 = 3_600_000;
+
+    /// One week of nights from Monday evening: work nights 23:00-07:00, free
+    /// nights (Friday and Saturday evening) from `free` hours.
+    fn week(free: (i64, i64)) -> ScoredEpochs {
+        let records: Vec<SleepRecord> = (0..7)
+            .map(|day| {
+                let (from, to) = if day == 4 || day == 5 { free } else { (23, 31) };
+                let midnight = START + day * 86_400_000;
+                SleepRecord { start: midnight + from * HOUR, end: midnight + to * HOUR, state: SleepState::Asleep }
+            })
+            .collect();
+        sleep_regularity::records_to_epochs(&records, Tz::UTC).unwrap()
+    }
+
+    fn close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "{} != {}", actual, expected);
+    }
+
+    #[test]
+    fn corrects_free_day_midsleep_for_oversleep() {
+        // Free nights 01:00-11:00
+        let result = chronotype(&week((25, 35)), &ChronotypeConfig::default(), Tz::UTC).unwrap();
+        assert_eq!((result.work_nights, result.free_nights), (5, 2));
+        close(result.msw, 3.0);
+        close(result.msf, 6.0);
+        close(result.social_jet_lag, 3.0);
+        close(result.sleep_duration_work, 8.0);
+        close(result.sleep_duration_free, 10.0);
+        let week = (5.0 * 8.0 + 2.0 * 10.0) / 7.0;
+        close(result.sleep_duration_week, week);
+        close(result.msf_sc, 6.0 - (10.0 - week) / 2.0);
+        assert_eq!(result.category, ChronotypeCategory::Late);
+        close(result.natural_sleep_onset, result.msf_sc - week / 2.0);
+        close(result.natural_wake_time, result.msf_sc + week / 2.0);
+    }
+
+    #[test]
+    fn shorter_free_sleep_is_not_corrected() {
+        // Free nights 00:00-07:00: later, but shorter than work nights
+        let records = week((24, 31));
+        let result = chronotype(&records, &ChronotypeConfig::default(), Tz::UTC).unwrap();
+        close(result.msf, 3.5);
+        close(result.msf_sc, result.msf);
+        close(result.social_jet_lag, 0.5);
+        assert_eq!(result.category, ChronotypeCategory::Intermediate);
+    }
+
+    #[test]
+    fn weekly_duration_follows_the_nights_recorded() {
+        // Wednesday 4 March off: Tuesday evening becomes a free night
+        let config = ChronotypeConfig {
+            free_dates: vec![NaiveDate::from_ymd_opt(2026, 3, 4).unwrap()],
+            ..Default::default()
+        };
+        let result = chronotype(&week((25, 35)), &config, Tz::UTC).unwrap();
+        assert_eq!((result.work_nights, result.free_nights), (4, 3));
+        // The holiday night kept work-night timing, pulling the free means back
+        close(result.sleep_duration_free, (8.0 + 2.0 * 10.0) / 3.0);
+        close(result.sleep_duration_week, (4.0 * 8.0 + 8.0 + 2.0 * 10.0) / 7.0);
+        close(result.msf, (3.0 + 2.0 * 6.0) / 3.0);
+
+        // Working Saturday 7 March turns Friday evening into a work night
+        let config = ChronotypeConfig {
+            work_dates: vec![NaiveDate::from_ymd_opt(2026, 3, 7).unwrap()],
+            ..Default::default()
+        };
+        let result = chronotype(&week((25, 35)), &config, Tz::UTC).unwrap();
+        assert_eq!((result.work_nights, result.free_nights), (6, 1));
+    }
+
+    #[test]
+    fn midsleep_wraps_past_midnight() {
+        // Work nights 23:00-07:00, free nights 21:00-01:00 centre before midnight
+        let result = chronotype(&week((21, 25)), &ChronotypeConfig::default(), Tz::UTC).unwrap();
+        close(result.msf, 23.0);
+        close(result.social_jet_lag, -4.0);
+        assert_eq!(result.category, ChronotypeCategory::ExtremelyEarly);
+    }
+
+    #[test]
+    fn categories_follow_the_msf_sc_bands() {
+        let at = |hour: f64| ChronotypeCategory::from_minutes_after_noon((hour + 12.0) * 60.0);
+        assert_eq!(at(1.99), ChronotypeCategory::ExtremelyEarly);
+        assert_eq!(at(2.0), ChronotypeCategory::Early);
+        assert_eq!(at(3.49), ChronotypeCategory::Early);
+        assert_eq!(at(3.5), ChronotypeCategory::Intermediate);
+        assert_eq!(at(4.99), ChronotypeCategory::Intermediate);
+        assert_eq!(at(5.0), ChronotypeCategory::Late);
+        assert_eq!(at(6.49), ChronotypeCategory::Late);
+        assert_eq!(at(6.5), ChronotypeCategory::ExtremelyLate);
+        assert_eq!(at(11.0), ChronotypeCategory::ExtremelyLate);
+    }
+
+    #[test]
+    fn needs_both_kinds_of_night() {
+        let config = ChronotypeConfig { workday_mask: [true; 7], ..Default::default() };
+        assert!(chronotype(&week((25, 35)), &config, Tz::UTC).is_err());
+    }
+}
@@ -0,0 +1,541 @@
+// Circadian phase markers from overnight wrist temperature and heart rate: the
+// core body temperature minimum (CBTmin) and a DLMO proxy. Each signal gets a
+// 24-hour cosine fitted alongside activity and sleep regressors, which
+// "purifies" it of masking (Waterhouse et al. 1999) before its phase is read.
+use chrono::{NaiveTime, TimeZone, Timelike, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+use crate::settings::SettingsState;
+use crate::sleep_metrics::ScoredEpochs;
+use crate::sleep_scoring::EpochLabel;
+use crate::stats;
+use crate::timeseries::TimeSeries;
+use crate::timezone;
+
+const HOUR_MS: f64 = 3_600_000.0;
+const OMEGA: f64 = 2.0 * PI / 24.0;
+/// Typical interval from melatonin onset to CBTmin in entrained adults
+/// (Benloucif et al. 2005).
+const DLMO_TO_CBT_MIN_HOURS: f64 = 7.0;
+/// Spread of that interval between people, added to the DLMO uncertainty.
+const DLMO_TO_CBT_MIN_SD_HOURS: f64 = 1.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MarkerSignal {
+    /// Distal skin temperature, which peaks as core temperature bottoms out.
+    WristTemperature,
+    /// Heart rate, whose circadian trough coincides with CBTmin.
+    HeartRate,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PhaseMarkerConfig {
+    pub confidence_level: f64,
+    /// Activity is averaged over this long before each sample, since both
+    /// signals respond to movement with some delay.
+    pub activity_lag_minutes: f64,
+    /// Shorter recordings do not constrain a 24-hour rhythm.
+    pub min_span_hours: f64,
+}
+
+impl Default for PhaseMarkerConfig {
+    fn default() -> Self {
+        Self {
+            confidence_level: 0.95,
+            activity_lag_minutes: 30.0,
+            min_span_hours: 6.0,
+        }
+    }
+}
+
+impl PhaseMarkerConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(self.confidence_level > 0.0 && self.confidence_level < 1.0) {
+            return Err("Confidence level must be between 0 and 1".to_string());
+        }
+        if self.activity_lag_minutes <= 0.0 || self.min_span_hours <= 0.0 {
+            return Err("Activity lag and minimum span must be positive".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// CBTmin as estimated from one signal.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SignalPhase {
+    pub signal: MarkerSignal,
+    /// Epoch ms.
+    pub cbt_min: i64,
+    pub cbt_min_ci: [i64; 2],
+    pub amplitude: f64,
+    /// Probability of a rhythm this strong by chance, allowing for
+    /// autocorrelated residuals.
+    pub p_value: f64,
+    /// False when the rhythm is not significant; the signal is then left out
+    /// of the combined estimate.
+    pub rhythm_detected: bool,
+    /// Change in the signal per unit of recent activity, `None` when activity
+    /// was not given or never varied.
+    pub activity_effect: Option<f64>,
+    /// Change in the signal while asleep, likewise.
+    pub sleep_effect: Option<f64>,
+    pub samples: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CircadianPhaseMarkers {
+    /// Epoch ms, the occurrence nearest the middle of the last day of data.
+    pub cbt_min: i64,
+    pub cbt_min_ci: [i64; 2],
+    /// Local clock hours in [0, 24).
+    pub cbt_min_clock: f64,
+    /// Dim-light melatonin onset, inferred from CBTmin.
+    pub dlmo: i64,
+    pub dlmo_ci: [i64; 2],
+    pub dlmo_clock: f64,
+    pub signals: Vec<SignalPhase>,
+    pub confidence_level: f64,
+}
+
+/// Where the masking regressors come from.
+struct Masking<'a> {
+    activity: Option<&'a TimeSeries>,
+    /// Prefix sums of activity values.
+    activity_sums: Vec<f64>,
+    sleep: Option<&'a ScoredEpochs>,
+    lag_ms: i64,
+}
+
+impl Masking<'_> {
+    /// Mean activity over the lag window ending at each timestamp, 0 where no
+    /// samples fall in it.
+    fn activity_column(&self, timestamps: &[i64]) -> Option<Vec<f64>> {
+        let activity = self.activity?;
+        let column = timestamps.iter().map(|&timestamp| {
+            let lo = activity.timestamps.partition_point(|&t| t <= timestamp - self.lag_ms);
+            let hi = activity.timestamps.partition_point(|&t| t <= timestamp);
+            if hi > lo { (self.activity_sums[hi] - self.activity_sums[lo]) / (hi - lo) as f64 } else { 0.0 }
+        });
+        varying(column.collect())
+    }
+
+    /// 1 inside a sleep epoch, otherwise 0.
+    fn sleep_column(&self, timestamps: &[i64]) -> Option<Vec<f64>> {
+        let sleep = self.sleep?;
+        let epoch_ms = sleep.epoch_seconds as i64 * 1000;
+        let column = timestamps.iter().map(|&timestamp| {
+            let i = sleep.epoch_starts.partition_point(|&t| t <= timestamp);
+            let asleep =
+                i > 0 && timestamp < sleep.epoch_starts[i - 1] + epoch_ms && sleep.labels[i - 1] == EpochLabel::Sleep;
+            if asleep { 1.0 } else { 0.0 }
+        });
+        varying(column.collect())
+    }
+}
+
+/// A regressor that never changes, such as sleep outside the recording,
+/// cannot be separated from the mean and is left out.
+fn varying(column: Vec<f64>) -> Option<Vec<f64>> {
+    column.iter().any(|v| *v != column[0]).then_some(column)
+}
+
+/// Occurrence of `phase_hours` after `reference` (mod 24) nearest `target`.
+fn nearest_occurrence(reference: i64, phase_hours: f64, target: i64) -> f64 {
+    let first = reference as f64 + phase_hours * HOUR_MS;
+    first + ((target as f64 - first) / (24.0 * HOUR_MS)).round() * 24.0 * HOUR_MS
+}
+
+fn fit_signal(
+    signal: MarkerSignal,
+    series: &TimeSeries,
+    masking: &Masking,
+    reference: i64,
+    target: i64,
+    config: &PhaseMarkerConfig,
+) -> Result<SignalPhase, String> {
+    let activity = masking.activity_column(&series.timestamps);
+    let asleep = masking.sleep_column(&series.timestamps);
+    let rows: Vec<Vec<f64>> = series
+        .timestamps
+        .iter()
+        .enumerate()
+        .map(|(i, &timestamp)| {
+            let hours = (timestamp - reference) as f64 / HOUR_MS;
+            let mut row = vec![1.0, (OMEGA * hours).cos(), (OMEGA * hours).sin()];
+            row.extend(activity.as_ref().map(|column| column[i]));
+            row.extend(asleep.as_ref().map(|column| column[i]));
+            row
+        })
+        .collect();
+    let n = rows.len();
+    let p = rows[0].len();
+    if n <= p + 2 {
+        return Err(format!("{:?} needs more than {} samples, got {}", signal, p + 2, n));
+    }
+
+    let mut xtx = vec![vec![0.0; p]; p];
+    let mut xty = vec![0.0; p];
+    for (row, &y) in rows.iter().zip(&series.values) {
+        for i in 0..p {
+            xty[i] += row[i] * y;
+            for j in 0..p {
+                xtx[i][j] += row[i] * row[j];
+            }
+        }
+    }
+    let inverse = stats::invert(&xtx)
+        .ok_or_else(|| format!("{:?} cannot be separated from activity and sleep", signal))?;
+    let beta: Vec<f64> = inverse.iter().map(|row| row.iter().zip(&xty).map(|(a, b)| a * b).sum()).collect();
+    let residuals: Vec<f64> = rows
+        .iter()
+        .zip(&series.values)
+        .map(|(row, y)| y - row.iter().zip(&beta).map(|(x, b)| x * b).sum::<f64>())
+        .collect();
+
+    // Neighbouring residuals are correlated, so there are fewer independent
+    // samples than rows; inflate the covariance by (1 + ρ) / (1 - ρ)
+    let rss: f64 = residuals.iter().map(|r| r * r).sum();
+    let lag1: f64 = residuals.windows(2).map(|pair| pair[0] * pair[1]).sum();
+    let rho = if rss > 0.0 { (lag1 / rss).clamp(0.0, 0.95) } else { 0.0 };
+    let inflation = (1.0 + rho) / (1.0 - rho);
+    let df = (n as f64 / inflation - p as f64).max(1.0);
+    let sigma2 = rss / (n - p) as f64 * inflation;
+    let covariance = |i: usize, j: usize| sigma2 * inverse[i][j];
+
+    let (b, g) = (beta[1], beta[2]);
+    let (var_b, var_g, cov_bg) = (covariance(1, 1), covariance(2, 2), covariance(1, 2));
+    let amplitude = b.hypot(g);
+    let a2 = amplitude * amplitude;
+    let se_phase_hours = if a2 > 0.0 {
+        ((g * g * var_b + b * b * var_g - 2.0 * b * g * cov_bg) / (a2 * a2)).max(0.0).sqrt() / OMEGA
+    } else {
+        12.0
+    };
+    let det = var_b * var_g - cov_bg * cov_bg;
+    let f = if det > 0.0 { (b * b * var_g - 2.0 * b * g * cov_bg + g * g * var_b) / det / 2.0 } else { 0.0 };
+    let p_value = stats::f_survival(f, 2.0, df);
+
+    let peak_hours = g.atan2(b).rem_euclid(2.0 * PI) / OMEGA;
+    let minimum_hours = match signal {
+        MarkerSignal::WristTemperature => peak_hours,
+        MarkerSignal::HeartRate => peak_hours + 12.0,
+    };
+    let cbt_min = nearest_occurrence(reference, minimum_hours, target);
+    let half_width = stats::student_t_quantile(0.5 + config.confidence_level / 2.0, df) * se_phase_hours * HOUR_MS;
+
+    Ok(SignalPhase {
+        signal,
+        cbt_min: cbt_min.round() as i64,
+        cbt_min_ci: [(cbt_min - half_width).round() as i64, (cbt_min + half_width).round() as i64],
+        amplitude,
+        p_value,
+        rhythm_detected: p_value < 1.0 - config.confidence_level,
+        activity_effect: activity.is_some().then(|| beta[3]),
+        sleep_effect: asleep.is_some().then(|| beta[p - 1]),
+        samples: n,
+    })
+}
+
+fn clock_hours(timestamp: i64, zone: Tz) -> f64 {
+    let local = Utc.timestamp_millis_opt(timestamp).single().unwrap_or_default().with_timezone(&zone);
+    local.num_seconds_from_midnight() as f64 / 3600.0
+}
+
+pub fn circadian_phase_markers(
+    temperature: Option<&TimeSeries>,
+    heart_rate: Option<&TimeSeries>,
+    activity: Option<&TimeSeries>,
+    sleep: Option<&ScoredEpochs>,
+    config: &PhaseMarkerConfig,
+    zone: Tz,
+) -> Result<CircadianPhaseMarkers, String> {
+    let inputs: Vec<(MarkerSignal, &TimeSeries)> = [
+        (MarkerSignal::WristTemperature, temperature),
+        (MarkerSignal::HeartRate, heart_rate),
+    ]
+    .into_iter()
+    .filter_map(|(signal, series)| series.filter(|s| !s.is_empty()).map(|s| (signal, s)))
+    .collect();
+    if inputs.is_empty() {
+        return Err("Provide wrist temperature or heart rate".to_string());
+    }
+    for (signal, series) in &inputs {
+        let span_hours = series.span_minutes() / 60.0;
+        if span_hours < config.min_span_hours {
+            return Err(format!(
+                "{:?} spans {:.1} h, less than the {} h needed",
+                signal, span_hours, config.min_span_hours
+            ));
+        }
+    }
+
+    let first = inputs.iter().map(|(_, s)| s.timestamps[0]).min().unwrap_or_default();
+    let last = inputs.iter().map(|(_, s)| s.timestamps[s.len() - 1]).max().unwrap_or_default();
+    let first_local = Utc.timestamp_millis_opt(first).single().unwrap_or_default().with_timezone(&zone);
+    let reference = timezone::resolve_on(zone, first_local.date_naive(), NaiveTime::MIN).timestamp_millis();
+    // Report the night in the most recent day of data
+    let target = (first.max(last - 24 * HOUR_MS as i64) + last) / 2;
+
+    let masking = Masking {
+        activity,
+        activity_sums: activity
+            .map(|a| {
+                let mut sums = vec![0.0];
+                for value in &a.values {
+                    sums.push(sums[sums.len() - 1] + value);
+                }
+                sums
+            })
+            .unwrap_or_default(),
+        sleep,
+        lag_ms: (config.activity_lag_minutes * 60_000.0) as i64,
+    };
+    let signals = inputs
+        .iter()
+        .map(|&(signal, series)| fit_signal(signal, series, &masking, reference, target, config))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Inverse-variance weighting of the signals that show a rhythm
+    let detected: Vec<&SignalPhase> = signals.iter().filter(|s| s.rhythm_detected).collect();
+    if detected.is_empty() {
+        return Err("Neither signal shows a significant 24-hour rhythm".to_string());
+    }
+    let z = stats::student_t_quantile(0.5 + config.confidence_level / 2.0, 1e6);
+    let weights: Vec<f64> = detected
+        .iter()
+        .map(|s| {
+            let se = (s.cbt_min_ci[1] - s.cbt_min_ci[0]) as f64 / 2.0 / z;
+            1.0 / se.max(1.0).powi(2)
+        })
+        .collect();
+    let total: f64 = weights.iter().sum();
+    // Relative to the first estimate so occurrences a day apart cannot average out
+    let anchor = detected[0].cbt_min as f64;
+    let offset: f64 = detected
+        .iter()
+        .zip(&weights)
+        .map(|(s, w)| {
+            let difference = s.cbt_min as f64 - anchor;
+            w * (difference - (difference / (24.0 * HOUR_MS)).round() * 24.0 * HOUR_MS)
+        })
+        .sum::<f64>()
+        / total;
+    let cbt_min = anchor + offset;
+    let se = total.sqrt().recip();
+    let dlmo = cbt_min - DLMO_TO_CBT_MIN_HOURS * HOUR_MS;
+    let dlmo_se = se.hypot(DLMO_TO_CBT_MIN_SD_HOURS * HOUR_MS);
+    let bounds = |center: f64, se: f64| [(center - z * se).round() as i64, (center + z * se).round() as i64];
+
+    Ok(CircadianPhaseMarkers {
+        cbt_min: cbt_min.round() as i64,
+        cbt_min_ci: bounds(cbt_min, se),
+        cbt_min_clock: clock_hours(cbt_min.round() as i64, zone),
+        dlmo: dlmo.round() as i64,
+        dlmo_ci: bounds(dlmo, dlmo_se),
+        dlmo_clock: clock_hours(dlmo.round() as i64, zone),
+        signals,
+        confidence_level: config.confidence_level,
+    })
+}
+
+/// Activity and sleep scoring are optional; with them the rhythm is separated
+/// from movement and sleep effects rather than read off the raw signals.
+#[tauri::command]
+pub async fn estimate_circadian_phase(
+    settings_state: tauri::State<'_, SettingsState>,
+    temperature: Option<TimeSeries>,
+    heart_rate: Option<TimeSeries>,
+    activity: Option<TimeSeries>,
+    sleep: Option<ScoredEpochs>,
+    config: Option<PhaseMarkerConfig>,
+) -> Result<CircadianPhaseMarkers, String> {
+    let zone = settings_state
+        .lock()
+        .map_err(|e| format!("Failed to lock settings: {}", e))?
+        .current
+        .zone();
+    let config = config.unwrap_or_default();
+    config.validate()?;
+    for series in [&temperature, &heart_rate, &activity].into_iter().flatten() {
+        series.validate()?;
+    }
+    if let Some(sleep) = &sleep {
+        sleep.validate()?;
+    }
+
+    tauri::async_runtime::spawn_blocking(move || {
+        circadian_phase_markers(
+            temperature.as_ref(),
+            heart_rate.as_ref(),
+            activity.as_ref(),
+            sleep.as_ref(),
+            &config,
+            zone,
+        )
+    })
+    .await
+    .map_err(|e| format!("Failed to estimate circadian phase: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2026-03-02 00:00 UTC.
+    const START: i64 = 1_772_409_600_000;
+    const SAMPLE_MS: i64 = 600_000;
+
+    /// Two days sampled every 10 minutes; `signal` takes hours since the start.
+    fn sampled(mut signal: impl FnMut(f64) -> f64) -> TimeSeries {
+        let timestamps: Vec<i64> = (0..288).map(|i| START + i * SAMPLE_MS).collect();
+        let values = timestamps.iter().map(|&t| signal((t - START) as f64 / HOUR_MS)).collect();
+        TimeSeries { timestamps, values }
+    }
+
+    /// Uniform noise in [-0.5, 0.5).
+    fn noise(seed: u64) -> impl FnMut() -> f64 {
+        let mut seed = seed;
+        move || {
+            seed = seed.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1_442_695_040_888_963_407);
+            (seed >> 11) as f64 / (1u64 << 53) as f64 - 0.5
+        }
+    }
+
+    /// 1 between 23:00 and 07:00.
+    fn night(hours: f64) -> f64 {
+        if !(7.0..23.0).contains(&hours.rem_euclid(24.0)) { 1.0 } else { 0.0 }
+    }
+
+    fn sleep_epochs() -> ScoredEpochs {
+        let epoch_starts: Vec<i64> = (0..48 * 60).map(|i| START + i * 60_000).collect();
+        let labels = epoch_starts
+            .iter()
+            .map(|&t| if night((t - START) as f64 / HOUR_MS) > 0.0 { EpochLabel::Sleep } else { EpochLabel::Wake })
+            .collect();
+        ScoredEpochs { epoch_seconds: 60, epoch_starts, labels }
+    }
+
+    fn within(ci: [i64; 2], timestamp: i64) -> bool {
+        ci[0] <= timestamp && timestamp <= ci[1]
+    }
+
+    /// 04:30 on 3 March, the occurrence nearest the middle of the last day.
+    const EXPECTED_MIN: i64 = START + 28 * 3_600_000 + 1_800_000;
+
+    #[test]
+    fn finds_the_minimum_of_a_clean_rhythm() {
+        let mut n = noise(1);
+        // Wrist temperature peaks at CBTmin; heart rate bottoms out with it
+        let temperature = sampled(|h| 34.0 + 0.3 * (OMEGA * (h - 4.5)).cos() + 0.1 * n());
+        let heart_rate = sampled(|h| 60.0 - 5.0 * (OMEGA * (h - 4.5)).cos() + 2.0 * n());
+        let config = PhaseMarkerConfig::default();
+        let markers = circadian_phase_markers(Some(&temperature), Some(&heart_rate), None, None, &config, Tz::UTC)
+            .unwrap();
+
+        for signal in &markers.signals {
+            assert!(signal.rhythm_detected, "{:?}", signal);
+            assert!(within(signal.cbt_min_ci, EXPECTED_MIN), "{:?}", signal);
+        }
+        assert!((markers.signals[0].amplitude - 0.3).abs() < 0.03);
+        assert!((markers.signals[1].amplitude - 5.0).abs() < 0.5);
+        assert!(within(markers.cbt_min_ci, EXPECTED_MIN), "{:?}", markers.cbt_min_ci);
+        assert!((markers.cbt_min_clock - 4.5).abs() < 0.1, "{}", markers.cbt_min_clock);
+        // The combined interval is narrower than either signal's
+        let width = |ci: [i64; 2]| ci[1] - ci[0];
+        assert!(markers.signals.iter().all(|s| width(markers.cbt_min_ci) < width(s.cbt_min_ci)));
+        assert_eq!(markers.dlmo, markers.cbt_min - 7 * 3_600_000);
+        assert!((markers.dlmo_clock - 21.5).abs() < 0.1, "{}", markers.dlmo_clock);
+        assert!(width(markers.dlmo_ci) > width(markers.cbt_min_ci));
+    }
+
+    #[test]
+    fn sleep_masking_is_separated_from_the_rhythm() {
+        let mut n = noise(2);
+        // Warmer wrists while asleep, centred on 03:00 rather than the 04:30 minimum
+        let temperature = sampled(|h| 34.0 + 0.3 * (OMEGA * (h - 4.5)).cos() + 0.8 * night(h) + 0.1 * n());
+        let config = PhaseMarkerConfig::default();
+
+        let raw = circadian_phase_markers(Some(&temperature), None, None, None, &config, Tz::UTC).unwrap();
+        assert!(!within(raw.cbt_min_ci, EXPECTED_MIN), "{:?}", raw.cbt_min_ci);
+        assert_eq!(raw.signals[0].sleep_effect, None);
+
+        let sleep = sleep_epochs();
+        let purified = circadian_phase_markers(Some(&temperature), None, None, Some(&sleep), &config, Tz::UTC).unwrap();
+        assert!(within(purified.cbt_min_ci, EXPECTED_MIN), "{:?}", purified.cbt_min_ci);
+        assert!((purified.signals[0].sleep_effect.unwrap() - 0.8).abs() < 0.05);
+        assert!((purified.signals[0].amplitude - 0.3).abs() < 0.03);
+    }
+
+    #[test]
+    fn activity_masking_is_separated_from_the_rhythm() {
+        // Busy from 08:00 to 14:00, pushing heart rate up in the morning
+        let activity = sampled(|h| if (8.0..14.0).contains(&h.rem_euclid(24.0)) { 100.0 } else { 0.0 });
+        // Mean over the 30-minute window ending at each sample, as the fit sees it
+        let lagged = |h: f64| {
+            let i = (h * 6.0).round() as usize;
+            activity.values[i.saturating_sub(2)..=i].iter().sum::<f64>() / 3.0
+        };
+        let mut n = noise(3);
+        let heart_rate = sampled(|h| 60.0 - 5.0 * (OMEGA * (h - 4.5)).cos() + 0.2 * lagged(h) + 2.0 * n());
+        let config = PhaseMarkerConfig::default();
+
+        let raw = circadian_phase_markers(None, Some(&heart_rate), None, None, &config, Tz::UTC).unwrap();
+        assert!(!within(raw.cbt_min_ci, EXPECTED_MIN), "{:?}", raw.cbt_min_ci);
+
+        let purified =
+            circadian_phase_markers(None, Some(&heart_rate), Some(&activity), None, &config, Tz::UTC).unwrap();
+        assert!(within(purified.cbt_min_ci, EXPECTED_MIN), "{:?}", purified.cbt_min_ci);
+        assert!((purified.signals[0].activity_effect.unwrap() - 0.2).abs() < 0.02);
+    }
+
+    #[test]
+    fn noise_shows_no_rhythm() {
+        let mut n = noise(4);
+        let heart_rate = sampled(|_| 60.0 + 2.0 * n());
+        let config = PhaseMarkerConfig::default();
+        assert!(circadian_phase_markers(None, Some(&heart_rate), None, None, &config, Tz::UTC).is_err());
+
+        // Alongside a real rhythm, the noisy signal is reported but left out
+        let mut n = noise(5);
+        let temperature = sampled(|h| 34.0 + 0.3 * (OMEGA * (h - 4.5)).cos() + 0.1 * n());
+        let markers =
+            circadian_phase_markers(Some(&temperature), Some(&heart_rate), None, None, &config, Tz::UTC).unwrap();
+        assert!(markers.signals[0].rhythm_detected);
+        assert!(!markers.signals[1].rhythm_detected, "{:?}", markers.signals[1]);
+        assert!(markers.signals[1].p_value > 0.05);
+        assert_eq!(markers.cbt_min, markers.signals[0].cbt_min);
+    }
+
+    #[test]
+    fn combines_estimates_across_midnight() {
+        // Minima at 23:40 and 00:20 fall nearly a day apart around the noon target
+        let mut n = noise(6);
+        let temperature = sampled(|h| 34.0 + 0.3 * (OMEGA * (h - (23.0 + 2.0 / 3.0))).cos() + 0.1 * n());
+        let heart_rate = sampled(|h| 60.0 - 5.0 * (OMEGA * (h - 1.0 / 3.0)).cos() + 5.0 / 3.0 * n());
+        let config = PhaseMarkerConfig::default();
+        let markers = circadian_phase_markers(Some(&temperature), Some(&heart_rate), None, None, &config, Tz::UTC)
+            .unwrap();
+
+        let [temperature_min, heart_rate_min] = [markers.signals[0].cbt_min, markers.signals[1].cbt_min];
+        assert!((temperature_min - heart_rate_min).abs() > 20 * 3_600_000);
+        // Halfway between them is midnight, not noon
+        let distance = markers.cbt_min_clock.min(24.0 - markers.cbt_min_clock);
+        assert!(distance < 0.2, "{}", markers.cbt_min_clock);
+        let midnight = START + 48 * 3_600_000;
+        assert!(within(markers.cbt_min_ci, midnight), "{:?}", markers.cbt_min_ci);
+    }
+
+    #[test]
+    fn rejects_short_or_missing_input() {
+        let config = PhaseMarkerConfig::default();
+        assert!(circadian_phase_markers(None, None, None, None, &config, Tz::UTC).is_err());
+        let short = TimeSeries { timestamps: vec![START, START + 3_600_000], values: vec![34.0, 34.5] };
+        assert!(circadian_phase_markers(Some(&short), None, None, None, &config, Tz::UTC).is_err());
+        assert!(PhaseMarkerConfig { confidence_level: 1.0, ..config.clone() }.validate().is_err());
+        assert!(PhaseMarkerConfig { activity_lag_minutes: 0.0, ..config }.validate().is_err());
+    }
+}
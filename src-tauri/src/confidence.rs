@@ -0,0 +1,280 @@
+// How much to trust the current phase reading, and why.
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+
+use crate::anchor::{AnchorMode, AnchorState, CycleAnchor};
+use crate::phase_estimator::{EstimatorState, PhaseEstimate};
+use crate::settings::SettingsState;
+use crate::timezone;
+
+/// Heart rate younger than this counts as fully fresh, minutes.
+const FRESH_HEART_RATE_MINUTES: f64 = 5.0;
+/// Heart rate older than this no longer supports the reading, minutes.
+const STALE_HEART_RATE_MINUTES: f64 = 60.0;
+/// Samples needed before the fit statistic means anything.
+const MIN_FIT_SAMPLES: usize = 10;
+/// Days of history for a fully personalised model.
+const FULL_HISTORY_DAYS: usize = 14;
+/// Factors scoring below this are reported as lowering confidence.
+const LOWERING_THRESHOLD: f64 = 0.7;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FactorKind {
+    HeartRateRecency,
+    PhaseFit,
+    WakeAnchor,
+    History,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConfidenceFactor {
+    pub kind: FactorKind,
+    /// 0..1, where 1 fully supports the reading.
+    pub score: f64,
+    pub weight: f64,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConfidenceReport {
+    pub confidence: f64,
+    pub factors: Vec<ConfidenceFactor>,
+    /// Factors that pulled the score down, largest loss first.
+    pub lowered_by: Vec<FactorKind>,
+}
+
+/// Days of data behind the personal model that the backend cannot see
+/// itself, e.g. an imported Health export.
+#[derive(Debug, Default)]
+pub struct PersonalHistory {
+    pub days: usize,
+}
+
+pub type HistoryState = Arc<Mutex<PersonalHistory>>;
+
+pub fn assess(
+    estimate: Option<&PhaseEstimate>,
+    anchor: &CycleAnchor,
+    history_days: usize,
+    now: DateTime<Tz>,
+) -> ConfidenceReport {
+    let factors = vec![
+        heart_rate_recency(estimate, now.with_timezone(&Utc)),
+        phase_fit(estimate),
+        wake_anchor(anchor, now),
+        history(estimate, history_days),
+    ];
+
+    let total_weight: f64 = factors.iter().map(|f| f.weight).sum();
+    let confidence = factors.iter().map(|f| f.score * f.weight).sum::<f64>() / total_weight;
+
+    let mut lowering: Vec<&ConfidenceFactor> = factors.iter().filter(|f| f.score < LOWERING_THRESHOLD).collect();
+    lowering.sort_by(|a, b| {
+        let loss_a = (1.0 - a.score) * a.weight;
+        let loss_b = (1.0 - b.score) * b.weight;
+        loss_b.total_cmp(&loss_a)
+    });
+    let lowered_by = lowering.iter().map(|f| f.kind).collect();
+
+    ConfidenceReport {
+        confidence,
+        factors,
+        lowered_by,
+    }
+}
+
+fn heart_rate_recency(estimate: Option<&PhaseEstimate>, now: DateTime<Utc>) -> ConfidenceFactor {
+    let (score, detail) = match estimate.and_then(|e| e.last_sample) {
+        None => (0.0, "No heart rate data received".to_string()),
+        Some(last) => {
+            let age = ((now - last).num_seconds() as f64 / 60.0).max(0.0);
+            let score = 1.0
+                - ((age - FRESH_HEART_RATE_MINUTES) / (STALE_HEART_RATE_MINUTES - FRESH_HEART_RATE_MINUTES))
+                    .clamp(0.0, 1.0);
+            (score, format!("Last heart rate {:.0} min ago", age))
+        }
+    };
+    ConfidenceFactor {
+        kind: FactorKind::HeartRateRecency,
+        score,
+        weight: 0.3,
+        detail,
+    }
+}
+
+fn phase_fit(estimate: Option<&PhaseEstimate>) -> ConfidenceFactor {
+    let (score, detail) = match estimate {
+        Some(e) if e.samples >= MIN_FIT_SAMPLES => {
+            // A well-fitting model has normalized innovations around 1.
+            let excess = (e.normalized_innovation - 1.0).max(0.0);
+            let score = 1.0 / (1.0 + excess);
+            let detail = if score >= LOWERING_THRESHOLD {
+                "Recent heart rate matches the predicted phase".to_string()
+            } else {
+                "Recent heart rate does not match the predicted phase".to_string()
+            };
+            (score, detail)
+        }
+        _ => (0.5, "Not enough heart rate data to check the predicted phase".to_string()),
+    };
+    ConfidenceFactor {
+        kind: FactorKind::PhaseFit,
+        score,
+        weight: 0.3,
+        detail,
+    }
+}
+
+fn wake_anchor(anchor: &CycleAnchor, now: DateTime<Tz>) -> ConfidenceFactor {
+    let (score, detail) = if anchor.is_observed(now) {
+        (1.0, "Cycles start from today's observed wake time")
+    } else if anchor.mode == AnchorMode::WakeTime {
+        (0.7, "Cycles start from the usual wake time, not an observed one")
+    } else {
+        (0.4, "Cycles are counted from midnight, not from waking")
+    };
+    ConfidenceFactor {
+        kind: FactorKind::WakeAnchor,
+        score,
+        weight: 0.2,
+        detail: detail.to_string(),
+    }
+}
+
+fn history(estimate: Option<&PhaseEstimate>, reported_days: usize) -> ConfidenceFactor {
+    let observed = estimate.map_or(0, |e| e.days_observed);
+    let days = observed.max(reported_days);
+    ConfidenceFactor {
+        kind: FactorKind::History,
+        score: (days as f64 / FULL_HISTORY_DAYS as f64).min(1.0),
+        weight: 0.2,
+        detail: format!("{} of {} days of history", days.min(FULL_HISTORY_DAYS), FULL_HISTORY_DAYS),
+    }
+}
+
+#[tauri::command]
+pub fn get_confidence(
+    settings_state: tauri::State<'_, SettingsState>,
+    anchor_state: tauri::State<'_, AnchorState>,
+    estimator_state: tauri::State<'_, EstimatorState>,
+    history_state: tauri::State<'_, HistoryState>,
+) -> Result<ConfidenceReport, String> {
+    let zone = settings_state
+        .lock()
+        .map_err(|e| format!("Failed to lock settings: {}", e))?
+        .current
+        .zone();
+    let now = timezone::now_in(zone);
+    let anchor = anchor_state.lock().map_err(|e| format!("Failed to lock anchor: {}", e))?.clone();
+    let estimate = estimator_state
+        .lock()
+        .map_err(|e| format!("Failed to lock estimator: {}", e))?
+        .estimate(now.with_timezone(&Utc));
+    let history_days = history_state.lock().map_err(|e| format!("Failed to lock history: {}", e))?.days;
+    Ok(assess(estimate.as_ref(), &anchor, history_days, now))
+}
+
+/// Lets the frontend report how many days of imported data back its personal model.
+#[tauri::command]
+pub fn set_history_days(history_state: tauri::State<'_, HistoryState>, days: usize) -> Result<(), String> {
+    let mut history = history_state.lock().map_err(|e| format!("Failed to lock history: {}", e))?;
+    history.days = days;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, NaiveTime, TimeZone};
+
+    fn now() -> DateTime<Tz> {
+        Tz::UTC.with_ymd_and_hms(2026, 3, 2, 9, 0, 0).unwrap()
+    }
+
+    fn estimate(last_sample_minutes_ago: i64, normalized_innovation: f64, days_observed: usize) -> PhaseEstimate {
+        PhaseEstimate {
+            cycle_fraction: 0.3,
+            period_minutes: 90.0,
+            phase_sd_minutes: 3.0,
+            period_sd_minutes: 1.0,
+            samples: 200,
+            last_sample: Some((now() - Duration::minutes(last_sample_minutes_ago)).with_timezone(&Utc)),
+            heart_rate: Some(70.0),
+            normalized_innovation,
+            days_observed,
+            locked: true,
+        }
+    }
+
+    fn observed_wake() -> CycleAnchor {
+        let mut anchor = CycleAnchor::new(AnchorMode::WakeTime, NaiveTime::from_hms_opt(7, 0, 0).unwrap());
+        anchor.detected_wake = Some(Utc.with_ymd_and_hms(2026, 3, 2, 6, 30, 0).unwrap());
+        anchor
+    }
+
+    fn score(report: &ConfidenceReport, kind: FactorKind) -> f64 {
+        report.factors.iter().find(|factor| factor.kind == kind).unwrap().score
+    }
+
+    #[test]
+    fn weights_sum_to_one_and_scores_stay_in_bounds() {
+        let cases = [
+            assess(None, &CycleAnchor::new(AnchorMode::Midnight, NaiveTime::MIN), 0, now()),
+            assess(Some(&estimate(0, 1.0, 30)), &observed_wake(), 100, now()),
+            assess(Some(&estimate(-5, 50.0, 0)), &observed_wake(), 0, now()),
+        ];
+        for report in &cases {
+            let total: f64 = report.factors.iter().map(|factor| factor.weight).sum();
+            assert!((total - 1.0).abs() < 1e-9);
+            assert!(report.factors.iter().all(|factor| (0.0..=1.0).contains(&factor.score)));
+            assert!((0.0..=1.0).contains(&report.confidence));
+        }
+    }
+
+    #[test]
+    fn no_data_scores_low() {
+        let report = assess(None, &CycleAnchor::new(AnchorMode::Midnight, NaiveTime::MIN), 0, now());
+        assert!((report.confidence - 0.23).abs() < 1e-9);
+        assert_eq!(
+            report.lowered_by,
+            [FactorKind::HeartRateRecency, FactorKind::History, FactorKind::PhaseFit, FactorKind::WakeAnchor]
+        );
+    }
+
+    #[test]
+    fn locked_estimate_with_observed_wake_is_fully_trusted() {
+        let report = assess(Some(&estimate(1, 0.9, 20)), &observed_wake(), 0, now());
+        assert!((report.confidence - 1.0).abs() < 1e-9);
+        assert!(report.lowered_by.is_empty());
+    }
+
+    #[test]
+    fn stale_heart_rate_lowers_confidence() {
+        let halfway = (FRESH_HEART_RATE_MINUTES + STALE_HEART_RATE_MINUTES) / 2.0;
+        let report = assess(Some(&estimate(halfway as i64, 1.0, 20)), &observed_wake(), 0, now());
+        assert!((score(&report, FactorKind::HeartRateRecency) - 0.5).abs() < 0.02);
+        assert_eq!(report.lowered_by, [FactorKind::HeartRateRecency]);
+
+        let report = assess(Some(&estimate(120, 1.0, 20)), &observed_wake(), 0, now());
+        assert_eq!(score(&report, FactorKind::HeartRateRecency), 0.0);
+        assert!((report.confidence - 0.7).abs() < 1e-9);
+    }
+
+    #[test]
+    fn poor_fit_and_configured_anchor_lower_confidence() {
+        let configured = CycleAnchor::new(AnchorMode::WakeTime, NaiveTime::from_hms_opt(7, 0, 0).unwrap());
+        let report = assess(Some(&estimate(0, 5.0, 20)), &configured, 0, now());
+        assert!((score(&report, FactorKind::PhaseFit) - 0.2).abs() < 1e-9);
+        assert_eq!(score(&report, FactorKind::WakeAnchor), 0.7);
+        assert_eq!(report.lowered_by, [FactorKind::PhaseFit]);
+    }
+
+    #[test]
+    fn reported_history_counts_when_larger() {
+        let report = assess(Some(&estimate(0, 1.0, 2)), &observed_wake(), 7, now());
+        assert_eq!(score(&report, FactorKind::History), 0.5);
+    }
+}
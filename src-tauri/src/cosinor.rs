@@ -0,0 +1,284 @@
+// Least-squares cosinor fits (Cornelissen 2014): a MESOR plus one cosine per
+// period, with confidence intervals and a zero-amplitude F test for each.
+use chrono::NaiveTime;
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+use crate::settings::SettingsState;
+use crate::stats;
+use crate::timeseries::TimeSeries;
+use crate::timezone;
+
+const DEFAULT_PERIOD_HOURS: f64 = 24.0;
+const DEFAULT_CONFIDENCE_LEVEL: f64 = 0.95;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CosinorComponent {
+    pub period_hours: f64,
+    pub amplitude: f64,
+    pub amplitude_ci: [f64; 2],
+    /// Cornelissen convention: radians in (-2π, 0], relative to the reference time.
+    pub acrophase_radians: f64,
+    /// Hours after the reference time at which the component peaks, in [0, period).
+    pub acrophase_hours: f64,
+    /// May extend outside [0, period). Only meaningful when the amplitude is
+    /// significantly above zero.
+    pub acrophase_ci_hours: [f64; 2],
+    pub zero_amplitude_f: f64,
+    /// Probability of an amplitude this large if the true amplitude is zero.
+    pub zero_amplitude_p: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CosinorFit {
+    /// Rhythm-adjusted mean.
+    pub mesor: f64,
+    pub mesor_ci: [f64; 2],
+    pub components: Vec<CosinorComponent>,
+    pub r_squared: f64,
+    pub adjusted_r_squared: f64,
+    pub residual_standard_error: f64,
+    /// Overall zero-amplitude test across all components.
+    pub f_statistic: f64,
+    pub p_value: f64,
+    pub samples: usize,
+    pub degrees_of_freedom: usize,
+    pub confidence_level: f64,
+    /// Local midnight (epoch ms) before the first sample; acrophases count from here.
+    pub reference_time: i64,
+}
+
+pub fn fit_cosinor(
+    series: &TimeSeries,
+    periods_hours: &[f64],
+    confidence_level: f64,
+    zone: Tz,
+) -> Result<CosinorFit, String> {
+    if periods_hours.is_empty() || periods_hours.iter().any(|p| !p.is_finite() || *p <= 0.0) {
+        return Err("Cosinor periods must be positive and finite".to_string());
+    }
+    if !(confidence_level > 0.0 && confidence_level < 1.0) {
+        return Err("Confidence level must be between 0 and 1".to_string());
+    }
+    let n = series.len();
+    let p = 1 + 2 * periods_hours.len();
+    if n <= p {
+        return Err(format!("Cosinor fit with {} parameters needs more than {} samples, got {}", p, p, n));
+    }
+
+    let first = series.time_at(0, zone);
+    let reference = timezone::resolve_on(zone, first.date_naive(), NaiveTime::MIN);
+    let reference_time = reference.timestamp_millis();
+    let omegas: Vec<f64> = periods_hours.iter().map(|period| 2.0 * PI / period).collect();
+    let design_row = |timestamp: i64| -> Vec<f64> {
+        let hours = (timestamp - reference_time) as f64 / 3_600_000.0;
+        let mut row = Vec::with_capacity(p);
+        row.push(1.0);
+        for omega in &omegas {
+            row.push((omega * hours).cos());
+            row.push((omega * hours).sin());
+        }
+        row
+    };
+
+    // Normal equations
+    let mut xtx = vec![vec![0.0; p]; p];
+    let mut xty = vec![0.0; p];
+    for (&timestamp, &y) in series.timestamps.iter().zip(&series.values) {
+        let row = design_row(timestamp);
+        for i in 0..p {
+            xty[i] += row[i] * y;
+            for j in 0..p {
+                xtx[i][j] += row[i] * row[j];
+            }
+        }
+    }
+    let inverse = stats::invert(&xtx)
+        .ok_or("Samples do not cover enough of each period to separate the components")?;
+    let beta: Vec<f64> = inverse.iter().map(|row| row.iter().zip(&xty).map(|(a, b)| a * b).sum()).collect();
+
+    let mean = series.values.iter().sum::<f64>() / n as f64;
+    let mut rss = 0.0;
+    let mut tss = 0.0;
+    for (&timestamp, &y) in series.timestamps.iter().zip(&series.values) {
+        let fitted: f64 = design_row(timestamp).iter().zip(&beta).map(|(x, b)| x * b).sum();
+        rss += (y - fitted).powi(2);
+        tss += (y - mean).powi(2);
+    }
+    let df = n - p;
+    let sigma2 = rss / df as f64;
+    let covariance = |i: usize, j: usize| sigma2 * inverse[i][j];
+    let t = stats::student_t_quantile(0.5 + confidence_level / 2.0, df as f64);
+
+    let components = periods_hours
+        .iter()
+        .zip(&omegas)
+        .enumerate()
+        .map(|(k, (&period_hours, &omega))| {
+            let (bi, gi) = (1 + 2 * k, 2 + 2 * k);
+            let (b, g) = (beta[bi], beta[gi]);
+            let (var_b, var_g, cov_bg) = (covariance(bi, bi), covariance(gi, gi), covariance(bi, gi));
+            let amplitude = b.hypot(g);
+
+            // Delta-method standard errors of amplitude and phase
+            let a2 = amplitude * amplitude;
+            let se_amplitude = if a2 > 0.0 {
+                ((b * b * var_b + g * g * var_g + 2.0 * b * g * cov_bg) / a2).max(0.0).sqrt()
+            } else {
+                var_b.max(var_g).sqrt()
+            };
+            let se_phase = if a2 > 0.0 {
+                ((g * g * var_b + b * b * var_g - 2.0 * b * g * cov_bg) / (a2 * a2)).max(0.0).sqrt()
+            } else {
+                PI
+            };
+
+            let theta = g.atan2(b).rem_euclid(2.0 * PI);
+            let acrophase_hours = theta / omega;
+            let half_width_hours = t * se_phase / omega;
+
+            // Zero-amplitude test: F = βᵀ V⁻¹ β / 2 over the (cos, sin) pair
+            let det = var_b * var_g - cov_bg * cov_bg;
+            let zero_amplitude_f = if det > 0.0 {
+                (b * b * var_g - 2.0 * b * g * cov_bg + g * g * var_b) / det / 2.0
+            } else {
+                0.0
+            };
+
+            CosinorComponent {
+                period_hours,
+                amplitude,
+                amplitude_ci: [(amplitude - t * se_amplitude).max(0.0), amplitude + t * se_amplitude],
+                acrophase_radians: if theta == 0.0 { 0.0 } else { -theta },
+                acrophase_hours,
+                acrophase_ci_hours: [acrophase_hours - half_width_hours, acrophase_hours + half_width_hours],
+                zero_amplitude_f,
+                zero_amplitude_p: stats::f_survival(zero_amplitude_f, 2.0, df as f64),
+            }
+        })
+        .collect();
+
+    let se_mesor = covariance(0, 0).max(0.0).sqrt();
+    let r_squared = if tss > 0.0 { 1.0 - rss / tss } else { 0.0 };
+    let f_statistic = if rss > 0.0 {
+        ((tss - rss) / (p - 1) as f64) / sigma2
+    } else {
+        f64::INFINITY
+    };
+
+    Ok(CosinorFit {
+        mesor: beta[0],
+        mesor_ci: [beta[0] - t * se_mesor, beta[0] + t * se_mesor],
+        components,
+        r_squared,
+        adjusted_r_squared: 1.0 - (1.0 - r_squared) * (n - 1) as f64 / df as f64,
+        residual_standard_error: sigma2.sqrt(),
+        f_statistic,
+        p_value: stats::f_survival(f_statistic, (p - 1) as f64, df as f64),
+        samples: n,
+        degrees_of_freedom: df,
+        confidence_level,
+        reference_time,
+    })
+}
+
+/// Fits one cosine per entry of `periods_hours` (24 h when omitted), e.g.
+/// `[24, 12]` for a circadian rhythm with a 12-hour harmonic.
+#[tauri::command]
+pub async fn fit_cosinor_model(
+    settings_state: tauri::State<'_, SettingsState>,
+    series: TimeSeries,
+    periods_hours: Option<Vec<f64>>,
+    confidence_level: Option<f64>,
+) -> Result<CosinorFit, String> {
+    series.validate()?;
+    let zone = settings_state
+        .lock()
+        .map_err(|e| format!("Failed to lock settings: {}", e))?
+        .current
+        .zone();
+    let periods = periods_hours.unwrap_or_else(|| vec![DEFAULT_PERIOD_HOURS]);
+    let confidence_level = confidence_level.unwrap_or(DEFAULT_CONFIDENCE_LEVEL);
+
+    tauri::async_runtime::spawn_blocking(move || fit_cosinor(&series, &periods, confidence_level, zone))
+        .await
+        .map_err(|e| format!("Failed to fit cosinor model: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY_START: i64 = 1_772_409_600_000; // 2026-03-02 00:00 UTC
+
+    /// Uniform noise in [-0.5, 0.5) from a fixed LCG.
+    fn noise(seed: &mut u64) -> f64 {
+        *seed = seed.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1_442_695_040_888_963_407);
+        (*seed >> 11) as f64 / (1u64 << 53) as f64 - 0.5
+    }
+
+    /// Three days of 15-minute samples of `signal(hours since midnight)` plus noise.
+    fn series(signal: impl Fn(f64) -> f64, noise_scale: f64) -> TimeSeries {
+        let mut seed = 7;
+        let timestamps: Vec<i64> = (0..3 * 96).map(|i| DAY_START + i * 900_000).collect();
+        let values = timestamps
+            .iter()
+            .map(|&t| signal((t - DAY_START) as f64 / 3_600_000.0) + noise_scale * noise(&mut seed))
+            .collect();
+        TimeSeries { timestamps, values }
+    }
+
+    fn cosine(mesor: f64, amplitude: f64, period: f64, peak_hour: f64) -> impl Fn(f64) -> f64 {
+        move |hours| mesor + amplitude * (2.0 * PI * (hours - peak_hour) / period).cos()
+    }
+
+    fn contains(ci: [f64; 2], value: f64) -> bool {
+        ci[0] <= value && value <= ci[1]
+    }
+
+    #[test]
+    fn recovers_mesor_amplitude_and_acrophase() {
+        let fit = fit_cosinor(&series(cosine(50.0, 10.0, 24.0, 15.0), 2.0), &[24.0], 0.95, Tz::UTC).unwrap();
+        let component = &fit.components[0];
+        assert!((fit.mesor - 50.0).abs() < 0.2 && contains(fit.mesor_ci, 50.0));
+        assert!((component.amplitude - 10.0).abs() < 0.3 && contains(component.amplitude_ci, 10.0));
+        assert!((component.acrophase_hours - 15.0).abs() < 0.1);
+        assert!(contains(component.acrophase_ci_hours, 15.0));
+        assert!((component.acrophase_radians + 2.0 * PI * 15.0 / 24.0).abs() < 0.03);
+        assert!(component.zero_amplitude_p < 1e-10);
+        assert!(fit.r_squared > 0.95);
+        assert_eq!(fit.reference_time, DAY_START);
+        assert_eq!(fit.degrees_of_freedom, fit.samples - 3);
+    }
+
+    #[test]
+    fn separates_harmonics() {
+        let signal = |hours: f64| cosine(36.5, 0.5, 24.0, 4.0)(hours) + cosine(0.0, 0.2, 12.0, 2.0)(hours);
+        let fit = fit_cosinor(&series(signal, 0.05), &[24.0, 12.0], 0.95, Tz::UTC).unwrap();
+        assert!((fit.components[0].amplitude - 0.5).abs() < 0.01);
+        assert!((fit.components[0].acrophase_hours - 4.0).abs() < 0.1);
+        assert!((fit.components[1].amplitude - 0.2).abs() < 0.01);
+        assert!((fit.components[1].acrophase_hours - 2.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn noise_has_no_significant_rhythm() {
+        let fit = fit_cosinor(&series(|_| 10.0, 1.0), &[24.0], 0.95, Tz::UTC).unwrap();
+        assert!(fit.p_value > 0.01);
+        assert!(fit.components[0].amplitude_ci[0] == 0.0 || fit.components[0].zero_amplitude_p > 0.01);
+    }
+
+    #[test]
+    fn rejects_invalid_periods_and_levels() {
+        let data = series(cosine(50.0, 10.0, 24.0, 15.0), 1.0);
+        let invalid = [vec![], vec![0.0], vec![-24.0], vec![f64::NAN], vec![f64::INFINITY], vec![24.0, f64::NEG_INFINITY]];
+        for periods in invalid {
+            assert!(fit_cosinor(&data, &periods, 0.95, Tz::UTC).is_err(), "{:?}", periods);
+        }
+        assert!(fit_cosinor(&data, &[24.0], 1.0, Tz::UTC).is_err());
+
+        let short = TimeSeries { timestamps: data.timestamps[..3].to_vec(), values: data.values[..3].to_vec() };
+        assert!(fit_cosinor(&short, &[24.0], 0.95, Tz::UTC).is_err());
+    }
+}
@@ -0,0 +1,285 @@
+// Ultradian cycle detection from activity data: smooth, find peaks, keep the
+// tall waking-hour ones and pair consecutive peaks a plausible cycle apart.
+use chrono::Timelike;
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+use crate::settings::SettingsState;
+use crate::timeseries::TimeSeries;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CycleDetectionConfig {
+    /// Width of the centred moving average applied before peak finding.
+    pub smoothing_minutes: f64,
+    /// Smoothed activity a peak must reach.
+    pub amplitude_threshold: f64,
+    /// Local hours `[start, end)` in which peaks count as waking activity.
+    pub waking_start_hour: u32,
+    pub waking_end_hour: u32,
+    /// Peaks closer than this are merged, keeping the tallest.
+    pub min_peak_gap_minutes: f64,
+    /// Consecutive peaks further apart than this do not form a cycle.
+    pub max_peak_gap_minutes: f64,
+    /// Recordings with fewer samples return no cycles, whatever their sample rate.
+    pub min_samples: usize,
+}
+
+impl Default for CycleDetectionConfig {
+    fn default() -> Self {
+        Self {
+            smoothing_minutes: 5.0,
+            amplitude_threshold: 30.0,
+            waking_start_hour: 7,
+            waking_end_hour: 22,
+            min_peak_gap_minutes: 60.0,
+            max_peak_gap_minutes: 120.0,
+            min_samples: 180,
+        }
+    }
+}
+
+impl CycleDetectionConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.smoothing_minutes < 0.0 {
+            return Err("Smoothing window must not be negative".to_string());
+        }
+        if self.waking_start_hour >= self.waking_end_hour || self.waking_end_hour > 24 {
+            return Err("Waking hours must satisfy start < end <= 24".to_string());
+        }
+        if !(self.min_peak_gap_minutes > 0.0 && self.min_peak_gap_minutes <= self.max_peak_gap_minutes) {
+            return Err("Peak gaps must satisfy 0 < min <= max".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DetectedCycle {
+    pub start: i64,
+    pub end: i64,
+    pub peak_time: i64,
+    /// Smoothed activity at the opening peak.
+    pub amplitude: f64,
+}
+
+/// Intermediate steps, for tuning the thresholds. Peaks are sample indices.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CycleDetectionDebug {
+    pub sample_interval_minutes: Option<f64>,
+    pub smoothed_activity: Vec<f64>,
+    pub candidate_peaks: Vec<usize>,
+    pub filtered_peaks: Vec<usize>,
+    pub reduced_peaks: Vec<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CycleDetection {
+    pub cycles: Vec<DetectedCycle>,
+    pub avg_duration_minutes: f64,
+    pub cycle_count: usize,
+    pub debug: CycleDetectionDebug,
+}
+
+pub fn detect_cycles(activity: &TimeSeries, config: &CycleDetectionConfig, zone: Tz) -> CycleDetection {
+    let mut debug = CycleDetectionDebug {
+        sample_interval_minutes: activity.median_interval_minutes(),
+        ..CycleDetectionDebug::default()
+    };
+    if activity.len() < config.min_samples {
+        return CycleDetection { cycles: Vec::new(), avg_duration_minutes: 0.0, cycle_count: 0, debug };
+    }
+
+    let smooth = activity.smoothed(config.smoothing_minutes);
+    let candidates = plateau_peaks(&smooth);
+
+    let filtered: Vec<usize> = candidates
+        .iter()
+        .copied()
+        .filter(|&i| {
+            let hour = activity.time_at(i, zone).hour();
+            smooth[i] >= config.amplitude_threshold
+                && hour >= config.waking_start_hour
+                && hour < config.waking_end_hour
+        })
+        .collect();
+
+    let gap_minutes = |a: usize, b: usize| (activity.timestamps[b] - activity.timestamps[a]) as f64 / 60_000.0;
+
+    // Collapse peaks closer than the minimum gap, keeping the tallest
+    let mut reduced: Vec<usize> = Vec::new();
+    for &i in &filtered {
+        match reduced.last_mut() {
+            Some(last) if gap_minutes(*last, i) < config.min_peak_gap_minutes => {
+                if smooth[i] > smooth[*last] {
+                    *last = i;
+                }
+            }
+            _ => reduced.push(i),
+        }
+    }
+
+    let cycles: Vec<DetectedCycle> = reduced
+        .windows(2)
+        .filter(|pair| {
+            let gap = gap_minutes(pair[0], pair[1]);
+            gap >= config.min_peak_gap_minutes && gap <= config.max_peak_gap_minutes
+        })
+        .map(|pair| DetectedCycle {
+            start: activity.timestamps[pair[0]],
+            end: activity.timestamps[pair[1]],
+            peak_time: activity.timestamps[pair[0]],
+            amplitude: smooth[pair[0]],
+        })
+        .collect();
+
+    let avg_duration_minutes = if cycles.is_empty() {
+        0.0
+    } else {
+        cycles.iter().map(|c| (c.end - c.start) as f64 / 60_000.0).sum::<f64>() / cycles.len() as f64
+    };
+
+    debug.smoothed_activity = smooth;
+    debug.candidate_peaks = candidates;
+    debug.filtered_peaks = filtered;
+    debug.reduced_peaks = reduced;
+    CycleDetection { cycle_count: cycles.len(), cycles, avg_duration_minutes, debug }
+}
+
+/// Local maxima, treating a flat top as one peak at its middle.
+fn plateau_peaks(values: &[f64]) -> Vec<usize> {
+    let mut peaks = Vec::new();
+    let mut i = 1;
+    while i + 1 < values.len() {
+        if values[i] > values[i - 1] {
+            let mut j = i;
+            while j + 1 < values.len() && values[j] == values[j + 1] {
+                j += 1;
+            }
+            if j + 1 < values.len() && values[j] > values[j + 1] {
+                peaks.push((i + j) / 2);
+            }
+            i = j;
+        }
+        i += 1;
+    }
+    peaks
+}
+
+/// Runs off the main thread so multi-week recordings do not stall the UI.
+#[tauri::command]
+pub async fn detect_ultradian_cycles(
+    settings_state: tauri::State<'_, SettingsState>,
+    activity: TimeSeries,
+    config: Option<CycleDetectionConfig>,
+) -> Result<CycleDetection, String> {
+    let config = config.unwrap_or_default();
+    config.validate()?;
+    activity.validate()?;
+    let zone = settings_state
+        .lock()
+        .map_err(|e| format!("Failed to lock settings: {}", e))?
+        .current
+        .zone();
+
+    tauri::async_runtime::spawn_blocking(move || detect_cycles(&activity, &config, zone))
+        .await
+        .map_err(|e| format!("Failed to detect ultradian cycles: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY_START: i64 = 1_772_409_600_000; // 2026-03-02 00:00 UTC
+    const MINUTE: i64 = 60_000;
+
+    /// Activity from 06:00 to 24:00 with bumps of height `height` at `peaks`
+    /// (minutes after midnight) over a baseline of 10.
+    fn activity(step_minutes: i64, peaks: &[i64], height: f64) -> TimeSeries {
+        let timestamps: Vec<i64> = (6 * 60..24 * 60)
+            .step_by(step_minutes as usize)
+            .map(|minute| DAY_START + minute * MINUTE)
+            .collect();
+        let values = timestamps
+            .iter()
+            .map(|&t| {
+                let minute = ((t - DAY_START) / MINUTE) as f64;
+                10.0 + peaks
+                    .iter()
+                    .map(|&peak| height * (-((minute - peak as f64) / 10.0).powi(2) / 2.0).exp())
+                    .sum::<f64>()
+            })
+            .collect();
+        TimeSeries { timestamps, values }
+    }
+
+    /// Every 90 minutes from 08:00 to 20:30, plus one at 23:00.
+    fn peaks() -> Vec<i64> {
+        (0..9).map(|k| 8 * 60 + 90 * k).chain([23 * 60]).collect()
+    }
+
+    #[test]
+    fn finds_ninety_minute_cycles() {
+        for step in [1, 5] {
+            let result = detect_cycles(&activity(step, &peaks(), 70.0), &CycleDetectionConfig::default(), Tz::UTC);
+            assert_eq!(result.cycle_count, 8, "step {}", step);
+            assert!((result.avg_duration_minutes - 90.0).abs() < 1e-9);
+            assert_eq!(result.cycles[0].peak_time, DAY_START + 8 * 60 * MINUTE);
+            assert!(result.cycles[0].amplitude > 30.0);
+        }
+    }
+
+    #[test]
+    fn ignores_night_and_low_peaks() {
+        let series = activity(1, &peaks(), 70.0);
+        let result = detect_cycles(&series, &CycleDetectionConfig::default(), Tz::UTC);
+        let late = DAY_START + 23 * 60 * MINUTE;
+        assert!(result.debug.candidate_peaks.iter().any(|&i| series.timestamps[i] == late));
+        assert!(result.debug.filtered_peaks.iter().all(|&i| series.timestamps[i] < late));
+
+        let low = detect_cycles(&activity(1, &peaks(), 15.0), &CycleDetectionConfig::default(), Tz::UTC);
+        assert_eq!(low.cycle_count, 0);
+        assert!(low.debug.filtered_peaks.is_empty());
+    }
+
+    #[test]
+    fn peaks_too_far_apart_form_no_cycle() {
+        let result = detect_cycles(&activity(1, &[9 * 60, 12 * 60], 70.0), &CycleDetectionConfig::default(), Tz::UTC);
+        assert_eq!(result.debug.reduced_peaks.len(), 2);
+        assert_eq!(result.cycle_count, 0);
+    }
+
+    #[test]
+    fn close_peaks_keep_the_tallest() {
+        let mut series = activity(1, &[9 * 60, 10 * 60 + 30], 70.0);
+        let extra = activity(1, &[9 * 60 + 30], 90.0);
+        for (value, bump) in series.values.iter_mut().zip(&extra.values) {
+            *value += bump - 10.0;
+        }
+        let result = detect_cycles(&series, &CycleDetectionConfig::default(), Tz::UTC);
+        assert_eq!(result.debug.filtered_peaks.len(), 3);
+        assert_eq!(result.debug.reduced_peaks.len(), 2);
+        assert_eq!(result.cycles[0].peak_time, DAY_START + (9 * 60 + 30) * MINUTE);
+    }
+
+    #[test]
+    fn short_recordings_return_no_cycles() {
+        let mut series = activity(1, &peaks(), 70.0);
+        series.timestamps.truncate(179);
+        series.values.truncate(179);
+        assert_eq!(detect_cycles(&series, &CycleDetectionConfig::default(), Tz::UTC).cycle_count, 0);
+
+        // 180 five-minute samples cover fifteen hours and are enough.
+        let coarse = activity(5, &peaks(), 70.0);
+        assert!(coarse.len() >= 180);
+        assert!(detect_cycles(&coarse, &CycleDetectionConfig::default(), Tz::UTC).cycle_count > 0);
+    }
+
+    #[test]
+    fn flat_tops_count_once_at_their_middle() {
+        assert_eq!(plateau_peaks(&[0.0, 1.0, 3.0, 3.0, 3.0, 1.0, 0.0]), [3]);
+        assert_eq!(plateau_peaks(&[0.0, 2.0, 2.0]), Vec::<usize>::new());
+        assert_eq!(plateau_peaks(&[3.0, 1.0, 2.0, 1.0, 4.0, 0.0]), [2, 4]);
+    }
+}
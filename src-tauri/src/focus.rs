@@ -0,0 +1,495 @@
+// Focus sessions fitted to the remaining high-energy window, counted down in
+// the tray and logged with planned versus actual length.
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use crate::anchor::AnchorState;
+use crate::phase_estimator::EstimatorState;
+use crate::settings::SettingsState;
+use crate::timezone;
+use crate::ultradian::{CycleReading, EnergyPhase, UltradianClock};
+
+pub const FOCUS_COMPLETED_EVENT: &str = "focus-completed";
+
+const FOCUS_FILE: &str = "focus_sessions.json";
+/// Sessions shorter than this are not worth starting.
+const MIN_SESSION_MINUTES: f64 = 5.0;
+/// Oldest entries are dropped beyond this many logged sessions.
+const MAX_HISTORY: usize = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FocusOutcome {
+    /// Ran for its full planned length.
+    Completed,
+    /// Ended early by the user.
+    Stopped,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FocusSession {
+    pub started_at: DateTime<Utc>,
+    pub planned_minutes: f64,
+    pub paused_at: Option<DateTime<Utc>>,
+    /// Time spent paused before `paused_at`.
+    pub paused_minutes: f64,
+}
+
+impl FocusSession {
+    /// Focused minutes so far, excluding pauses.
+    pub fn elapsed_minutes(&self, now: DateTime<Utc>) -> f64 {
+        let until = self.paused_at.unwrap_or(now);
+        (minutes_between(self.started_at, until) - self.paused_minutes).max(0.0)
+    }
+
+    pub fn remaining_minutes(&self, now: DateTime<Utc>) -> f64 {
+        (self.planned_minutes - self.elapsed_minutes(now)).max(0.0)
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused_at.is_some()
+    }
+
+    /// Countdown for the tray, e.g. "🎯 24:13", or "⏸ 24:13" while paused.
+    pub fn tray_title(&self, now: DateTime<Utc>) -> String {
+        let seconds = (self.remaining_minutes(now) * 60.0).ceil() as i64;
+        let icon = if self.is_paused() { "⏸" } else { "🎯" };
+        format!("{} {}:{:02}", icon, seconds / 60, seconds % 60)
+    }
+
+    fn finish(&self, now: DateTime<Utc>, outcome: FocusOutcome) -> FocusRecord {
+        FocusRecord {
+            started_at: self.started_at,
+            ended_at: now,
+            planned_minutes: self.planned_minutes,
+            actual_minutes: self.elapsed_minutes(now),
+            paused_minutes: self.paused_minutes + self.paused_at.map_or(0.0, |at| minutes_between(at, now)),
+            outcome,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FocusRecord {
+    pub started_at: DateTime<Utc>,
+    pub ended_at: DateTime<Utc>,
+    pub planned_minutes: f64,
+    /// Focused minutes, excluding pauses.
+    pub actual_minutes: f64,
+    pub paused_minutes: f64,
+    pub outcome: FocusOutcome,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FocusStatus {
+    pub session: FocusSession,
+    pub elapsed_minutes: f64,
+    pub remaining_minutes: f64,
+    pub paused: bool,
+}
+
+impl FocusStatus {
+    fn of(session: &FocusSession, now: DateTime<Utc>) -> Self {
+        Self {
+            session: session.clone(),
+            elapsed_minutes: session.elapsed_minutes(now),
+            remaining_minutes: session.remaining_minutes(now),
+            paused: session.is_paused(),
+        }
+    }
+}
+
+/// Minutes of high energy left from `reading`, counting a rising transition
+/// as part of the window it leads into.
+pub fn remaining_high_window(clock: &UltradianClock, reading: &CycleReading) -> Option<f64> {
+    match reading.energy_phase {
+        EnergyPhase::Low => None,
+        _ if reading.cycle_position >= clock.high_end => None,
+        _ => Some(clock.high_end - reading.cycle_position),
+    }
+}
+
+/// Whether the rest of the current high-energy window is long enough for a session.
+pub fn can_start(clock: &UltradianClock, reading: &CycleReading) -> bool {
+    remaining_high_window(clock, reading).is_some_and(|window| window >= MIN_SESSION_MINUTES)
+}
+
+/// Active session and past sessions, persisted so neither is lost on restart.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct FocusLog {
+    active: Option<FocusSession>,
+    history: Vec<FocusRecord>,
+}
+
+#[derive(Debug)]
+pub struct FocusTimer {
+    path: PathBuf,
+    log: FocusLog,
+}
+
+impl FocusTimer {
+    pub fn load(config_dir: &Path) -> Self {
+        let path = config_dir.join(FOCUS_FILE);
+        let log = match fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|e| {
+                eprintln!("Warning: ignoring focus session file {}: {}", path.display(), e);
+                FocusLog::default()
+            }),
+            Err(_) => FocusLog::default(),
+        };
+        Self { path, log }
+    }
+
+    fn save(&self) -> Result<(), String> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir).map_err(|e| format!("Failed to create config directory: {}", e))?;
+        }
+        let json = serde_json::to_string_pretty(&self.log)
+            .map_err(|e| format!("Failed to serialize focus sessions: {}", e))?;
+        fs::write(&self.path, json).map_err(|e| format!("Failed to write focus sessions: {}", e))
+    }
+
+    /// Saves the log, putting `previous` back if that fails so memory matches disk.
+    fn save_or_restore(&mut self, previous: FocusLog) -> Result<(), String> {
+        if let Err(e) = self.save() {
+            self.log = previous;
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    pub fn active(&self) -> Option<&FocusSession> {
+        self.log.active.as_ref()
+    }
+
+    pub fn history(&self) -> &[FocusRecord] {
+        &self.log.history
+    }
+
+    /// Starts a session lasting `planned_minutes`.
+    pub fn start(&mut self, planned_minutes: f64, now: DateTime<Utc>) -> Result<&FocusSession, String> {
+        if self.log.active.is_some() {
+            return Err("A focus session is already running".to_string());
+        }
+        let previous = self.log.clone();
+        self.log.active = Some(FocusSession {
+            started_at: now,
+            planned_minutes,
+            paused_at: None,
+            paused_minutes: 0.0,
+        });
+        self.save_or_restore(previous)?;
+        Ok(self.log.active.as_ref().expect("session was just started"))
+    }
+
+    pub fn pause(&mut self, now: DateTime<Utc>) -> Result<(), String> {
+        let previous = self.log.clone();
+        let session = self.log.active.as_mut().ok_or("No focus session is running")?;
+        if session.paused_at.is_none() {
+            session.paused_at = Some(now);
+        }
+        self.save_or_restore(previous)
+    }
+
+    pub fn resume(&mut self, now: DateTime<Utc>) -> Result<(), String> {
+        let previous = self.log.clone();
+        let session = self.log.active.as_mut().ok_or("No focus session is running")?;
+        if let Some(paused_at) = session.paused_at.take() {
+            session.paused_minutes += minutes_between(paused_at, now);
+        }
+        self.save_or_restore(previous)
+    }
+
+    /// Ends the active session early, logging it as stopped.
+    pub fn stop(&mut self, now: DateTime<Utc>) -> Result<FocusRecord, String> {
+        let previous = self.log.clone();
+        let session = self.log.active.take().ok_or("No focus session is running")?;
+        let record = session.finish(now, FocusOutcome::Stopped);
+        self.record(record.clone());
+        self.save_or_restore(previous)?;
+        Ok(record)
+    }
+
+    /// Completes the active session once its planned time has been focused.
+    /// Called from the tray loop every tick.
+    pub fn tick(&mut self, now: DateTime<Utc>) -> Option<FocusRecord> {
+        let session = self.log.active.as_ref()?;
+        if session.is_paused() || session.remaining_minutes(now) > 0.0 {
+            return None;
+        }
+        let record = session.finish(now, FocusOutcome::Completed);
+        self.log.active = None;
+        self.record(record.clone());
+        let _ = self.save();
+        Some(record)
+    }
+
+    fn record(&mut self, record: FocusRecord) {
+        self.log.history.push(record);
+        let excess = self.log.history.len().saturating_sub(MAX_HISTORY);
+        self.log.history.drain(..excess);
+    }
+}
+
+fn minutes_between(from: DateTime<Utc>, to: DateTime<Utc>) -> f64 {
+    (to - from).num_milliseconds() as f64 / 60_000.0
+}
+
+pub type FocusState = Arc<Mutex<FocusTimer>>;
+
+/// Session length for a `window` of high energy left: the whole window, or the
+/// requested minutes if shorter.
+fn planned_minutes(window: f64, requested: Option<f64>) -> Result<f64, String> {
+    let planned = match requested {
+        Some(requested) if requested > 0.0 => requested.min(window),
+        Some(requested) => return Err(format!("Focus session length must be positive, got {}", requested)),
+        None => window,
+    };
+    if planned < MIN_SESSION_MINUTES {
+        return Err(format!(
+            "Only {:.0} min of the high-energy window remain, too short for a focus session",
+            window
+        ));
+    }
+    Ok(planned)
+}
+
+/// Starts a session that ends with the current high-energy window, or after
+/// `minutes` if that is shorter.
+pub fn start_fitted(
+    settings_state: &SettingsState,
+    anchor_state: &AnchorState,
+    estimator_state: &EstimatorState,
+    focus_state: &FocusState,
+    minutes: Option<f64>,
+) -> Result<FocusStatus, String> {
+    let (clock, zone) = {
+        let store = settings_state.lock().map_err(|e| format!("Failed to lock settings: {}", e))?;
+        (store.current.clock(), store.current.zone())
+    };
+    let now = timezone::now_in(zone);
+    let anchor = anchor_state
+        .lock()
+        .map_err(|e| format!("Failed to lock anchor: {}", e))?
+        .anchor_for(now);
+    let estimate = estimator_state
+        .lock()
+        .map_err(|e| format!("Failed to lock estimator: {}", e))?
+        .estimate(now.with_timezone(&Utc));
+    let scheduled = clock.reading_at(now, anchor);
+    let reading = match &estimate {
+        Some(estimate) => estimate.apply(&clock, &scheduled),
+        None => scheduled,
+    };
+
+    let window = remaining_high_window(&clock, &reading)
+        .ok_or("The high-energy window has ended for this cycle")?;
+    let planned = planned_minutes(window, minutes)?;
+
+    let now = now.with_timezone(&Utc);
+    let mut timer = focus_state.lock().map_err(|e| format!("Failed to lock focus timer: {}", e))?;
+    let session = timer.start(planned, now)?;
+    Ok(FocusStatus::of(session, now))
+}
+
+#[tauri::command]
+pub fn start_focus_session(
+    settings_state: tauri::State<'_, SettingsState>,
+    anchor_state: tauri::State<'_, AnchorState>,
+    estimator_state: tauri::State<'_, EstimatorState>,
+    focus_state: tauri::State<'_, FocusState>,
+    minutes: Option<f64>,
+) -> Result<FocusStatus, String> {
+    start_fitted(&settings_state, &anchor_state, &estimator_state, &focus_state, minutes)
+}
+
+#[tauri::command]
+pub fn pause_focus_session(focus_state: tauri::State<'_, FocusState>) -> Result<FocusStatus, String> {
+    let now = Utc::now();
+    let mut timer = focus_state.lock().map_err(|e| format!("Failed to lock focus timer: {}", e))?;
+    timer.pause(now)?;
+    let session = timer.active().ok_or("No focus session is running")?;
+    Ok(FocusStatus::of(session, now))
+}
+
+#[tauri::command]
+pub fn resume_focus_session(focus_state: tauri::State<'_, FocusState>) -> Result<FocusStatus, String> {
+    let now = Utc::now();
+    let mut timer = focus_state.lock().map_err(|e| format!("Failed to lock focus timer: {}", e))?;
+    timer.resume(now)?;
+    let session = timer.active().ok_or("No focus session is running")?;
+    Ok(FocusStatus::of(session, now))
+}
+
+#[tauri::command]
+pub fn stop_focus_session(focus_state: tauri::State<'_, FocusState>) -> Result<FocusRecord, String> {
+    focus_state
+        .lock()
+        .map_err(|e| format!("Failed to lock focus timer: {}", e))?
+        .stop(Utc::now())
+}
+
+#[tauri::command]
+pub fn get_focus_status(focus_state: tauri::State<'_, FocusState>) -> Result<Option<FocusStatus>, String> {
+    let now = Utc::now();
+    let timer = focus_state.lock().map_err(|e| format!("Failed to lock focus timer: {}", e))?;
+    Ok(timer.active().map(|session| FocusStatus::of(session, now)))
+}
+
+#[tauri::command]
+pub fn get_focus_history(focus_state: tauri::State<'_, FocusState>) -> Result<Vec<FocusRecord>, String> {
+    let timer = focus_state.lock().map_err(|e| format!("Failed to lock focus timer: {}", e))?;
+    Ok(timer.history().to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    fn t0() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, 2, 9, 0, 0).unwrap()
+    }
+
+    fn after(minutes: i64) -> DateTime<Utc> {
+        t0() + Duration::minutes(minutes)
+    }
+
+    /// A timer saving to its own empty directory under the system temp dir.
+    fn timer(name: &str) -> (FocusTimer, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("circada-focus-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        (FocusTimer::load(&dir), dir)
+    }
+
+    #[test]
+    fn pauses_do_not_count_as_focus() {
+        let (mut timer, dir) = timer("pauses");
+        timer.start(25.0, t0()).unwrap();
+        assert!(timer.start(25.0, after(1)).is_err());
+        timer.pause(after(10)).unwrap();
+        let session = timer.active().unwrap();
+        assert_eq!(session.elapsed_minutes(after(18)), 10.0);
+        assert_eq!(session.tray_title(after(18)), "⏸ 15:00");
+
+        timer.resume(after(20)).unwrap();
+        let session = timer.active().unwrap();
+        assert_eq!(session.paused_minutes, 10.0);
+        assert_eq!(session.elapsed_minutes(after(25)), 15.0);
+        assert_eq!(session.remaining_minutes(after(25)), 10.0);
+        assert_eq!(session.tray_title(after(25)), "🎯 10:00");
+
+        timer.pause(after(27)).unwrap();
+        let record = timer.stop(after(40)).unwrap();
+        assert_eq!(record.outcome, FocusOutcome::Stopped);
+        assert_eq!(record.actual_minutes, 17.0);
+        assert_eq!(record.paused_minutes, 23.0);
+        assert!(timer.active().is_none());
+
+        // The log survives a restart
+        let reloaded = FocusTimer::load(&dir);
+        assert_eq!(reloaded.history(), [record]);
+        assert!(reloaded.active().is_none());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn tick_completes_once_the_planned_focus_is_done() {
+        let (mut timer, dir) = timer("tick");
+        timer.start(25.0, t0()).unwrap();
+        assert_eq!(timer.tick(after(24)), None);
+        timer.pause(after(10)).unwrap();
+        // Paused sessions never complete
+        assert_eq!(timer.tick(after(40)), None);
+        timer.resume(after(40)).unwrap();
+        assert_eq!(timer.tick(after(54)), None);
+
+        let record = timer.tick(after(55)).unwrap();
+        assert_eq!(record.outcome, FocusOutcome::Completed);
+        assert_eq!(record.actual_minutes, 25.0);
+        assert_eq!(record.paused_minutes, 30.0);
+        assert!(timer.active().is_none());
+        assert_eq!(timer.tick(after(56)), None);
+        assert_eq!(timer.history().len(), 1);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn history_keeps_the_latest_sessions() {
+        let (mut timer, _) = timer("history");
+        for i in 0..MAX_HISTORY as i64 + 5 {
+            let session = FocusSession {
+                started_at: after(i),
+                planned_minutes: 1.0,
+                paused_at: None,
+                paused_minutes: 0.0,
+            };
+            timer.record(session.finish(after(i + 1), FocusOutcome::Completed));
+        }
+        assert_eq!(timer.history().len(), MAX_HISTORY);
+        assert_eq!(timer.history()[0].started_at, after(5));
+        assert_eq!(timer.history()[MAX_HISTORY - 1].started_at, after(MAX_HISTORY as i64 + 4));
+    }
+
+    #[test]
+    fn sessions_fit_the_rest_of_the_high_window() {
+        let clock = UltradianClock::default();
+        let window = |position: f64| remaining_high_window(&clock, &clock.at_position(position, 1));
+        // The rising transition counts towards the window it leads into
+        assert_eq!(window(3.0), Some(57.0));
+        assert_eq!(window(30.0), Some(30.0));
+        assert_eq!(window(56.0), Some(4.0));
+        assert_eq!(window(62.0), None);
+        assert_eq!(window(70.0), None);
+
+        let startable = |position: f64| can_start(&clock, &clock.at_position(position, 1));
+        assert!(startable(30.0));
+        assert!(startable(55.0));
+        assert!(!startable(56.0));
+        assert!(!startable(70.0));
+    }
+
+    #[test]
+    fn requested_lengths_are_clamped_to_the_window() {
+        assert_eq!(planned_minutes(40.0, None), Ok(40.0));
+        assert_eq!(planned_minutes(40.0, Some(25.0)), Ok(25.0));
+        assert_eq!(planned_minutes(20.0, Some(25.0)), Ok(20.0));
+        assert!(planned_minutes(40.0, Some(0.0)).is_err());
+        assert!(planned_minutes(40.0, Some(3.0)).is_err());
+        assert!(planned_minutes(4.0, None).is_err());
+    }
+
+    #[test]
+    fn failed_saves_leave_the_timer_unchanged() {
+        let (mut timer, dir) = timer("rollback");
+        fs::create_dir_all(&dir).unwrap();
+        // A file where the config directory should be makes every save fail
+        let blocker = dir.join("blocker");
+        fs::write(&blocker, "").unwrap();
+
+        let mut blocked = FocusTimer::load(&blocker);
+        assert!(blocked.start(25.0, t0()).is_err());
+        assert!(blocked.active().is_none());
+
+        timer.start(25.0, t0()).unwrap();
+        timer.path = blocker.join(FOCUS_FILE);
+        assert!(timer.pause(after(10)).is_err());
+        assert!(!timer.active().unwrap().is_paused());
+        assert!(timer.stop(after(12)).is_err());
+        assert!(timer.active().is_some());
+        assert!(timer.history().is_empty());
+
+        timer.path = dir.join(FOCUS_FILE);
+        timer.pause(after(10)).unwrap();
+        timer.path = blocker.join(FOCUS_FILE);
+        assert!(timer.resume(after(15)).is_err());
+        let session = timer.active().unwrap();
+        assert_eq!(session.paused_at, Some(after(10)));
+        assert_eq!(session.paused_minutes, 0.0);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,230 @@
+// Upcoming phase segments over a time horizon, built from the same clock and
+// anchor as the tray and widget so every consumer sees the same schedule.
+use chrono::{DateTime, Duration, FixedOffset, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+use crate::anchor::{AnchorState, CycleAnchor};
+use crate::confidence::{self, HistoryState};
+use crate::phase_estimator::EstimatorState;
+use crate::settings::SettingsState;
+use crate::timezone;
+use crate::ultradian::{EnergyPhase, UltradianClock};
+
+/// Longest horizon the forecast command accepts.
+const MAX_HORIZON_HOURS: f64 = 72.0;
+/// Hours after which forecast confidence has halved, as predicted cycles drift
+/// further from the observed ones.
+const CONFIDENCE_HALF_LIFE_HOURS: f64 = 12.0;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PhaseSegment {
+    pub start: DateTime<FixedOffset>,
+    pub end: DateTime<FixedOffset>,
+    pub cycle_number: i32,
+    pub energy_phase: EnergyPhase,
+    pub phase_icon: String,
+    pub phase_label: String,
+    /// Mean energy intensity over the segment.
+    pub expected_intensity: f64,
+    pub confidence: f64,
+}
+
+/// Ordered, non-overlapping segments covering `now..now + horizon`, shifted
+/// `offset_minutes` ahead of the schedule as a locked phase estimate would be
+/// (see `PhaseEstimate::offset_minutes`); the offset is held over the horizon.
+pub fn forecast(
+    clock: &UltradianClock,
+    anchor: &CycleAnchor,
+    now: DateTime<Tz>,
+    horizon: Duration,
+    base_confidence: f64,
+    offset_minutes: f64,
+) -> Vec<PhaseSegment> {
+    let end = now + horizon;
+    let mut segments: Vec<PhaseSegment> = Vec::new();
+    let mut t = now;
+
+    while t < end {
+        let anchor_start = anchor.anchor_for(t);
+        let elapsed = (t - anchor_start).num_milliseconds() as f64 / 60_000.0;
+        let reading = clock.shifted_reading(elapsed, offset_minutes);
+        let to_boundary = clock.minutes_to_boundary(reading.cycle_position);
+        let segment_end = (t + minutes(to_boundary))
+            .min(anchor.next_reset_after(t))
+            .min(end);
+
+        let span = (segment_end - t).num_milliseconds() as f64 / 60_000.0;
+        let midpoint = clock.reading(reading.cycle_position + span / 2.0);
+        let intensity = mean_intensity(clock, reading.cycle_position, span);
+        let hours_ahead = (t - now).num_milliseconds() as f64 / 3_600_000.0;
+
+        match segments.last_mut() {
+            Some(last)
+                if last.cycle_number == reading.cycle_number
+                    && last.energy_phase == midpoint.energy_phase
+                    && last.phase_label == midpoint.phase_label =>
+            {
+                let last_span = (last.end - last.start).num_milliseconds() as f64 / 60_000.0;
+                let total = last_span + span;
+                if total > 0.0 {
+                    last.expected_intensity = (last.expected_intensity * last_span + intensity * span) / total;
+                }
+                last.end = segment_end.fixed_offset();
+            }
+            _ => segments.push(PhaseSegment {
+                start: t.fixed_offset(),
+                end: segment_end.fixed_offset(),
+                cycle_number: reading.cycle_number,
+                energy_phase: midpoint.energy_phase,
+                phase_icon: midpoint.phase_icon,
+                phase_label: midpoint.phase_label,
+                expected_intensity: intensity,
+                confidence: base_confidence * 0.5f64.powf(hours_ahead / CONFIDENCE_HALF_LIFE_HOURS),
+            }),
+        }
+
+        t = segment_end;
+    }
+
+    segments
+}
+
+fn minutes(value: f64) -> Duration {
+    Duration::milliseconds((value * 60_000.0).round() as i64)
+}
+
+/// Average intensity across `span` minutes starting at `cycle_position`.
+fn mean_intensity(clock: &UltradianClock, cycle_position: f64, span: f64) -> f64 {
+    const SAMPLES: usize = 8;
+    let total: f64 = (0..SAMPLES)
+        .map(|i| {
+            let position = cycle_position + span * (i as f64 + 0.5) / SAMPLES as f64;
+            clock.energy_intensity(position.min(clock.cycle_length))
+        })
+        .sum();
+    total / SAMPLES as f64
+}
+
+#[tauri::command]
+pub fn get_phase_forecast(
+    settings_state: tauri::State<'_, SettingsState>,
+    anchor_state: tauri::State<'_, AnchorState>,
+    estimator_state: tauri::State<'_, EstimatorState>,
+    history_state: tauri::State<'_, HistoryState>,
+    hours: f64,
+) -> Result<Vec<PhaseSegment>, String> {
+    if !(hours > 0.0 && hours <= MAX_HORIZON_HOURS) {
+        return Err(format!("Forecast horizon must be between 0 and {} hours", MAX_HORIZON_HOURS));
+    }
+    let (clock, zone) = {
+        let store = settings_state.lock().map_err(|e| format!("Failed to lock settings: {}", e))?;
+        (store.current.clock(), store.current.zone())
+    };
+    let anchor = anchor_state
+        .lock()
+        .map_err(|e| format!("Failed to lock anchor: {}", e))?
+        .clone();
+
+    let now = timezone::now_in(zone);
+    let estimate = estimator_state
+        .lock()
+        .map_err(|e| format!("Failed to lock estimator: {}", e))?
+        .estimate(now.with_timezone(&Utc));
+    let history_days = history_state
+        .lock()
+        .map_err(|e| format!("Failed to lock history: {}", e))?
+        .days;
+    let report = confidence::assess(estimate.as_ref(), &anchor, history_days, now);
+    // Start from the same adjusted reading the tray and widget show
+    let scheduled = clock.reading_at(now, anchor.anchor_for(now));
+    let offset = estimate.as_ref().map_or(0.0, |estimate| estimate.offset_minutes(&clock, &scheduled));
+
+    Ok(forecast(&clock, &anchor, now, minutes(hours * 60.0), report.confidence, offset))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::anchor::AnchorMode;
+    use chrono::{NaiveTime, TimeZone};
+
+    fn midnight() -> CycleAnchor {
+        CycleAnchor::new(AnchorMode::Midnight, NaiveTime::MIN)
+    }
+
+    fn at(h: u32, min: u32) -> DateTime<Tz> {
+        Tz::UTC.with_ymd_and_hms(2026, 3, 2, h, min, 0).unwrap()
+    }
+
+    fn labels(segments: &[PhaseSegment]) -> Vec<&str> {
+        segments.iter().map(|segment| segment.phase_label.as_str()).collect()
+    }
+
+    #[test]
+    fn segments_cover_horizon_without_gaps() {
+        let clock = UltradianClock::default();
+        let segments = forecast(&clock, &midnight(), at(0, 0), Duration::hours(3), 1.0, 0.0);
+        assert_eq!(segments.first().unwrap().start, at(0, 0).fixed_offset());
+        assert_eq!(segments.last().unwrap().end, at(3, 0).fixed_offset());
+        assert!(segments.windows(2).all(|pair| pair[0].end == pair[1].start));
+        assert_eq!(
+            labels(&segments[..8]),
+            [
+                "Rising Energy",
+                "Rising Energy",
+                "Building Energy",
+                "Peak Energy",
+                "Peak Flow",
+                "Winding Down",
+                "Winding Down",
+                "Rest Phase"
+            ]
+        );
+        assert_eq!(segments[1].energy_phase, EnergyPhase::High);
+        assert_eq!(segments[8].start, at(1, 30).fixed_offset());
+        assert_eq!(segments[8].cycle_number, 2);
+    }
+
+    #[test]
+    fn segments_end_at_anchor_reset() {
+        let clock = UltradianClock::default();
+        let anchor = CycleAnchor::new(AnchorMode::WakeTime, NaiveTime::from_hms_opt(7, 0, 0).unwrap());
+        let segments = forecast(&clock, &anchor, at(6, 0), Duration::hours(2), 1.0, 0.0);
+        let reset = segments.iter().position(|segment| segment.start == at(7, 0).fixed_offset()).unwrap();
+        assert_eq!(segments[reset].cycle_number, 1);
+        assert_eq!(segments[reset].phase_label, "Rising Energy");
+        assert!(segments[reset - 1].cycle_number > 1);
+    }
+
+    #[test]
+    fn offset_shifts_the_schedule() {
+        let clock = UltradianClock::default();
+        let ahead = forecast(&clock, &midnight(), at(0, 0), Duration::hours(2), 1.0, 30.0);
+        let later = forecast(&clock, &midnight(), at(0, 30), Duration::hours(2), 1.0, 0.0);
+        assert_eq!(ahead[0].phase_label, "Peak Energy");
+        assert_eq!(labels(&ahead[..5]), labels(&later[..5]));
+        for (shifted, scheduled) in ahead.iter().zip(&later).skip(1).take(4) {
+            assert_eq!(shifted.start + Duration::minutes(30), scheduled.start);
+        }
+    }
+
+    #[test]
+    fn offset_behind_the_anchor_stays_in_first_cycle() {
+        let clock = UltradianClock::default();
+        let segments = forecast(&clock, &midnight(), at(0, 0), Duration::hours(1), 1.0, -10.0);
+        assert_eq!(segments[0].phase_label, "Rest Phase");
+        assert_eq!(segments[0].cycle_number, 1);
+        assert_eq!(segments[1].start, at(0, 10).fixed_offset());
+        assert_eq!(segments[1].phase_label, "Rising Energy");
+    }
+
+    #[test]
+    fn confidence_halves_every_half_life() {
+        let clock = UltradianClock::default();
+        let segments = forecast(&clock, &midnight(), at(0, 0), Duration::hours(13), 0.8, 0.0);
+        assert_eq!(segments[0].confidence, 0.8);
+        let noon = segments.iter().find(|segment| segment.start == at(12, 0).fixed_offset()).unwrap();
+        assert!((noon.confidence - 0.4).abs() < 1e-9);
+    }
+}
@@ -132,7 +132,7 @@ extern "C" fn heart_rate_callback(rate: f64, timestamp: u64) {
 }
 
 pub struct HealthKitManager {
-    _receiver: Receiver<HeartRateData>,
+    receiver: Option<Receiver<HeartRateData>>,
     is_monitoring: Arc<Mutex<bool>>,
 }
 
@@ -149,11 +149,17 @@ impl HealthKitManager {
         }
         
         Self {
-            _receiver: receiver,
+            receiver: Some(receiver),
             is_monitoring: Arc::new(Mutex::new(false)),
         }
     }
     
+    /// Hands out the heart rate stream fed by the Swift callback. Only the
+    /// first caller gets it.
+    pub fn take_receiver(&mut self) -> Option<Receiver<HeartRateData>> {
+        self.receiver.take()
+    }
+
     pub fn request_permissions(&self) -> bool {
         healthkit_request_permissions()
     }
@@ -0,0 +1,663 @@
+// Heart rate variability from RR intervals (Task Force 1996): time domain,
+// LF/HF band power, Poincaré SD1/SD2, DFA α1 and sample entropy, for batch
+// series in windows and for a rolling live buffer. Artifacts are corrected
+// first unless disabled.
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::f64::consts::PI;
+use std::sync::{Arc, Mutex};
+
+use crate::periodogram;
+use crate::rr_artifacts::{self, ArtifactConfig, RrArtifact};
+
+const VLF_BAND: (f64, f64) = (0.0033, 0.04);
+const LF_BAND: (f64, f64) = (0.04, 0.15);
+const HF_BAND: (f64, f64) = (0.15, 0.4);
+/// Lomb frequency grid spacing, Hz.
+const LOMB_STEP_HZ: f64 = 0.001;
+/// Windows with fewer beats are skipped.
+const MIN_WINDOW_BEATS: usize = 30;
+/// DFA α1 box sizes in beats.
+const DFA_SHORT_SCALES: std::ops::RangeInclusive<usize> = 4..=16;
+const SAMPLE_ENTROPY_M: usize = 2;
+const SAMPLE_ENTROPY_R: f64 = 0.2;
+/// Sample entropy compares every template pair, so longer series are left to
+/// their windows.
+const SAMPLE_ENTROPY_MAX_BEATS: usize = 1000;
+/// Beats kept in the live buffer, seconds.
+const LIVE_RETENTION_SECONDS: f64 = 3600.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpectralMethod {
+    /// Lomb-Scargle directly on the uneven beat times.
+    Lomb,
+    /// Welch's method on the tachogram resampled at 4 Hz.
+    Welch,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HrvConfig {
+    pub window_seconds: f64,
+    /// Window start spacing; equal to the window for back-to-back windows.
+    pub step_seconds: f64,
+    pub spectral_method: SpectralMethod,
+    pub resample_hz: f64,
+    /// Welch segment length, overlapping by half.
+    pub welch_segment_seconds: f64,
+    pub correct_artifacts: bool,
+    pub artifacts: ArtifactConfig,
+}
+
+impl Default for HrvConfig {
+    fn default() -> Self {
+        Self {
+            window_seconds: 300.0,
+            step_seconds: 300.0,
+            spectral_method: SpectralMethod::Lomb,
+            resample_hz: 4.0,
+            welch_segment_seconds: 150.0,
+            correct_artifacts: true,
+            artifacts: ArtifactConfig::default(),
+        }
+    }
+}
+
+impl HrvConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.window_ms() < 30_000 || self.step_ms() <= 0 {
+            return Err("HRV windows must be at least 30 s with a step of at least 1 ms".to_string());
+        }
+        if self.resample_hz < 2.0 * HF_BAND.1 || self.welch_segment_seconds <= 0.0 {
+            return Err("Resampling must be at least 0.8 Hz and Welch segments positive".to_string());
+        }
+        self.artifacts.validate()
+    }
+
+    fn window_ms(&self) -> i64 {
+        (self.window_seconds * 1000.0) as i64
+    }
+
+    fn step_ms(&self) -> i64 {
+        (self.step_seconds * 1000.0) as i64
+    }
+}
+
+/// RR intervals (ms) of consecutive beats, the first ending at `start` (epoch ms).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RrSeries {
+    #[serde(default)]
+    pub start: i64,
+    pub intervals: Vec<f64>,
+}
+
+impl RrSeries {
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(i) = self.intervals.iter().position(|rr| !(rr.is_finite() && *rr > 0.0)) {
+            return Err(format!("RR interval {} is not a positive number", i));
+        }
+        Ok(())
+    }
+
+    /// Epoch ms at which each beat occurs.
+    pub fn beat_times(&self) -> Vec<i64> {
+        let mut elapsed = 0.0;
+        self.intervals
+            .iter()
+            .enumerate()
+            .map(|(i, rr)| {
+                if i > 0 {
+                    elapsed += rr;
+                }
+                self.start + elapsed.round() as i64
+            })
+            .collect()
+    }
+}
+
+/// Milliseconds and ms² unless noted; `None` where the window is too short.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HrvMetrics {
+    pub start: i64,
+    pub end: i64,
+    pub beats: usize,
+    pub mean_rr: f64,
+    pub mean_heart_rate: f64,
+    pub sdnn: f64,
+    pub rmssd: f64,
+    pub sdsd: f64,
+    pub nn50: usize,
+    pub pnn50: f64,
+    pub vlf_power: f64,
+    pub lf_power: f64,
+    pub hf_power: f64,
+    pub lf_hf_ratio: Option<f64>,
+    /// LF and HF as percentages of LF + HF.
+    pub lf_nu: Option<f64>,
+    pub hf_nu: Option<f64>,
+    pub sd1: f64,
+    pub sd2: f64,
+    /// Short-term fractal scaling exponent over 4-16 beats.
+    pub dfa_alpha1: Option<f64>,
+    /// With m = 2 and r = 0.2 SDNN; `None` over more than 1000 beats.
+    pub sample_entropy: Option<f64>,
+    /// Share of intervals in the window produced by artifact correction.
+    pub corrected_beats_percent: f64,
+    /// False when too many beats were corrected for the metrics to be trusted.
+    pub reliable: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HrvAnalysis {
+    pub overall: HrvMetrics,
+    pub windows: Vec<HrvMetrics>,
+    /// Indexed into the input intervals.
+    pub artifacts: Vec<RrArtifact>,
+}
+
+fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+fn standard_deviation(values: &[f64]) -> f64 {
+    if values.len() < 2 {
+        return 0.0;
+    }
+    let m = mean(values);
+    (values.iter().map(|v| (v - m).powi(2)).sum::<f64>() / (values.len() - 1) as f64).sqrt()
+}
+
+/// One-sided PSD (ms²/Hz) at `frequencies` from the Lomb-Scargle periodogram,
+/// scaled so it integrates to the variance.
+fn lomb_psd(times_s: &[f64], rr: &[f64], frequencies: &[f64]) -> Vec<f64> {
+    let m = mean(rr);
+    let y: Vec<f64> = rr.iter().map(|v| v - m).collect();
+    let span = times_s[times_s.len() - 1] - times_s[0];
+    frequencies
+        .iter()
+        .map(|f| 2.0 * span / y.len() as f64 * periodogram::power_at(times_s, &y, 1.0, 2.0 * PI * f))
+        .collect()
+}
+
+/// Natural cubic spline through (`x`, `y`) evaluated every `1 / fs` from `x[0]`.
+fn spline_resample(x: &[f64], y: &[f64], fs: f64) -> Vec<f64> {
+    let n = x.len();
+    let h: Vec<f64> = x.windows(2).map(|pair| pair[1] - pair[0]).collect();
+    // Second derivatives from the tridiagonal system, zero at both ends
+    let mut second = vec![0.0; n];
+    let mut diagonal = vec![1.0; n];
+    let mut rhs = vec![0.0; n];
+    for i in 1..n - 1 {
+        diagonal[i] = 2.0 * (h[i - 1] + h[i]);
+        rhs[i] = 6.0 * ((y[i + 1] - y[i]) / h[i] - (y[i] - y[i - 1]) / h[i - 1]);
+        if i > 1 {
+            let factor = h[i - 1] / diagonal[i - 1];
+            diagonal[i] -= factor * h[i - 1];
+            rhs[i] -= factor * rhs[i - 1];
+        }
+    }
+    for i in (1..n - 1).rev() {
+        let next = if i + 1 < n - 1 { h[i] * second[i + 1] } else { 0.0 };
+        second[i] = (rhs[i] - next) / diagonal[i];
+    }
+
+    let count = ((x[n - 1] - x[0]) * fs) as usize + 1;
+    let mut k = 0;
+    (0..count)
+        .map(|i| {
+            let t = x[0] + i as f64 / fs;
+            while k + 2 < n && x[k + 1] <= t {
+                k += 1;
+            }
+            let (a, b) = ((x[k + 1] - t) / h[k], (t - x[k]) / h[k]);
+            let curvature = (a.powi(3) - a) * second[k] + (b.powi(3) - b) * second[k + 1];
+            a * y[k] + b * y[k + 1] + curvature * h[k] * h[k] / 6.0
+        })
+        .collect()
+}
+
+/// Welch PSD (ms²/Hz) of the tachogram resampled at `fs`, with Hann-windowed
+/// half-overlapping segments. Returns (frequencies, psd).
+fn welch_psd(times_s: &[f64], rr: &[f64], fs: f64, segment_seconds: f64) -> (Vec<f64>, Vec<f64>) {
+    let resampled = spline_resample(times_s, rr, fs);
+    let count = resampled.len();
+    let length = ((segment_seconds * fs) as usize).clamp(2, count.max(2));
+    let window: Vec<f64> = (0..length).map(|i| 0.5 - 0.5 * (2.0 * PI * i as f64 / (length - 1) as f64).cos()).collect();
+    let window_energy: f64 = window.iter().map(|w| w * w).sum();
+    let bins = ((HF_BAND.1 * length as f64 / fs).ceil() as usize + 1).min(length / 2 + 1);
+    let frequencies: Vec<f64> = (0..bins).map(|b| b as f64 * fs / length as f64).collect();
+    let mut psd = vec![0.0; bins];
+    let mut segments = 0;
+    let mut offset = 0;
+    while offset + length <= resampled.len() {
+        let segment = &resampled[offset..offset + length];
+        let m = mean(segment);
+        for (b, value) in psd.iter_mut().enumerate() {
+            let omega = 2.0 * PI * b as f64 / length as f64;
+            let (mut re, mut im) = (0.0, 0.0);
+            for (i, (x, w)) in segment.iter().zip(&window).enumerate() {
+                let (sin, cos) = (omega * i as f64).sin_cos();
+                re += (x - m) * w * cos;
+                im -= (x - m) * w * sin;
+            }
+            *value += 2.0 * (re * re + im * im) / (fs * window_energy);
+        }
+        segments += 1;
+        offset += length / 2;
+    }
+    for value in &mut psd {
+        *value /= segments.max(1) as f64;
+    }
+    (frequencies, psd)
+}
+
+fn band_power(frequencies: &[f64], psd: &[f64], (low, high): (f64, f64)) -> f64 {
+    let step = if frequencies.len() > 1 { frequencies[1] - frequencies[0] } else { 0.0 };
+    frequencies.iter().zip(psd).filter(|(f, _)| **f >= low && **f < high).map(|(_, p)| p * step).sum()
+}
+
+/// Detrended fluctuation analysis exponent over `scales` beats.
+fn dfa_alpha(rr: &[f64], scales: std::ops::RangeInclusive<usize>) -> Option<f64> {
+    let m = mean(rr);
+    let mut profile = Vec::with_capacity(rr.len());
+    let mut sum = 0.0;
+    for value in rr {
+        sum += value - m;
+        profile.push(sum);
+    }
+
+    let mut points = Vec::new();
+    for n in scales {
+        let boxes = profile.len() / n;
+        if boxes < 2 {
+            break;
+        }
+        // Least-squares line in each box; x is 0..n so its moments are fixed
+        let x_mean = (n - 1) as f64 / 2.0;
+        let sxx: f64 = (0..n).map(|i| (i as f64 - x_mean).powi(2)).sum();
+        let mut squared = 0.0;
+        for chunk in profile.chunks_exact(n) {
+            let y_mean = mean(chunk);
+            let slope = chunk.iter().enumerate().map(|(i, y)| (i as f64 - x_mean) * (y - y_mean)).sum::<f64>() / sxx;
+            squared += chunk
+                .iter()
+                .enumerate()
+                .map(|(i, y)| (y - y_mean - slope * (i as f64 - x_mean)).powi(2))
+                .sum::<f64>();
+        }
+        let fluctuation = (squared / (boxes * n) as f64).sqrt();
+        if fluctuation > 0.0 {
+            points.push(((n as f64).ln(), fluctuation.ln()));
+        }
+    }
+    if points.len() < 3 {
+        return None;
+    }
+    let x_mean = points.iter().map(|p| p.0).sum::<f64>() / points.len() as f64;
+    let y_mean = points.iter().map(|p| p.1).sum::<f64>() / points.len() as f64;
+    let sxy: f64 = points.iter().map(|p| (p.0 - x_mean) * (p.1 - y_mean)).sum();
+    let sxx: f64 = points.iter().map(|p| (p.0 - x_mean).powi(2)).sum();
+    Some(sxy / sxx)
+}
+
+/// Richman & Moorman sample entropy, excluding self-matches.
+fn sample_entropy(rr: &[f64], m: usize, tolerance: f64) -> Option<f64> {
+    let templates = rr.len().checked_sub(m)?;
+    let (mut shorter, mut longer) = (0usize, 0usize);
+    for i in 0..templates {
+        for j in i + 1..templates {
+            if (0..m).all(|k| (rr[i + k] - rr[j + k]).abs() <= tolerance) {
+                shorter += 1;
+                if (rr[i + m] - rr[j + m]).abs() <= tolerance {
+                    longer += 1;
+                }
+            }
+        }
+    }
+    (shorter > 0 && longer > 0).then(|| -(longer as f64 / shorter as f64).ln())
+}
+
+/// Metrics over consecutive beats at `times` (epoch ms) with intervals `rr`,
+/// `corrected` marking those produced by artifact correction.
+pub fn hrv_metrics(times: &[i64], rr: &[f64], corrected: &[bool], config: &HrvConfig) -> Result<HrvMetrics, String> {
+    if rr.len() < 3 {
+        return Err("HRV needs at least 3 RR intervals".to_string());
+    }
+    let diffs: Vec<f64> = rr.windows(2).map(|pair| pair[1] - pair[0]).collect();
+    let mean_rr = mean(rr);
+    let sdnn = standard_deviation(rr);
+    let rmssd = (diffs.iter().map(|d| d * d).sum::<f64>() / diffs.len() as f64).sqrt();
+    let sdsd = standard_deviation(&diffs);
+    let nn50 = diffs.iter().filter(|d| d.abs() > 50.0).count();
+
+    let times_s: Vec<f64> = times.iter().map(|t| (t - times[0]) as f64 / 1000.0).collect();
+    let (frequencies, psd) = match config.spectral_method {
+        SpectralMethod::Lomb => {
+            let count = ((HF_BAND.1 - VLF_BAND.0) / LOMB_STEP_HZ).round() as usize + 1;
+            let frequencies: Vec<f64> = (0..count).map(|i| VLF_BAND.0 + i as f64 * LOMB_STEP_HZ).collect();
+            let psd = lomb_psd(&times_s, rr, &frequencies);
+            (frequencies, psd)
+        }
+        SpectralMethod::Welch => welch_psd(&times_s, rr, config.resample_hz, config.welch_segment_seconds),
+    };
+    let vlf_power = band_power(&frequencies, &psd, VLF_BAND);
+    let lf_power = band_power(&frequencies, &psd, LF_BAND);
+    let hf_power = band_power(&frequencies, &psd, HF_BAND);
+    let lf_hf = lf_power + hf_power;
+
+    // Poincaré ellipse axes from the successive-difference and overall spread
+    let sd1 = (sdsd * sdsd / 2.0).sqrt();
+    let sd2 = (2.0 * sdnn * sdnn - sd1 * sd1).max(0.0).sqrt();
+    let corrected_beats_percent = corrected.iter().filter(|&&c| c).count() as f64 / rr.len() as f64 * 100.0;
+
+    Ok(HrvMetrics {
+        start: times[0],
+        end: times[times.len() - 1],
+        beats: rr.len(),
+        mean_rr,
+        mean_heart_rate: 60_000.0 / mean_rr,
+        sdnn,
+        rmssd,
+        sdsd,
+        nn50,
+        pnn50: nn50 as f64 / diffs.len() as f64 * 100.0,
+        vlf_power,
+        lf_power,
+        hf_power,
+        lf_hf_ratio: (hf_power > 0.0).then(|| lf_power / hf_power),
+        lf_nu: (lf_hf > 0.0).then(|| lf_power / lf_hf * 100.0),
+        hf_nu: (lf_hf > 0.0).then(|| hf_power / lf_hf * 100.0),
+        sd1,
+        sd2,
+        dfa_alpha1: dfa_alpha(rr, DFA_SHORT_SCALES),
+        sample_entropy: (rr.len() <= SAMPLE_ENTROPY_MAX_BEATS)
+            .then(|| sample_entropy(rr, SAMPLE_ENTROPY_M, SAMPLE_ENTROPY_R * sdnn))
+            .flatten(),
+        corrected_beats_percent,
+        reliable: corrected_beats_percent <= config.artifacts.max_corrected_percent,
+    })
+}
+
+/// The series to analyse, which intervals were corrected, and the artifacts found.
+fn corrected_series(series: &RrSeries, config: &HrvConfig) -> (RrSeries, Vec<bool>, Vec<RrArtifact>) {
+    if !config.correct_artifacts {
+        return (series.clone(), vec![false; series.intervals.len()], Vec::new());
+    }
+    let correction = rr_artifacts::correct_artifacts(series, &config.artifacts);
+    (correction.series, correction.corrected, correction.artifacts)
+}
+
+pub fn analyze_hrv(series: &RrSeries, config: &HrvConfig) -> Result<HrvAnalysis, String> {
+    let (series, corrected, artifacts) = corrected_series(series, config);
+    let times = series.beat_times();
+    let overall = hrv_metrics(&times, &series.intervals, &corrected, config)?;
+
+    let window_ms = config.window_ms();
+    let mut windows = Vec::new();
+    let mut start = times[0];
+    let (mut lo, mut hi) = (0, 0);
+    while start.saturating_add(window_ms) <= times[times.len() - 1] + 1 {
+        while lo < times.len() && times[lo] < start {
+            lo += 1;
+        }
+        hi = hi.max(lo);
+        while hi < times.len() && times[hi] < start + window_ms {
+            hi += 1;
+        }
+        if hi - lo >= MIN_WINDOW_BEATS {
+            windows.push(hrv_metrics(&times[lo..hi], &series.intervals[lo..hi], &corrected[lo..hi], config)?);
+        }
+        start = start.saturating_add(config.step_ms());
+    }
+    Ok(HrvAnalysis { overall, windows, artifacts })
+}
+
+/// Recent beats from a live source, oldest first.
+#[derive(Debug, Default)]
+pub struct LiveHrv {
+    beats: VecDeque<(i64, f64)>,
+}
+
+impl LiveHrv {
+    /// Appends beats, continuing from the last one when `start` is not given.
+    pub fn push(&mut self, start: Option<i64>, intervals: &[f64]) -> Result<(), String> {
+        let start = match (start, self.beats.back()) {
+            (Some(start), _) => start,
+            (None, Some(&(last, _))) => last + intervals.first().map_or(0, |rr| rr.round() as i64),
+            (None, None) => return Err("No RR intervals received yet to continue from".to_string()),
+        };
+        let series = RrSeries { start, intervals: intervals.to_vec() };
+        for (time, &rr) in series.beat_times().into_iter().zip(intervals) {
+            if self.beats.back().is_none_or(|&(last, _)| time > last) {
+                self.beats.push_back((time, rr));
+            }
+        }
+        let cutoff = self.beats.back().map_or(0, |&(t, _)| t - (LIVE_RETENTION_SECONDS * 1000.0) as i64);
+        while self.beats.front().is_some_and(|&(t, _)| t < cutoff) {
+            self.beats.pop_front();
+        }
+        Ok(())
+    }
+
+    /// The last `window_seconds` of beats as a series of their own.
+    pub fn recent(&self, config: &HrvConfig) -> Result<RrSeries, String> {
+        let &(last, _) = self.beats.back().ok_or("No RR intervals received yet")?;
+        let from = last.saturating_sub(config.window_ms());
+        let (times, intervals): (Vec<i64>, Vec<f64>) = self.beats.iter().filter(|(t, _)| *t > from).copied().unzip();
+        Ok(RrSeries { start: times[0], intervals })
+    }
+}
+
+/// Metrics over a stretch of live beats, after artifact correction.
+pub fn live_metrics(recent: &RrSeries, config: &HrvConfig) -> Result<HrvMetrics, String> {
+    let (series, corrected, _) = corrected_series(recent, config);
+    hrv_metrics(&series.beat_times(), &series.intervals, &corrected, config)
+}
+
+pub type HrvState = Arc<Mutex<LiveHrv>>;
+
+#[tauri::command]
+pub async fn calculate_hrv(series: RrSeries, config: Option<HrvConfig>) -> Result<HrvAnalysis, String> {
+    let config = config.unwrap_or_default();
+    config.validate()?;
+    series.validate()?;
+
+    tauri::async_runtime::spawn_blocking(move || analyze_hrv(&series, &config))
+        .await
+        .map_err(|e| format!("Failed to calculate HRV: {}", e))?
+}
+
+/// Feeds beat-to-beat intervals from a live source, such as a chest strap.
+#[tauri::command]
+pub fn push_rr_intervals(state: tauri::State<'_, HrvState>, series: RrSeries, continues: bool) -> Result<(), String> {
+    series.validate()?;
+    let mut live = state.lock().map_err(|e| format!("Failed to lock live HRV: {}", e))?;
+    live.push((!continues).then_some(series.start), &series.intervals)
+}
+
+#[tauri::command]
+pub async fn get_live_hrv(state: tauri::State<'_, HrvState>, config: Option<HrvConfig>) -> Result<HrvMetrics, String> {
+    let config = config.unwrap_or_default();
+    config.validate()?;
+    // Copy the beats out so pushes are not held up while the metrics are computed
+    let recent = state
+        .lock()
+        .map_err(|e| format!("Failed to lock live HRV: {}", e))?
+        .recent(&config)?;
+
+    tauri::async_runtime::spawn_blocking(move || live_metrics(&recent, &config))
+        .await
+        .map_err(|e| format!("Failed to calculate HRV: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2026-03-02 00:00 UTC.
+    const START: i64 = 1_772_409_600_000;
+
+    /// Beats whose intervals follow `rr(t)` at each beat time, t in seconds.
+    fn tachogram(seconds: f64, rr: impl Fn(f64) -> f64) -> RrSeries {
+        let (mut t, mut intervals) = (0.0, Vec::new());
+        while t < seconds {
+            let interval = rr(t);
+            intervals.push(interval);
+            t += interval / 1000.0;
+        }
+        RrSeries { start: START, intervals }
+    }
+
+    /// Uniform noise in [-0.5, 0.5).
+    fn noise(count: usize, mut seed: u64) -> Vec<f64> {
+        (0..count)
+            .map(|_| {
+                seed = seed.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1_442_695_040_888_963_407);
+                (seed >> 11) as f64 / (1u64 << 53) as f64 - 0.5
+            })
+            .collect()
+    }
+
+    fn metrics(series: &RrSeries, config: &HrvConfig) -> HrvMetrics {
+        let corrected = vec![false; series.intervals.len()];
+        hrv_metrics(&series.beat_times(), &series.intervals, &corrected, config).unwrap()
+    }
+
+    /// 40 ms at 0.1 Hz (800 ms² LF) and 20 ms at 0.25 Hz (200 ms² HF).
+    fn two_tones(seconds: f64) -> RrSeries {
+        tachogram(seconds, |t| 1000.0 + 40.0 * (2.0 * PI * 0.1 * t).sin() + 20.0 * (2.0 * PI * 0.25 * t).sin())
+    }
+
+    fn close(actual: f64, expected: f64, tolerance: f64) {
+        assert!((actual - expected).abs() <= tolerance, "{} is not within {} of {}", actual, tolerance, expected);
+    }
+
+    #[test]
+    fn time_domain_matches_hand_worked_values() {
+        let series = RrSeries { start: START, intervals: vec![800.0, 850.0, 800.0, 900.0, 800.0] };
+        let m = metrics(&series, &HrvConfig::default());
+        // Differences 50, -50, 100, -100
+        close(m.mean_rr, 830.0, 1e-9);
+        close(m.mean_heart_rate, 60_000.0 / 830.0, 1e-9);
+        close(m.sdnn, 2000f64.sqrt(), 1e-9);
+        close(m.rmssd, 6250f64.sqrt(), 1e-9);
+        close(m.sdsd, (25_000.0f64 / 3.0).sqrt(), 1e-9);
+        assert_eq!(m.nn50, 2);
+        close(m.pnn50, 50.0, 1e-9);
+        assert_eq!(m.end - m.start, 3350);
+    }
+
+    #[test]
+    fn poincare_axes_follow_the_difference_and_overall_spread() {
+        // Steady lengthening: no beat-to-beat scatter, all spread along the identity line
+        let ramp = RrSeries { start: START, intervals: (0..60).map(|i| 800.0 + i as f64).collect() };
+        let m = metrics(&ramp, &HrvConfig::default());
+        close(m.sd1, 0.0, 1e-9);
+        close(m.sd2, 2f64.sqrt() * m.sdnn, 1e-9);
+
+        // Alternating beats: all scatter across it
+        let intervals = (0..60).map(|i| 800.0 + 50.0 * (i % 2) as f64).collect();
+        let alternating = RrSeries { start: START, intervals };
+        let m = metrics(&alternating, &HrvConfig::default());
+        close(m.sd1, m.sdsd / 2f64.sqrt(), 1e-9);
+        close(m.sd1 * m.sd1 + m.sd2 * m.sd2, 2.0 * m.sdnn * m.sdnn, 1e-6);
+        assert!(m.sd2 < 0.1 * m.sd1, "{} {}", m.sd1, m.sd2);
+    }
+
+    #[test]
+    fn lomb_recovers_the_band_powers_of_a_two_tone_tachogram() {
+        let m = metrics(&two_tones(300.0), &HrvConfig::default());
+        close(m.lf_power, 800.0, 40.0);
+        close(m.hf_power, 200.0, 10.0);
+        close(m.lf_hf_ratio.unwrap(), 4.0, 0.2);
+        close(m.lf_nu.unwrap(), 80.0, 1.0);
+        close(m.hf_nu.unwrap(), 20.0, 1.0);
+        assert!(m.vlf_power < 10.0, "{}", m.vlf_power);
+    }
+
+    #[test]
+    fn welch_recovers_the_band_powers_of_a_two_tone_tachogram() {
+        let config = HrvConfig { spectral_method: SpectralMethod::Welch, ..Default::default() };
+        let m = metrics(&two_tones(600.0), &config);
+        close(m.lf_power, 800.0, 40.0);
+        close(m.hf_power, 200.0, 10.0);
+        close(m.lf_hf_ratio.unwrap(), 4.0, 0.2);
+        assert!(m.vlf_power < 10.0, "{}", m.vlf_power);
+    }
+
+    #[test]
+    fn dfa_alpha1_separates_white_from_brown_noise() {
+        let white = noise(4000, 7);
+        close(dfa_alpha(&white, DFA_SHORT_SCALES).unwrap(), 0.5, 0.1);
+        let mut sum = 0.0;
+        let brown: Vec<f64> = white
+            .iter()
+            .map(|v| {
+                sum += v;
+                sum
+            })
+            .collect();
+        close(dfa_alpha(&brown, DFA_SHORT_SCALES).unwrap(), 1.5, 0.1);
+        // Too few beats for three box sizes
+        assert_eq!(dfa_alpha(&white[..11], DFA_SHORT_SCALES), None);
+    }
+
+    #[test]
+    fn sample_entropy_matches_hand_worked_and_theoretical_values() {
+        // Templates 0 and 4, 0 and 6, 4 and 6, 1 and 5 match; only 0 and 4 continue to match
+        let rr = [1.0, 2.0, 1.0, 3.0, 1.0, 2.0, 1.0, 2.0, 4.0];
+        close(sample_entropy(&rr, 2, 0.5).unwrap(), 4f64.ln(), 1e-12);
+        // A strictly periodic series is fully predictable
+        let periodic: Vec<f64> = (0..40).map(|i| [800.0, 900.0, 850.0][i % 3]).collect();
+        assert_eq!(sample_entropy(&periodic, 2, 10.0), Some(0.0));
+        assert_eq!(sample_entropy(&[1.0, 5.0, 9.0, 13.0], 2, 0.5), None);
+
+        // Independent uniform values on [0, 1) match within r with probability 2r - r²
+        let white = noise(800, 11);
+        let r = 0.2 * standard_deviation(&white);
+        close(sample_entropy(&white, 2, r).unwrap(), -(2.0 * r - r * r).ln(), 0.1);
+    }
+
+    #[test]
+    fn sample_entropy_is_left_to_windows_on_long_recordings() {
+        let series = two_tones(1200.0);
+        assert!(series.intervals.len() > SAMPLE_ENTROPY_MAX_BEATS);
+        let analysis = analyze_hrv(&series, &HrvConfig::default()).unwrap();
+        assert_eq!(analysis.overall.sample_entropy, None);
+        assert_eq!(analysis.windows.len(), 3);
+        assert!(analysis.windows.iter().all(|w| w.sample_entropy.is_some()));
+        assert!(analysis.artifacts.is_empty());
+    }
+
+    #[test]
+    fn validation_uses_whole_milliseconds() {
+        assert!(HrvConfig::default().validate().is_ok());
+        for step_seconds in [0.0, 0.0004, -1.0, f64::NAN] {
+            assert!(HrvConfig { step_seconds, ..Default::default() }.validate().is_err(), "{}", step_seconds);
+        }
+        assert!(HrvConfig { step_seconds: 0.001, ..Default::default() }.validate().is_ok());
+        for window_seconds in [29.9995, f64::NAN] {
+            assert!(HrvConfig { window_seconds, ..Default::default() }.validate().is_err(), "{}", window_seconds);
+        }
+        assert!(HrvConfig { resample_hz: 0.5, ..Default::default() }.validate().is_err());
+    }
+
+    #[test]
+    fn live_buffer_continues_beats_across_pushes() {
+        let config = HrvConfig::default();
+        let mut live = LiveHrv::default();
+        assert!(live.recent(&config).is_err());
+        let series = two_tones(300.0);
+        let (first, rest) = series.intervals.split_at(100);
+        // Continuing needs a beat to continue from
+        assert!(live.push(None, first).is_err());
+        assert!(live.recent(&config).is_err());
+        live.push(Some(START), first).unwrap();
+        live.push(None, rest).unwrap();
+        let m = live_metrics(&live.recent(&config).unwrap(), &config).unwrap();
+        assert_eq!(m.beats, series.intervals.len());
+        assert_eq!(m.start, START);
+        assert_eq!(m.end, *series.beat_times().last().unwrap());
+    }
+}
@@ -1,25 +1,58 @@
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
 use serde::{Serialize, Deserialize};
-use tauri::{AppHandle, Manager};
-use chrono::Timelike;
+use chrono::{TimeZone, Utc};
+use chrono_tz::Tz;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_notification::NotificationExt;
 use std::sync::{Arc, Mutex};
 use std::sync::atomic::{AtomicBool, Ordering};
-use tokio::time::{interval, Duration};
-
+use tokio::time::{sleep, Duration};
+
+mod anchor;
+mod awakening;
+mod calendar;
+mod chronotype;
+mod circadian_markers;
+mod confidence;
+mod cosinor;
+mod cycle_detection;
+mod focus;
+mod forecast;
 mod healthkit_ffi;
+mod hrv;
+mod notifications;
+mod periodogram;
+mod phase_estimator;
+mod phase_events;
+mod rest_activity;
+mod rr_artifacts;
+mod settings;
+mod sleep_metrics;
+mod sleep_regularity;
+mod sleep_scoring;
+mod stats;
+mod timeseries;
+mod timezone;
+mod tray_menu;
+mod ultradian;
+mod wavelet;
+
+use anchor::{AnchorState, CycleAnchor};
+use confidence::HistoryState;
+use focus::{FocusState, FocusTimer, FOCUS_COMPLETED_EVENT};
+use hrv::HrvState;
+use notifications::{NotificationSettings, NotificationState};
+use phase_estimator::{EstimatorState, PhaseEstimate};
+use phase_events::{PhaseTracker, PHASE_CHANGED_EVENT};
+use settings::{SettingsState, SettingsStore};
+use tray_menu::{TrayControlState, TrayMenu, TrayMenuView};
+use ultradian::{CycleReading, TrayDisplayMode, UltradianClock};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct VariabilityResult {
     pub intradaily_variability: f64,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct SleepEfficiencyResult {
-    pub sleep_efficiency: f64,
-    pub total_sleep_minutes: f64,
-    pub time_in_bed_minutes: f64,
-}
-
 #[tauri::command]
 fn greet(name: &str) -> String {
     format!("Hello, {}! You've been greeted from Rust!", name)
@@ -48,26 +81,6 @@ fn calculate_intradaily_variability(activity_data: Vec<f64>) -> VariabilityResul
     VariabilityResult { intradaily_variability: iv }
 }
 
-#[tauri::command]
-fn calculate_sleep_efficiency(activity_data: Vec<f64>, sleep_threshold: f64) -> SleepEfficiencyResult {
-    let n = activity_data.len();
-    if n == 0 {
-        return SleepEfficiencyResult { sleep_efficiency: 0.0, total_sleep_minutes: 0.0, time_in_bed_minutes: 0.0 };
-    }
-    // identify sleep minutes where activity below threshold
-    let mut sleep_minutes = 0usize;
-    for &val in &activity_data {
-        if val <= sleep_threshold { sleep_minutes += 1; }
-    }
-    let time_in_bed = n as f64; // assuming 1-minute samples
-    let efficiency = (sleep_minutes as f64 / time_in_bed) * 100.0;
-    SleepEfficiencyResult {
-        sleep_efficiency: efficiency,
-        total_sleep_minutes: sleep_minutes as f64,
-        time_in_bed_minutes: time_in_bed,
-    }
-}
-
 #[tauri::command]
 fn show_menubar_window(app: AppHandle) {
     if let Some(window) = app.get_webview_window("menubar") {
@@ -108,6 +121,11 @@ pub struct WidgetCycleData {
 
 #[tauri::command]
 fn write_widget_data(data: WidgetCycleData) -> Result<(), String> {
+    save_widget_data(&data)
+}
+
+/// Writes widget data to the shared app group container read by the macOS widget.
+fn save_widget_data(data: &WidgetCycleData) -> Result<(), String> {
     #[cfg(target_os = "macos")]
     {
         use std::fs;
@@ -124,7 +142,7 @@ fn write_widget_data(data: WidgetCycleData) -> Result<(), String> {
         let file_path = format!("{}/widget-data.json", container_path);
         
         // Serialize data to JSON
-        let json_data = serde_json::to_string(&data).map_err(|e| format!("Failed to serialize data: {}", e))?;
+        let json_data = serde_json::to_string(data).map_err(|e| format!("Failed to serialize data: {}", e))?;
         
         // Write to file
         fs::write(file_path, json_data).map_err(|e| format!("Failed to write widget data: {}", e))?;
@@ -134,6 +152,7 @@ fn write_widget_data(data: WidgetCycleData) -> Result<(), String> {
     
     #[cfg(not(target_os = "macos"))]
     {
+        let _ = data;
         Ok(()) // No-op on non-macOS platforms
     }
 }
@@ -151,8 +170,8 @@ impl TrayUpdaterState {
     }
 }
 
-#[tauri::command]
-async fn start_tray_updater(app: AppHandle, state: tauri::State<'_, Arc<Mutex<TrayUpdaterState>>>) -> Result<(), String> {
+/// Starts the 1-second tray title loop unless it is already running.
+fn spawn_tray_updater(app: AppHandle, state: Arc<Mutex<TrayUpdaterState>>) -> Result<(), String> {
     let state_lock = state.lock().map_err(|e| format!("Failed to lock state: {}", e))?;
 
     // Check if already running
@@ -166,67 +185,157 @@ async fn start_tray_updater(app: AppHandle, state: tauri::State<'_, Arc<Mutex<Tr
     // Release lock before spawning task
     drop(state_lock);
 
+    let anchor_state = app.state::<AnchorState>().inner().clone();
+    let settings_state = app.state::<SettingsState>().inner().clone();
+    let notification_state = app.state::<NotificationState>().inner().clone();
+    let estimator_state = app.state::<EstimatorState>().inner().clone();
+    let history_state = app.state::<HistoryState>().inner().clone();
+    let focus_state = app.state::<FocusState>().inner().clone();
+    let controls_state = app.state::<TrayControlState>().inner().clone();
+
     // Spawn background task
-    tokio::spawn(async move {
-        let mut ticker = interval(Duration::from_secs(1));
+    tauri::async_runtime::spawn(async move {
+        let mut tracker = PhaseTracker::default();
+        let mut shown_menu: Option<(TrayMenuView, TrayMenu)> = None;
 
         while is_running.load(Ordering::Relaxed) {
-            ticker.tick().await;
-
-            // Calculate tray title
-            let now = chrono::Local::now();
-            let total_minutes = now.hour() as f64 * 60.0 + now.minute() as f64 + now.second() as f64 / 60.0;
-            let cycle_position = total_minutes % 90.0;
-
-            // Determine energy phase
-            let energy_phase = if cycle_position <= 5.0 {
-                "transition"
-            } else if cycle_position <= 60.0 {
-                "high"
-            } else if cycle_position <= 65.0 {
-                "transition"
-            } else {
-                "low"
+            // Re-read settings every tick so changes apply without a restart
+            let (clock, update_interval, notification_settings, zone, display_mode) = match settings_state.lock() {
+                Ok(store) => (
+                    store.current.clock(),
+                    store.current.tray_update_interval_seconds,
+                    store.current.notifications.clone(),
+                    store.current.zone(),
+                    store.current.tray_display_mode,
+                ),
+                Err(_) => (
+                    UltradianClock::default(),
+                    1,
+                    NotificationSettings::default(),
+                    timezone::active_zone(None),
+                    TrayDisplayMode::Countdown,
+                ),
             };
+            let tracking_paused = controls_state.lock().map(|controls| controls.tracking_paused).unwrap_or(false);
 
-            // Calculate time remaining
-            let time_remaining = if energy_phase == "high" || (energy_phase == "transition" && cycle_position <= 60.0) {
-                60.0 - cycle_position
-            } else {
-                90.0 - cycle_position
+            let now = timezone::now_in(zone);
+            let cycle_anchor = match anchor_state.lock() {
+                Ok(anchor) => anchor.clone(),
+                Err(poisoned) => poisoned.into_inner().clone(),
             };
-
-            let minutes_left = time_remaining.floor() as i32;
-            let seconds_left = ((time_remaining - minutes_left as f64) * 60.0).floor() as i32;
-
-            // Determine phase icon (6-phase system)
-            let phase_arrow = if cycle_position <= 15.0 {
-                "↗"
-            } else if cycle_position <= 30.0 {
-                "↑"
-            } else if cycle_position <= 45.0 {
-                "🔥"
-            } else if cycle_position <= 60.0 {
-                "⚡"
-            } else if cycle_position <= 75.0 {
-                "↘"
-            } else {
-                "😴"
+            let anchor = cycle_anchor.anchor_for(now);
+            let estimate = match estimator_state.lock() {
+                Ok(estimator) => estimator.estimate(now.with_timezone(&Utc)),
+                Err(_) => None,
+            };
+            let scheduled = clock.reading_at(now, anchor);
+            let offset = estimate.as_ref().map_or(0.0, |estimate| estimate.offset_minutes(&clock, &scheduled));
+            let reading = match &estimate {
+                Some(estimate) => estimate.apply(&clock, &scheduled),
+                None => scheduled,
             };
 
-            // Format title
-            let title = format!("{} {:02}:{:02}", phase_arrow, minutes_left, seconds_left);
+            // A running focus session replaces the phase countdown in the tray
+            let (focus_title, completed) = match focus_state.lock() {
+                Ok(mut timer) => {
+                    let completed = timer.tick(now.with_timezone(&Utc));
+                    (timer.active().map(|session| session.tray_title(now.with_timezone(&Utc))), completed)
+                }
+                Err(_) => (None, None),
+            };
+            if let Some(record) = completed {
+                let _ = app.emit(FOCUS_COMPLETED_EVENT, &record);
+                if notification_settings.enabled {
+                    let _ = app
+                        .notification()
+                        .builder()
+                        .title("Focus session complete")
+                        .body(format!("You focused for {:.0} min. Time to wind down.", record.actual_minutes))
+                        .show();
+                }
+            }
 
             // Update tray title
+            let focus_running = focus_title.is_some();
+            let title = tray_menu::tray_title(&reading, focus_title, tracking_paused, display_mode);
             if let Some(tray) = app.tray_by_id("main") {
                 let _ = tray.set_title(Some(title));
             }
+
+            let phase_event = if tracking_paused {
+                // Start fresh on resume rather than reporting the paused stretch as a transition
+                tracker = PhaseTracker::default();
+                None
+            } else {
+                tracker.observe(&reading, now)
+            };
+
+            // Rebuild the tray menu when a phase boundary is crossed or its contents change,
+            // otherwise just refresh the time remaining
+            let snoozed_until = notification_state
+                .lock()
+                .ok()
+                .and_then(|scheduler| scheduler.snoozed_until(now.with_timezone(&Utc)));
+            let view = TrayMenuView {
+                upcoming: if tracking_paused {
+                    Vec::new()
+                } else {
+                    tray_menu::upcoming_transitions(&clock, &cycle_anchor, now, offset)
+                },
+                focus_running,
+                focus_available: !tracking_paused && focus::can_start(&clock, &reading),
+                snoozed_until: snoozed_until.map(|until| until.with_timezone(&zone).format("%-H:%M").to_string()),
+                display_mode,
+                tracking_paused,
+            };
+            let current_text = tray_menu::current_phase_text(&reading, tracking_paused);
+            match &shown_menu {
+                Some((shown, menu)) if phase_event.is_none() && *shown == view => {
+                    let _ = menu.current.set_text(&current_text);
+                }
+                _ => {
+                    if let (Some(tray), Ok(menu)) =
+                        (app.tray_by_id("main"), tray_menu::build_menu(&app, &view, &current_text))
+                    {
+                        let _ = tray.set_menu(Some(menu.menu.clone()));
+                        shown_menu = Some((view, menu));
+                    }
+                }
+            }
+
+            // Let the frontend and widget know when a phase boundary is crossed
+            if let Some(event) = phase_event {
+                let _ = app.emit(PHASE_CHANGED_EVENT, &event);
+                let history_days = history_state.lock().map(|history| history.days).unwrap_or(0);
+                let report = confidence::assess(estimate.as_ref(), &cycle_anchor, history_days, now);
+                let _ = save_widget_data(&widget_data_at(&reading, now, estimate.as_ref(), report.confidence));
+            }
+
+            let due = match notification_state.lock() {
+                Ok(mut scheduler) if !tracking_paused => scheduler.due(&notification_settings, &clock, &reading, now),
+                _ => Vec::new(),
+            };
+            for notification in due {
+                let _ = app
+                    .notification()
+                    .builder()
+                    .title(notification.title)
+                    .body(notification.body)
+                    .show();
+            }
+
+            sleep(Duration::from_secs(update_interval)).await;
         }
     });
 
     Ok(())
 }
 
+#[tauri::command]
+async fn start_tray_updater(app: AppHandle, state: tauri::State<'_, Arc<Mutex<TrayUpdaterState>>>) -> Result<(), String> {
+    spawn_tray_updater(app, state.inner().clone())
+}
+
 #[tauri::command]
 async fn stop_tray_updater(state: tauri::State<'_, Arc<Mutex<TrayUpdaterState>>>) -> Result<(), String> {
     let state_lock = state.lock().map_err(|e| format!("Failed to lock state: {}", e))?;
@@ -235,93 +344,61 @@ async fn stop_tray_updater(state: tauri::State<'_, Arc<Mutex<TrayUpdaterState>>>
 }
 
 #[tauri::command]
-fn get_widget_data() -> WidgetCycleData {
-    let current_time = chrono::Utc::now();
-    let total_minutes = current_time.hour() as f64 * 60.0 + current_time.minute() as f64 + current_time.second() as f64 / 60.0;
-    let cycle_position = total_minutes % 90.0;
-    let cycle_number = (total_minutes / 90.0).floor() as i32 + 1;
-    
-    // Energy phase logic (matching main app)
-    let (energy_phase, energy_intensity) = if cycle_position <= 5.0 {
-        ("transition".to_string(), 0.4 + (cycle_position / 5.0) * 0.4)
-    } else if cycle_position <= 60.0 {
-        let progress = (cycle_position - 5.0) / 55.0;
-        ("high".to_string(), 0.5 + 0.4 * (progress * std::f64::consts::PI).sin())
-    } else if cycle_position <= 65.0 {
-        ("transition".to_string(), 0.8 - ((cycle_position - 60.0) / 5.0) * 0.4)
-    } else {
-        let progress = (cycle_position - 65.0) / 25.0;
-        ("low".to_string(), 0.2 + 0.2 * (progress * std::f64::consts::PI).sin())
-    };
-    
-    // Time remaining calculation
-    let time_remaining: f64 = if energy_phase == "high" || (energy_phase == "transition" && cycle_position <= 60.0) {
-        60.0 - cycle_position
-    } else {
-        90.0 - cycle_position
+fn get_widget_data(
+    settings_state: tauri::State<'_, SettingsState>,
+    anchor_state: tauri::State<'_, AnchorState>,
+    estimator_state: tauri::State<'_, EstimatorState>,
+    history_state: tauri::State<'_, HistoryState>,
+) -> Result<WidgetCycleData, String> {
+    let (clock, zone) = {
+        let store = settings_state.lock().map_err(|e| format!("Failed to lock settings: {}", e))?;
+        (store.current.clock(), store.current.zone())
     };
-    
-    let minutes_left = time_remaining.floor() as i32;
-    let seconds_left = ((time_remaining - minutes_left as f64) * 60.0).floor() as i32;
-    
-    // Phase icon (6-phase system)
-    let phase_icon = if cycle_position <= 15.0 {
-        "↗".to_string()
-    } else if cycle_position <= 30.0 {
-        "↑".to_string()
-    } else if cycle_position <= 45.0 {
-        "🔥".to_string()
-    } else if cycle_position <= 60.0 {
-        "⚡".to_string()
-    } else if cycle_position <= 75.0 {
-        "↘".to_string()
-    } else {
-        "😴".to_string()
+    let now = timezone::now_in(zone);
+    let cycle_anchor = anchor_state
+        .lock()
+        .map_err(|e| format!("Failed to lock anchor: {}", e))?
+        .clone();
+    let estimate = estimator_state
+        .lock()
+        .map_err(|e| format!("Failed to lock estimator: {}", e))?
+        .estimate(now.with_timezone(&Utc));
+    let history_days = history_state
+        .lock()
+        .map_err(|e| format!("Failed to lock history: {}", e))?
+        .days;
+    let report = confidence::assess(estimate.as_ref(), &cycle_anchor, history_days, now);
+    let scheduled = clock.reading_at(now, cycle_anchor.anchor_for(now));
+    let reading = match &estimate {
+        Some(estimate) => estimate.apply(&clock, &scheduled),
+        None => scheduled,
     };
-    
-    // Phase label
-    let phase_label = if cycle_position <= 15.0 {
-        "Rising Energy".to_string()
-    } else if cycle_position <= 30.0 {
-        "Building Energy".to_string()
-    } else if cycle_position <= 45.0 {
-        "Peak Energy".to_string()
-    } else if cycle_position <= 60.0 {
-        "Peak Flow".to_string()
-    } else if cycle_position <= 75.0 {
-        "Winding Down".to_string()
-    } else {
-        "Rest Phase".to_string()
-    };
-    
-    // Background color (HSL)
-    let background_color = if energy_phase == "high" {
-        format!("hsl({}, 70%, {}%)", 120.0 + energy_intensity * 60.0, 50.0 + energy_intensity * 20.0)
-    } else if energy_phase == "low" {
-        format!("hsl(220, 70%, {}%)", 30.0 + energy_intensity * 30.0)
-    } else {
-        format!("hsl(170, 70%, {}%)", 40.0 + energy_intensity * 20.0)
-    };
-    
-    // Next phase time
-    let next_phase_minutes = total_minutes + time_remaining;
-    let next_phase_hour = ((next_phase_minutes / 60.0).floor() as i32) % 24;
-    let next_phase_min = (next_phase_minutes % 60.0) as i32;
-    let next_phase_time = format!("{}:{:02}", next_phase_hour, next_phase_min);
-    
+
+    Ok(widget_data_at(&reading, now, estimate.as_ref(), report.confidence))
+}
+
+fn widget_data_at(
+    reading: &CycleReading,
+    now: chrono::DateTime<Tz>,
+    estimate: Option<&PhaseEstimate>,
+    confidence: f64,
+) -> WidgetCycleData {
+    let next_phase_time = reading.next_transition_at(now).format("%-H:%M").to_string();
+    let heart_rate = estimate.and_then(|estimate| estimate.current_heart_rate(now.with_timezone(&Utc)));
+
     WidgetCycleData {
-        cycle_position,
-        phase_icon,
-        phase_label,
-        energy_phase,
-        energy_intensity,
-        time_remaining_minutes: minutes_left,
-        time_remaining_seconds: seconds_left,
+        cycle_position: reading.cycle_position,
+        phase_icon: reading.phase_icon.clone(),
+        phase_label: reading.phase_label.clone(),
+        energy_phase: reading.energy_phase.as_str().to_string(),
+        energy_intensity: reading.energy_intensity,
+        time_remaining_minutes: reading.minutes_left(),
+        time_remaining_seconds: reading.seconds_left(),
         next_phase_time,
-        cycle_number,
-        heart_rate: None, // Will be populated by live data if available
-        confidence: 0.75,
-        background_color,
+        cycle_number: reading.cycle_number,
+        heart_rate: heart_rate.map(|rate| rate.round() as i32),
+        confidence,
+        background_color: reading.background_color(),
     }
 }
 
@@ -333,11 +410,31 @@ pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_updater::Builder::new().build())
+        .plugin(tauri_plugin_notification::init())
         .manage(Arc::new(Mutex::new(TrayUpdaterState::new())))
+        .manage(NotificationState::default())
+        .manage(EstimatorState::default())
+        .manage(HistoryState::default())
+        .manage(HrvState::default())
+        .manage(TrayControlState::default())
         .invoke_handler(tauri::generate_handler![
             greet,
             calculate_intradaily_variability,
-            calculate_sleep_efficiency,
+            cycle_detection::detect_ultradian_cycles,
+            rest_activity::calculate_rest_activity_metrics,
+            cosinor::fit_cosinor_model,
+            periodogram::calculate_periodogram,
+            wavelet::calculate_wavelet_scalogram,
+            sleep_scoring::score_sleep_wake,
+            sleep_metrics::calculate_sleep_period_metrics,
+            sleep_regularity::calculate_sleep_regularity,
+            chronotype::calculate_chronotype,
+            circadian_markers::estimate_circadian_phase,
+            awakening::detect_awakening,
+            hrv::calculate_hrv,
+            hrv::push_rr_intervals,
+            hrv::get_live_hrv,
+            rr_artifacts::correct_rr_intervals,
             healthkit_ffi::request_healthkit_permissions,
             healthkit_ffi::start_healthkit_monitoring,
             healthkit_ffi::stop_healthkit_monitoring,
@@ -347,11 +444,71 @@ pub fn run() {
             hide_menubar_window,
             update_tray_title,
             get_widget_data,
+            anchor::get_cycle_anchor,
+            anchor::set_cycle_anchor,
+            anchor::record_wake_time,
+            forecast::get_phase_forecast,
+            calendar::export_phase_calendar,
+            focus::start_focus_session,
+            focus::pause_focus_session,
+            focus::resume_focus_session,
+            focus::stop_focus_session,
+            focus::get_focus_status,
+            focus::get_focus_history,
+            phase_estimator::get_phase_estimate,
+            confidence::get_confidence,
+            confidence::set_history_days,
+            settings::get_settings,
+            settings::update_settings,
+            settings::reset_settings,
             write_widget_data,
             start_tray_updater,
             stop_tray_updater
         ])
         .setup(|app| {
+            let config_dir = app.path().app_config_dir()?;
+            let settings = SettingsStore::load(&config_dir);
+            let anchor = CycleAnchor::new(settings.current.anchor_mode, settings.current.wake_time);
+            app.manage(AnchorState::new(Mutex::new(anchor)));
+            app.manage(SettingsState::new(Mutex::new(settings)));
+            app.manage(FocusState::new(Mutex::new(FocusTimer::load(&config_dir))));
+
+            // Feed HealthKit heart rate samples into the live phase estimator
+            let mut healthkit = healthkit_ffi::HealthKitManager::new(app.handle().clone());
+            if let Some(receiver) = healthkit.take_receiver() {
+                let settings_state = app.state::<SettingsState>().inner().clone();
+                let anchor_state = app.state::<AnchorState>().inner().clone();
+                let estimator_state = app.state::<EstimatorState>().inner().clone();
+                let controls_state = app.state::<TrayControlState>().inner().clone();
+                std::thread::spawn(move || {
+                    for sample in receiver {
+                        if controls_state.lock().map(|controls| controls.tracking_paused).unwrap_or(false) {
+                            continue;
+                        }
+                        let Ok(store) = settings_state.lock() else { continue };
+                        let clock = store.current.clock();
+                        let zone = store.current.zone();
+                        drop(store);
+                        let sampled_at = Utc
+                            .timestamp_millis_opt(sample.timestamp as i64)
+                            .single()
+                            .unwrap_or_else(Utc::now)
+                            .with_timezone(&zone);
+                        let scheduled_fraction = match anchor_state.lock() {
+                            Ok(anchor) => {
+                                clock.reading_at(sampled_at, anchor.anchor_for(sampled_at)).cycle_position
+                                    / clock.cycle_length
+                            }
+                            Err(_) => continue,
+                        };
+                        if let Ok(mut estimator) = estimator_state.lock() {
+                            estimator.observe(&sample, &clock, scheduled_fraction);
+                        }
+                    }
+                });
+            }
+
+            // Placeholder until the tray updater builds the phase menu on its first tick
             let show_item = MenuItem::with_id(app, "show", "Show Dashboard", true, None::<&str>)?;
             let quit_item = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
             let menu = Menu::with_items(app, &[&show_item, &quit_item])?;
@@ -359,18 +516,7 @@ pub fn run() {
             let _tray = TrayIconBuilder::with_id("main")
                 .menu(&menu)
                 .title("Circada")
-                .on_menu_event(|app, event| match event.id.as_ref() {
-                    "show" => {
-                        if let Some(window) = app.get_webview_window("main") {
-                            let _ = window.show();
-                            let _ = window.set_focus();
-                        }
-                    }
-                    "quit" => {
-                        app.exit(0);
-                    }
-                    _ => {}
-                })
+                .on_menu_event(|app, event| tray_menu::handle_menu_event(app, event.id.as_ref()))
                 .on_tray_icon_event(|_tray, event| {
                     if let tauri::tray::TrayIconEvent::Click { .. } = event {
                         println!("Tray icon clicked!");
@@ -385,85 +531,8 @@ pub fn run() {
                 .build(app)?;
 
             // Auto-start the Rust-based tray updater
-            let app_handle = app.handle().clone();
             let state = app.state::<Arc<Mutex<TrayUpdaterState>>>().inner().clone();
-
-            // Start the tray updater in a background task
-            tauri::async_runtime::spawn(async move {
-                let state_lock = match state.lock() {
-                    Ok(lock) => lock,
-                    Err(_) => return,
-                };
-
-                // Check if already running
-                if state_lock.is_running.load(Ordering::Relaxed) {
-                    return;
-                }
-
-                state_lock.is_running.store(true, Ordering::Relaxed);
-                let is_running = Arc::clone(&state_lock.is_running);
-
-                // Release lock before spawning task
-                drop(state_lock);
-
-                // Spawn background task
-                tokio::spawn(async move {
-                    let mut ticker = tokio::time::interval(tokio::time::Duration::from_secs(1));
-
-                    while is_running.load(Ordering::Relaxed) {
-                        ticker.tick().await;
-
-                        // Calculate tray title
-                        let now = chrono::Local::now();
-                        let total_minutes = now.hour() as f64 * 60.0 + now.minute() as f64 + now.second() as f64 / 60.0;
-                        let cycle_position = total_minutes % 90.0;
-
-                        // Determine energy phase
-                        let energy_phase = if cycle_position <= 5.0 {
-                            "transition"
-                        } else if cycle_position <= 60.0 {
-                            "high"
-                        } else if cycle_position <= 65.0 {
-                            "transition"
-                        } else {
-                            "low"
-                        };
-
-                        // Calculate time remaining
-                        let time_remaining = if energy_phase == "high" || (energy_phase == "transition" && cycle_position <= 60.0) {
-                            60.0 - cycle_position
-                        } else {
-                            90.0 - cycle_position
-                        };
-
-                        let minutes_left = time_remaining.floor() as i32;
-                        let seconds_left = ((time_remaining - minutes_left as f64) * 60.0).floor() as i32;
-
-                        // Determine phase icon (6-phase system)
-                        let phase_arrow = if cycle_position <= 15.0 {
-                            "↗"
-                        } else if cycle_position <= 30.0 {
-                            "↑"
-                        } else if cycle_position <= 45.0 {
-                            "🔥"
-                        } else if cycle_position <= 60.0 {
-                            "⚡"
-                        } else if cycle_position <= 75.0 {
-                            "↘"
-                        } else {
-                            "😴"
-                        };
-
-                        // Format title
-                        let title = format!("{} {:02}:{:02}", phase_arrow, minutes_left, seconds_left);
-
-                        // Update tray title
-                        if let Some(tray) = app_handle.tray_by_id("main") {
-                            let _ = tray.set_title(Some(title));
-                        }
-                    }
-                });
-            });
+            let _ = spawn_tray_updater(app.handle().clone(), state);
 
             Ok(())
         })
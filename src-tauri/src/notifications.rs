@@ -0,0 +1,422 @@
+// Desktop notifications for selected phase transitions, driven by the same
+// readings the tray updater computes every tick.
+use chrono::{DateTime, NaiveTime, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+
+use crate::ultradian::{CycleReading, UltradianClock};
+
+/// Ticks further apart than this (e.g. after the machine slept) do not fire
+/// the transitions that were skipped over.
+const MAX_TICK_GAP_MINUTES: f64 = 5.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransitionKind {
+    /// The rising transition ends and the high-energy window opens.
+    PeakStart,
+    /// The high-energy window closes.
+    PeakEnd,
+    /// The falling transition ends and the low-energy rest window starts.
+    RestStart,
+    /// A new cycle begins.
+    CycleStart,
+}
+
+impl TransitionKind {
+    /// Cycle position (minutes) at which this transition happens.
+    fn position(&self, clock: &UltradianClock) -> f64 {
+        match self {
+            TransitionKind::PeakStart => clock.rise_end,
+            TransitionKind::PeakEnd => clock.high_end,
+            TransitionKind::RestStart => clock.fall_end,
+            TransitionKind::CycleStart => clock.cycle_length,
+        }
+    }
+
+    fn message(&self, lead_minutes: f64, clock: &UltradianClock) -> (String, String) {
+        let rest_minutes = (clock.cycle_length - clock.fall_end).round();
+        if lead_minutes > 0.0 {
+            let lead = lead_minutes.round();
+            return match self {
+                TransitionKind::PeakStart => (
+                    format!("Focus window opens in {} min", lead),
+                    "Line up the task you want to give your best energy to.".to_string(),
+                ),
+                TransitionKind::PeakEnd => (
+                    format!("Peak ending in {} min", lead),
+                    "Start wrapping up so you can stop before the dip.".to_string(),
+                ),
+                TransitionKind::RestStart => (
+                    format!("Rest phase in {} min", lead),
+                    "Energy is winding down. Plan a break.".to_string(),
+                ),
+                TransitionKind::CycleStart => (
+                    format!("New cycle in {} min", lead),
+                    "Energy will start rising again soon.".to_string(),
+                ),
+            };
+        }
+        match self {
+            TransitionKind::PeakStart => (
+                "Focus window open".to_string(),
+                "High-energy phase started. Good time for deep work.".to_string(),
+            ),
+            TransitionKind::PeakEnd => (
+                "Peak ended".to_string(),
+                "The high-energy window has closed.".to_string(),
+            ),
+            TransitionKind::RestStart => (
+                "Rest phase started".to_string(),
+                format!("Take a break. The low-energy phase lasts about {} min.", rest_minutes),
+            ),
+            TransitionKind::CycleStart => (
+                "New cycle started".to_string(),
+                "Energy is rising again.".to_string(),
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NotificationRule {
+    pub transition: TransitionKind,
+    pub enabled: bool,
+    /// Minutes before the transition to notify; 0 notifies when it happens.
+    pub lead_minutes: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QuietHours {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl QuietHours {
+    /// Whether `time` falls in the quiet window, which may wrap past midnight.
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start <= self.end {
+            time >= self.start && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationSettings {
+    pub enabled: bool,
+    pub rules: Vec<NotificationRule>,
+    pub quiet_hours: Option<QuietHours>,
+    /// Minimum gap between two notifications.
+    pub min_interval_minutes: f64,
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            rules: vec![
+                NotificationRule { transition: TransitionKind::PeakStart, enabled: false, lead_minutes: 0.0 },
+                NotificationRule { transition: TransitionKind::PeakEnd, enabled: true, lead_minutes: 5.0 },
+                NotificationRule { transition: TransitionKind::RestStart, enabled: true, lead_minutes: 0.0 },
+                NotificationRule { transition: TransitionKind::CycleStart, enabled: false, lead_minutes: 0.0 },
+            ],
+            quiet_hours: Some(QuietHours {
+                start: NaiveTime::from_hms_opt(22, 0, 0).unwrap(),
+                end: NaiveTime::from_hms_opt(7, 0, 0).unwrap(),
+            }),
+            min_interval_minutes: 10.0,
+        }
+    }
+}
+
+impl NotificationSettings {
+    pub fn validate(&self, cycle_length: f64) -> Result<(), String> {
+        for rule in &self.rules {
+            if rule.lead_minutes < 0.0 || rule.lead_minutes >= cycle_length {
+                return Err(format!(
+                    "Notification lead time must be between 0 and {} minutes, got {}",
+                    cycle_length, rule.lead_minutes
+                ));
+            }
+        }
+        if !(0.0..=180.0).contains(&self.min_interval_minutes) {
+            return Err("Notification interval must be between 0 and 180 minutes".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PhaseNotification {
+    pub transition: TransitionKind,
+    pub title: String,
+    pub body: String,
+}
+
+/// A crossed transition held back by the rate limit.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Deferred {
+    transition: TransitionKind,
+    lead_minutes: f64,
+    crossed_at: DateTime<Utc>,
+}
+
+impl Deferred {
+    /// Message as of `now`, with the lead time shortened by the wait.
+    fn notification(&self, clock: &UltradianClock, now: DateTime<Utc>) -> PhaseNotification {
+        let waited = (now - self.crossed_at).num_milliseconds() as f64 / 60_000.0;
+        let lead = (self.lead_minutes - waited).max(0.0).round();
+        let (title, body) = self.transition.message(lead, clock);
+        PhaseNotification { transition: self.transition, title, body }
+    }
+}
+
+/// Tracks what has been sent so transitions fire once, respecting quiet hours
+/// and the rate limit. Transitions crossed during quiet hours, a snooze or a
+/// long tick gap are dropped; those held back by the rate limit are deferred,
+/// keeping only the most recent, and sent once the interval has passed.
+#[derive(Debug, Default)]
+pub struct NotificationScheduler {
+    last_tick: Option<(DateTime<Utc>, f64)>,
+    last_sent: Option<DateTime<Utc>>,
+    snoozed_until: Option<DateTime<Utc>>,
+    deferred: Option<Deferred>,
+}
+
+impl NotificationScheduler {
+    /// Holds back all notifications until `until`.
+    pub fn snooze(&mut self, until: DateTime<Utc>) {
+        self.snoozed_until = Some(until);
+    }
+
+    pub fn clear_snooze(&mut self) {
+        self.snoozed_until = None;
+    }
+
+    /// End of the current snooze, if one is still running at `now`.
+    pub fn snoozed_until(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.snoozed_until.filter(|until| *until > now)
+    }
+
+    /// Notifications whose trigger point (transition minus lead time) was
+    /// crossed between the previous tick and this one.
+    pub fn due(
+        &mut self,
+        settings: &NotificationSettings,
+        clock: &UltradianClock,
+        reading: &CycleReading,
+        now: DateTime<Tz>,
+    ) -> Vec<PhaseNotification> {
+        // Quiet hours are wall-clock times in the active zone; everything else
+        // is measured between instants.
+        let wall_time = now.time();
+        let now = now.with_timezone(&Utc);
+        let previous = self.last_tick.replace((now, reading.cycle_position));
+        let Some((last_time, last_position)) = previous else {
+            return Vec::new();
+        };
+        let elapsed = (now - last_time).num_milliseconds() as f64 / 60_000.0;
+        let quiet = settings.quiet_hours.as_ref().is_some_and(|quiet| quiet.contains(wall_time));
+        if !settings.enabled
+            || elapsed <= 0.0
+            || elapsed > MAX_TICK_GAP_MINUTES
+            || quiet
+            || self.snoozed_until(now).is_some()
+        {
+            self.deferred = None;
+            return Vec::new();
+        }
+
+        let mut crossed = settings.rules.iter().filter(|rule| {
+            let target = rule.transition.position(clock);
+            let before = countdown(target, last_position, clock.cycle_length);
+            let after = countdown(target, reading.cycle_position, clock.cycle_length);
+            let wrapped = after > before;
+            rule.enabled
+                && if wrapped {
+                    before > rule.lead_minutes || after <= rule.lead_minutes
+                } else {
+                    before > rule.lead_minutes && after <= rule.lead_minutes
+                }
+        });
+        let defer = |rule: &NotificationRule| Deferred {
+            transition: rule.transition,
+            lead_minutes: rule.lead_minutes,
+            crossed_at: now,
+        };
+
+        let rate_limited = self
+            .last_sent
+            .is_some_and(|sent| ((now - sent).num_milliseconds() as f64 / 60_000.0) < settings.min_interval_minutes);
+        if rate_limited {
+            if let Some(rule) = crossed.next_back() {
+                self.deferred = Some(defer(rule));
+            }
+            return Vec::new();
+        }
+
+        // One notification per tick, so the rate limit also covers transitions
+        // that coincide; a newer crossing supersedes a deferred one.
+        let next = match crossed.next() {
+            Some(rule) => {
+                self.deferred = crossed.next_back().map(defer);
+                Some(defer(rule))
+            }
+            None => self.deferred.take(),
+        };
+        let due: Vec<PhaseNotification> = next.map(|next| next.notification(clock, now)).into_iter().collect();
+        if !due.is_empty() {
+            self.last_sent = Some(now);
+        }
+        due
+    }
+}
+
+/// Minutes until the cycle next reaches `target`, in `(0, cycle_length]`.
+fn countdown(target: f64, position: f64, cycle_length: f64) -> f64 {
+    let remaining = (target - position).rem_euclid(cycle_length);
+    if remaining == 0.0 {
+        cycle_length
+    } else {
+        remaining
+    }
+}
+
+pub type NotificationState = Arc<Mutex<NotificationScheduler>>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    fn time(h: u32, m: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(h, m, 0).unwrap()
+    }
+
+    fn rule(transition: TransitionKind, lead_minutes: f64) -> NotificationRule {
+        NotificationRule { transition, enabled: true, lead_minutes }
+    }
+
+    fn settings(rules: Vec<NotificationRule>, min_interval_minutes: f64) -> NotificationSettings {
+        NotificationSettings { rules, min_interval_minutes, ..NotificationSettings::default() }
+    }
+
+    /// Ticks once a minute through cycle positions `from..=to`, with position 0
+    /// at `start`, collecting `(position, title)` of everything sent.
+    fn run(
+        scheduler: &mut NotificationScheduler,
+        settings: &NotificationSettings,
+        start: DateTime<Tz>,
+        from: u32,
+        to: u32,
+    ) -> Vec<(u32, String)> {
+        let clock = UltradianClock::default();
+        let mut sent = Vec::new();
+        for position in from..=to {
+            let now = start + Duration::minutes(position as i64);
+            let reading = clock.reading(position as f64);
+            for notification in scheduler.due(settings, &clock, &reading, now) {
+                sent.push((position, notification.title));
+            }
+        }
+        sent
+    }
+
+    fn morning() -> DateTime<Tz> {
+        Tz::UTC.with_ymd_and_hms(2026, 3, 2, 9, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn quiet_hours_wrap_past_midnight() {
+        let quiet = QuietHours { start: time(22, 0), end: time(7, 0) };
+        assert!(quiet.contains(time(22, 0)));
+        assert!(quiet.contains(time(23, 59)));
+        assert!(quiet.contains(time(0, 0)));
+        assert!(quiet.contains(time(6, 59)));
+        assert!(!quiet.contains(time(7, 0)));
+        assert!(!quiet.contains(time(12, 0)));
+
+        let daytime = QuietHours { start: time(12, 0), end: time(13, 0) };
+        assert!(daytime.contains(time(12, 30)));
+        assert!(!daytime.contains(time(13, 0)));
+        assert!(!daytime.contains(time(23, 0)));
+    }
+
+    #[test]
+    fn first_tick_only_seeds() {
+        let mut scheduler = NotificationScheduler::default();
+        let settings = settings(vec![rule(TransitionKind::RestStart, 0.0)], 0.0);
+        assert!(run(&mut scheduler, &settings, morning(), 65, 65).is_empty());
+    }
+
+    #[test]
+    fn lead_time_fires_before_transition() {
+        let mut scheduler = NotificationScheduler::default();
+        let settings = settings(vec![rule(TransitionKind::PeakEnd, 5.0)], 0.0);
+        let sent = run(&mut scheduler, &settings, morning(), 50, 62);
+        assert_eq!(sent, [(55, "Peak ending in 5 min".to_string())]);
+    }
+
+    #[test]
+    fn cycle_start_fires_across_the_wrap() {
+        let mut scheduler = NotificationScheduler::default();
+        let settings = settings(vec![rule(TransitionKind::CycleStart, 0.0)], 0.0);
+        let clock = UltradianClock::default();
+        let now = morning();
+        assert!(scheduler.due(&settings, &clock, &clock.reading(89.5), now).is_empty());
+        let sent = scheduler.due(&settings, &clock, &clock.reading(90.5), now + Duration::minutes(1));
+        assert_eq!(sent[0].title, "New cycle started");
+    }
+
+    #[test]
+    fn quiet_hours_drop_transitions() {
+        let mut scheduler = NotificationScheduler::default();
+        let settings = settings(vec![rule(TransitionKind::RestStart, 0.0)], 0.0);
+        let night = Tz::UTC.with_ymd_and_hms(2026, 3, 2, 23, 0, 0).unwrap();
+        assert!(run(&mut scheduler, &settings, night, 60, 90).is_empty());
+    }
+
+    #[test]
+    fn rate_limited_transition_is_deferred() {
+        let mut scheduler = NotificationScheduler::default();
+        let settings = settings(vec![rule(TransitionKind::PeakEnd, 5.0), rule(TransitionKind::RestStart, 0.0)], 15.0);
+        let sent = run(&mut scheduler, &settings, morning(), 50, 75);
+        assert_eq!(
+            sent,
+            [(55, "Peak ending in 5 min".to_string()), (70, "Rest phase started".to_string())]
+        );
+    }
+
+    #[test]
+    fn deferred_lead_time_counts_down() {
+        let mut scheduler = NotificationScheduler::default();
+        let rules = vec![rule(TransitionKind::RestStart, 0.0), rule(TransitionKind::CycleStart, 10.0)];
+        let settings = settings(rules, 20.0);
+        let sent = run(&mut scheduler, &settings, morning(), 60, 89);
+        assert_eq!(
+            sent,
+            [(65, "Rest phase started".to_string()), (85, "New cycle in 5 min".to_string())]
+        );
+    }
+
+    #[test]
+    fn coinciding_transitions_are_sent_in_turn() {
+        let mut scheduler = NotificationScheduler::default();
+        let settings = settings(vec![rule(TransitionKind::PeakEnd, 0.0), rule(TransitionKind::RestStart, 5.0)], 3.0);
+        let sent = run(&mut scheduler, &settings, morning(), 55, 65);
+        assert_eq!(sent, [(60, "Peak ended".to_string()), (63, "Rest phase in 2 min".to_string())]);
+    }
+
+    #[test]
+    fn snooze_drops_deferred_transition() {
+        let mut scheduler = NotificationScheduler::default();
+        let settings = settings(vec![rule(TransitionKind::PeakEnd, 5.0), rule(TransitionKind::RestStart, 0.0)], 15.0);
+        assert_eq!(run(&mut scheduler, &settings, morning(), 50, 66).len(), 1);
+        scheduler.snooze((morning() + Duration::minutes(68)).with_timezone(&Utc));
+        assert!(run(&mut scheduler, &settings, morning(), 67, 80).is_empty());
+    }
+}
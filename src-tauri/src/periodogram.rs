@@ -0,0 +1,305 @@
+// Lomb-Scargle periodogram for irregularly sampled series, searched within
+// period bands so users can calibrate their cycle length from their own data.
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+use crate::timeseries::TimeSeries;
+
+/// Frequencies evaluated per independent frequency.
+const DEFAULT_OVERSAMPLING: f64 = 5.0;
+const DEFAULT_SIGNIFICANCE_LEVEL: f64 = 0.01;
+const DEFAULT_MAX_PEAKS: usize = 5;
+/// Upper bound on the grid size of a single band.
+const MAX_FREQUENCIES_PER_BAND: usize = 20_000;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PeriodBand {
+    pub name: String,
+    pub min_period_minutes: f64,
+    pub max_period_minutes: f64,
+}
+
+impl PeriodBand {
+    fn new(name: &str, min_period_minutes: f64, max_period_minutes: f64) -> Self {
+        Self { name: name.to_string(), min_period_minutes, max_period_minutes }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PeriodogramConfig {
+    pub bands: Vec<PeriodBand>,
+    pub oversampling: f64,
+    /// Peaks with a false-alarm probability below this are significant.
+    pub significance_level: f64,
+    /// Peaks reported per band, strongest first.
+    pub max_peaks: usize,
+}
+
+impl Default for PeriodogramConfig {
+    fn default() -> Self {
+        Self {
+            bands: vec![
+                PeriodBand::new("ultradian", 60.0, 150.0),
+                PeriodBand::new("circadian", 20.0 * 60.0, 28.0 * 60.0),
+            ],
+            oversampling: DEFAULT_OVERSAMPLING,
+            significance_level: DEFAULT_SIGNIFICANCE_LEVEL,
+            max_peaks: DEFAULT_MAX_PEAKS,
+        }
+    }
+}
+
+impl PeriodogramConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        for band in &self.bands {
+            if !(band.min_period_minutes > 0.0 && band.min_period_minutes < band.max_period_minutes) {
+                return Err(format!("Period band '{}' must satisfy 0 < min < max", band.name));
+            }
+        }
+        if self.oversampling < 1.0 {
+            return Err("Oversampling must be at least 1".to_string());
+        }
+        if !(self.significance_level > 0.0 && self.significance_level < 1.0) {
+            return Err("Significance level must be between 0 and 1".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpectralPeak {
+    pub period_minutes: f64,
+    /// Power normalised by the series variance.
+    pub power: f64,
+    /// Probability that noise alone produces a peak this high somewhere in the band.
+    pub false_alarm_probability: f64,
+    pub significant: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BandSpectrum {
+    pub band: PeriodBand,
+    /// Evaluated periods, shortest first, with the power at each.
+    pub periods_minutes: Vec<f64>,
+    pub power: Vec<f64>,
+    pub peaks: Vec<SpectralPeak>,
+    /// Independent frequencies in the band, used for the false-alarm probability.
+    pub independent_frequencies: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Periodogram {
+    pub bands: Vec<BandSpectrum>,
+    pub samples: usize,
+    pub span_minutes: f64,
+}
+
+/// Normalised Lomb-Scargle power at angular frequency `omega` (rad/min) for
+/// mean-removed values `y` at times `t` (minutes). Any time unit works if
+/// `omega` matches; a `variance` of 1 gives the unnormalised power.
+pub fn power_at(t: &[f64], y: &[f64], variance: f64, omega: f64) -> f64 {
+    let (mut yc, mut ys, mut cc, mut ss, mut cs) = (0.0, 0.0, 0.0, 0.0, 0.0);
+    for (&time, &value) in t.iter().zip(y) {
+        let (sin, cos) = (omega * time).sin_cos();
+        yc += value * cos;
+        ys += value * sin;
+        cc += cos * cos;
+        ss += sin * sin;
+        cs += sin * cos;
+    }
+    // Rotate by the time offset tau that makes the sine and cosine terms orthogonal
+    let two_omega_tau = (2.0 * cs).atan2(cc - ss);
+    let (s, c) = (two_omega_tau / 2.0).sin_cos();
+    let yc_tau = c * yc + s * ys;
+    let ys_tau = c * ys - s * yc;
+    let cc_tau = c * c * cc + 2.0 * c * s * cs + s * s * ss;
+    let ss_tau = s * s * cc - 2.0 * c * s * cs + c * c * ss;
+
+    let mut power = 0.0;
+    if cc_tau > 0.0 {
+        power += yc_tau * yc_tau / cc_tau;
+    }
+    if ss_tau > 0.0 {
+        power += ys_tau * ys_tau / ss_tau;
+    }
+    power / (2.0 * variance)
+}
+
+fn band_spectrum(t: &[f64], y: &[f64], variance: f64, span: f64, band: &PeriodBand, config: &PeriodogramConfig) -> BandSpectrum {
+    // Periods longer than half the recording cannot be resolved
+    let max_period = band.max_period_minutes.min(span / 2.0);
+    let mut spectrum = BandSpectrum {
+        band: band.clone(),
+        periods_minutes: Vec::new(),
+        power: Vec::new(),
+        peaks: Vec::new(),
+        independent_frequencies: 0.0,
+    };
+    if max_period <= band.min_period_minutes {
+        return spectrum;
+    }
+
+    let (f_min, f_max) = (1.0 / max_period, 1.0 / band.min_period_minutes);
+    let independent = ((f_max - f_min) * span).max(1.0);
+    let count = ((independent * config.oversampling).ceil() as usize).clamp(2, MAX_FREQUENCIES_PER_BAND);
+    let step = (f_max - f_min) / (count - 1) as f64;
+
+    // Highest frequency first so periods come out in increasing order
+    for k in 0..count {
+        let frequency = f_max - step * k as f64;
+        spectrum.periods_minutes.push(1.0 / frequency);
+        spectrum.power.push(power_at(t, y, variance, 2.0 * PI * frequency));
+    }
+
+    let power = &spectrum.power;
+    let mut peaks: Vec<usize> = (0..power.len())
+        .filter(|&i| (i == 0 || power[i] > power[i - 1]) && (i + 1 == power.len() || power[i] >= power[i + 1]))
+        .collect();
+    peaks.sort_by(|&a, &b| power[b].total_cmp(&power[a]));
+    spectrum.peaks = peaks
+        .into_iter()
+        .take(config.max_peaks)
+        .map(|i| {
+            // Horne & Baliunas (1986): FAP = 1 - (1 - e^-z)^M
+            let false_alarm_probability = -(independent * (-(-power[i]).exp()).ln_1p()).exp_m1();
+            SpectralPeak {
+                period_minutes: spectrum.periods_minutes[i],
+                power: power[i],
+                false_alarm_probability,
+                significant: false_alarm_probability < config.significance_level,
+            }
+        })
+        .collect();
+    spectrum.independent_frequencies = independent;
+    spectrum
+}
+
+pub fn lomb_scargle(series: &TimeSeries, config: &PeriodogramConfig) -> Result<Periodogram, String> {
+    if series.len() < 3 {
+        return Err("A periodogram needs at least 3 samples".to_string());
+    }
+    let start = series.timestamps[0];
+    let t: Vec<f64> = series.timestamps.iter().map(|ts| (ts - start) as f64 / 60_000.0).collect();
+    let mean = series.values.iter().sum::<f64>() / series.len() as f64;
+    let y: Vec<f64> = series.values.iter().map(|value| value - mean).collect();
+    let variance = y.iter().map(|value| value * value).sum::<f64>() / (y.len() - 1) as f64;
+    if variance == 0.0 {
+        return Err("Series is constant, so it has no periodic component".to_string());
+    }
+    let span = series.span_minutes();
+
+    Ok(Periodogram {
+        bands: config.bands.iter().map(|band| band_spectrum(&t, &y, variance, span, band, config)).collect(),
+        samples: series.len(),
+        span_minutes: span,
+    })
+}
+
+#[tauri::command]
+pub async fn calculate_periodogram(series: TimeSeries, config: Option<PeriodogramConfig>) -> Result<Periodogram, String> {
+    let config = config.unwrap_or_default();
+    config.validate()?;
+    series.validate()?;
+
+    tauri::async_runtime::spawn_blocking(move || lomb_scargle(&series, &config))
+        .await
+        .map_err(|e| format!("Failed to calculate periodogram: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::TAU;
+
+    const START: i64 = 1_772_409_600_000; // 2026-03-02 00:00 UTC
+
+    struct Lcg(u64);
+
+    impl Lcg {
+        /// Uniform in [0, 1).
+        fn next(&mut self) -> f64 {
+            self.0 = self.0.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1_442_695_040_888_963_407);
+            (self.0 >> 11) as f64 / (1u64 << 53) as f64
+        }
+
+        /// Roughly standard normal, from the sum of twelve uniforms.
+        fn normal(&mut self) -> f64 {
+            (0..12).map(|_| self.next()).sum::<f64>() - 6.0
+        }
+    }
+
+    /// Three days sampled at irregular 2-8 minute gaps.
+    fn irregular(signal: impl Fn(f64) -> f64, seed: u64) -> TimeSeries {
+        let mut rng = Lcg(seed);
+        let mut minutes = 0.0;
+        let (mut timestamps, mut values) = (Vec::new(), Vec::new());
+        while minutes < 3.0 * 24.0 * 60.0 {
+            timestamps.push(START + (minutes * 60_000.0) as i64);
+            values.push(signal(minutes) + rng.normal());
+            minutes += 2.0 + 6.0 * rng.next();
+        }
+        TimeSeries { timestamps, values }
+    }
+
+    fn strongest(spectrum: &BandSpectrum) -> &SpectralPeak {
+        spectrum.peaks.iter().max_by(|a, b| a.power.total_cmp(&b.power)).unwrap()
+    }
+
+    #[test]
+    fn recovers_period_of_irregularly_sampled_sinusoid() {
+        let series = irregular(|minutes| 1.5 * (TAU * minutes / 95.0).sin(), 3);
+        let result = lomb_scargle(&series, &PeriodogramConfig::default()).unwrap();
+        let ultradian = &result.bands[0];
+        let peak = strongest(ultradian);
+        assert!((peak.period_minutes - 95.0).abs() < 1.0, "{}", peak.period_minutes);
+        assert!(peak.false_alarm_probability < 0.01);
+        assert!(peak.significant);
+        assert_eq!(result.samples, series.len());
+    }
+
+    #[test]
+    fn recovers_circadian_period() {
+        let series = irregular(|minutes| 3.0 * (TAU * minutes / (24.0 * 60.0)).cos(), 5);
+        let result = lomb_scargle(&series, &PeriodogramConfig::default()).unwrap();
+        let peak = strongest(&result.bands[1]);
+        assert!((peak.period_minutes / 60.0 - 24.0).abs() < 1.0, "{}", peak.period_minutes);
+        assert!(peak.significant);
+    }
+
+    #[test]
+    fn white_noise_has_no_significant_peak() {
+        for seed in [11, 12, 13] {
+            let result = lomb_scargle(&irregular(|_| 0.0, seed), &PeriodogramConfig::default()).unwrap();
+            for band in &result.bands {
+                assert!(!band.peaks.is_empty());
+                assert!(band.peaks.iter().all(|peak| !peak.significant), "seed {} band {}", seed, band.band.name);
+            }
+        }
+    }
+
+    #[test]
+    fn spectrum_stays_inside_band() {
+        let result = lomb_scargle(&irregular(|_| 0.0, 1), &PeriodogramConfig::default()).unwrap();
+        let band = &result.bands[0];
+        assert_eq!(band.periods_minutes.len(), band.power.len());
+        assert!(band.periods_minutes.iter().all(|&p| (60.0..=150.0).contains(&p)));
+        assert!(band.peaks.len() <= DEFAULT_MAX_PEAKS);
+    }
+
+    #[test]
+    fn rejects_constant_and_tiny_series() {
+        let constant = TimeSeries { timestamps: vec![START, START + 60_000, START + 120_000], values: vec![1.0; 3] };
+        assert!(lomb_scargle(&constant, &PeriodogramConfig::default()).is_err());
+        let tiny = TimeSeries { timestamps: vec![START, START + 60_000], values: vec![1.0, 2.0] };
+        assert!(lomb_scargle(&tiny, &PeriodogramConfig::default()).is_err());
+    }
+
+    #[test]
+    fn validate_rejects_bad_bands() {
+        let config = PeriodogramConfig { bands: vec![PeriodBand::new("bad", 100.0, 50.0)], ..Default::default() };
+        assert!(config.validate().is_err());
+        assert!(PeriodogramConfig { oversampling: 0.5, ..Default::default() }.validate().is_err());
+        assert!(PeriodogramConfig::default().validate().is_ok());
+    }
+}
@@ -0,0 +1,391 @@
+// Live ultradian phase/period estimate from the HealthKit heart-rate stream.
+//
+// Heart rate is modelled as `baseline + amplitude * cos(phase - peak_phase)`
+// with the phase advancing at an angular frequency close to the configured
+// cycle length. An extended Kalman filter tracks phase and frequency (a
+// phase-locked loop with proper uncertainty); baseline and amplitude follow
+// slow exponential averages.
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::f64::consts::TAU;
+use std::sync::{Arc, Mutex};
+
+use crate::healthkit_ffi::HeartRateData;
+use crate::ultradian::{CycleReading, UltradianClock};
+
+/// Phase diffusion, rad² per minute.
+const PHASE_NOISE: f64 = 5e-3;
+/// Frequency drift, (rad/min)² per minute.
+const FREQUENCY_NOISE: f64 = 5e-8;
+/// Heart-rate measurement noise variance, bpm².
+const MEASUREMENT_NOISE: f64 = 36.0;
+/// Initial phase uncertainty when seeding from the schedule, in cycle fractions.
+const INITIAL_PHASE_SD: f64 = 1.0 / 6.0;
+/// Time constant of the baseline and amplitude averages, minutes.
+const LEVEL_TIME_CONSTANT: f64 = 120.0;
+/// Amplitude bounds, bpm, so a flat or noisy signal cannot collapse or blow up the gain.
+const MIN_AMPLITUDE: f64 = 2.0;
+const MAX_AMPLITUDE: f64 = 15.0;
+/// Initial period uncertainty around the configured cycle length, minutes.
+const INITIAL_PERIOD_SD: f64 = 10.0;
+/// Plausible period range as multiples of the configured cycle length
+/// (60–150 minutes for the default 90).
+const MIN_PERIOD_RATIO: f64 = 2.0 / 3.0;
+const MAX_PERIOD_RATIO: f64 = 5.0 / 3.0;
+/// Gaps longer than this reseed the filter from the schedule.
+const MAX_GAP_MINUTES: f64 = 360.0;
+/// Conditions for the estimate to override the schedule.
+const MIN_LOCK_SAMPLES: usize = 20;
+const MAX_LOCK_PHASE_SD_MINUTES: f64 = 10.0;
+const MAX_LOCK_AGE_MINUTES: f64 = 30.0;
+/// Samples averaged into the fit statistic.
+const FIT_WINDOW_SAMPLES: f64 = 30.0;
+/// Days of data remembered for the history count.
+const MAX_TRACKED_DAYS: usize = 60;
+/// Oldest sample still shown as the current heart rate, minutes.
+const MAX_HEART_RATE_AGE_MINUTES: f64 = 10.0;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PhaseEstimate {
+    /// Estimated position in the cycle, 0..1.
+    pub cycle_fraction: f64,
+    pub period_minutes: f64,
+    pub phase_sd_minutes: f64,
+    pub period_sd_minutes: f64,
+    pub samples: usize,
+    pub last_sample: Option<DateTime<Utc>>,
+    pub heart_rate: Option<f64>,
+    /// Recent mean of squared innovations over their predicted variance. Close
+    /// to 1 when heart rate behaves as the predicted phase expects; larger
+    /// values mean the data fits poorly.
+    pub normalized_innovation: f64,
+    /// Distinct days with heart rate samples.
+    pub days_observed: usize,
+    /// Whether the estimate is recent and tight enough to replace the schedule.
+    pub locked: bool,
+}
+
+impl PhaseEstimate {
+    /// Latest heart rate, if it is recent enough to display as current.
+    pub fn current_heart_rate(&self, now: DateTime<Utc>) -> Option<f64> {
+        let last = self.last_sample?;
+        if minutes_between(last, now) > MAX_HEART_RATE_AGE_MINUTES {
+            return None;
+        }
+        self.heart_rate
+    }
+
+    /// Minutes the estimate runs ahead of `scheduled`, within half a cycle
+    /// either way; 0 unless locked.
+    pub fn offset_minutes(&self, clock: &UltradianClock, scheduled: &CycleReading) -> f64 {
+        if !self.locked {
+            return 0.0;
+        }
+        let ahead = self.cycle_fraction * clock.cycle_length - scheduled.cycle_position;
+        ahead - clock.cycle_length * (ahead / clock.cycle_length).round()
+    }
+
+    /// The schedule reading moved to the estimated position when locked. The
+    /// cycle number follows the move, so near a boundary the reading can fall
+    /// in the previous or next cycle.
+    pub fn apply(&self, clock: &UltradianClock, scheduled: &CycleReading) -> CycleReading {
+        if !self.locked {
+            return scheduled.clone();
+        }
+        let elapsed = f64::from(scheduled.cycle_number - 1) * clock.cycle_length + scheduled.cycle_position;
+        clock.shifted_reading(elapsed, self.offset_minutes(clock, scheduled))
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct PhaseEstimator {
+    /// Phase in radians, 0 at the start of a cycle.
+    phase: f64,
+    /// Angular frequency, rad/min.
+    omega: f64,
+    cov: [[f64; 2]; 2],
+    baseline: Option<f64>,
+    variance: f64,
+    last_time: Option<DateTime<Utc>>,
+    last_heart_rate: Option<f64>,
+    samples: usize,
+    normalized_innovation: f64,
+    days: BTreeSet<NaiveDate>,
+}
+
+impl PhaseEstimator {
+    /// Folds in one heart-rate sample. `scheduled_fraction` is where the
+    /// schedule puts the sample in its cycle; it seeds the filter on the first
+    /// sample or after a long gap.
+    pub fn observe(&mut self, sample: &HeartRateData, clock: &UltradianClock, scheduled_fraction: f64) {
+        let Some(time) = Utc.timestamp_millis_opt(sample.timestamp as i64).single() else {
+            return;
+        };
+        if !sample.rate.is_finite() || sample.rate <= 0.0 {
+            return;
+        }
+
+        match self.last_time {
+            Some(last) if time <= last => return,
+            Some(last) if minutes_between(last, time) <= MAX_GAP_MINUTES => {
+                self.predict(minutes_between(last, time));
+            }
+            _ => self.seed(clock, scheduled_fraction),
+        }
+        self.last_time = Some(time);
+        self.last_heart_rate = Some(sample.rate);
+        self.samples += 1;
+        self.days.insert(time.date_naive());
+        if self.days.len() > MAX_TRACKED_DAYS {
+            self.days.pop_first();
+        }
+
+        let baseline = *self.baseline.get_or_insert(sample.rate);
+        let amplitude = (2.0 * (self.variance - MEASUREMENT_NOISE).max(0.0))
+            .sqrt()
+            .clamp(MIN_AMPLITUDE, MAX_AMPLITUDE);
+        let peak = peak_phase(clock);
+
+        // Measurement update on phase only; frequency follows through the covariance.
+        let predicted = baseline + amplitude * (self.phase - peak).cos();
+        let h = -amplitude * (self.phase - peak).sin();
+        let s = h * h * self.cov[0][0] + MEASUREMENT_NOISE;
+        let k0 = self.cov[0][0] * h / s;
+        let k1 = self.cov[1][0] * h / s;
+        let innovation = sample.rate - predicted;
+        let weight = 1.0 / (self.samples as f64).min(FIT_WINDOW_SAMPLES);
+        self.normalized_innovation += weight * (innovation * innovation / s - self.normalized_innovation);
+        self.phase = (self.phase + k0 * innovation).rem_euclid(TAU);
+        self.omega = (self.omega + k1 * innovation).clamp(
+            TAU / (clock.cycle_length * MAX_PERIOD_RATIO),
+            TAU / (clock.cycle_length * MIN_PERIOD_RATIO),
+        );
+        let [[p00, p01], [p10, p11]] = self.cov;
+        self.cov = [
+            [p00 - k0 * h * p00, p01 - k0 * h * p01],
+            [p10 - k1 * h * p00, p11 - k1 * h * p01],
+        ];
+
+        // Slow level tracking so the oscillation itself is not absorbed.
+        let alpha = 1.0 / LEVEL_TIME_CONSTANT;
+        let deviation = sample.rate - baseline;
+        self.baseline = Some(baseline + alpha * deviation);
+        self.variance += alpha * (deviation * deviation - self.variance);
+    }
+
+    /// Estimate projected forward to `now`.
+    pub fn estimate(&self, now: DateTime<Utc>) -> Option<PhaseEstimate> {
+        let last = self.last_time?;
+        let dt = minutes_between(last, now).max(0.0);
+        let phase = (self.phase + self.omega * dt).rem_euclid(TAU);
+        let [[p00, p01], [_, p11]] = self.cov;
+        let phase_var = p00 + 2.0 * p01 * dt + p11 * dt * dt + PHASE_NOISE * dt;
+        let phase_sd_minutes = phase_var.max(0.0).sqrt() / self.omega;
+        let period_sd_minutes = TAU / (self.omega * self.omega) * p11.max(0.0).sqrt();
+
+        Some(PhaseEstimate {
+            cycle_fraction: phase / TAU,
+            period_minutes: TAU / self.omega,
+            phase_sd_minutes,
+            period_sd_minutes,
+            samples: self.samples,
+            last_sample: Some(last),
+            heart_rate: self.last_heart_rate,
+            normalized_innovation: self.normalized_innovation,
+            days_observed: self.days.len(),
+            locked: self.samples >= MIN_LOCK_SAMPLES
+                && phase_sd_minutes <= MAX_LOCK_PHASE_SD_MINUTES
+                && dt <= MAX_LOCK_AGE_MINUTES,
+        })
+    }
+
+    fn seed(&mut self, clock: &UltradianClock, scheduled_fraction: f64) {
+        let omega = TAU / clock.cycle_length;
+        let frequency_sd = TAU * INITIAL_PERIOD_SD / (clock.cycle_length * clock.cycle_length);
+        self.phase = (scheduled_fraction * TAU).rem_euclid(TAU);
+        self.omega = omega;
+        self.cov = [
+            [(INITIAL_PHASE_SD * TAU).powi(2), 0.0],
+            [0.0, frequency_sd.powi(2)],
+        ];
+        self.baseline = None;
+        self.variance = 0.0;
+        self.samples = 0;
+    }
+
+    fn predict(&mut self, dt: f64) {
+        self.phase = (self.phase + self.omega * dt).rem_euclid(TAU);
+        let [[p00, p01], [p10, p11]] = self.cov;
+        self.cov = [
+            [p00 + dt * (p10 + p01) + dt * dt * p11 + PHASE_NOISE * dt, p01 + dt * p11],
+            [p10 + dt * p11, p11 + FREQUENCY_NOISE * dt],
+        ];
+    }
+}
+
+/// Heart rate is expected to peak in the middle of the high-energy window.
+fn peak_phase(clock: &UltradianClock) -> f64 {
+    TAU * (clock.rise_end + clock.high_end) / 2.0 / clock.cycle_length
+}
+
+fn minutes_between(from: DateTime<Utc>, to: DateTime<Utc>) -> f64 {
+    (to - from).num_milliseconds() as f64 / 60_000.0
+}
+
+pub type EstimatorState = Arc<Mutex<PhaseEstimator>>;
+
+#[tauri::command]
+pub fn get_phase_estimate(state: tauri::State<'_, EstimatorState>) -> Result<Option<PhaseEstimate>, String> {
+    let estimator = state.lock().map_err(|e| format!("Failed to lock estimator: {}", e))?;
+    Ok(estimator.estimate(Utc::now()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    const START_MS: u64 = 1_772_409_600_000; // 2026-03-02 00:00 UTC
+
+    /// Heart rate following the model, with the true rhythm `lead` minutes
+    /// ahead of a schedule that starts at `START_MS`.
+    fn sample(clock: &UltradianClock, minute: u64, lead: f64) -> HeartRateData {
+        let phase = TAU * (minute as f64 + lead) / clock.cycle_length;
+        HeartRateData {
+            rate: 70.0 + 10.0 * (phase - peak_phase(clock)).cos(),
+            timestamp: START_MS + minute * 60_000,
+        }
+    }
+
+    fn scheduled_fraction(clock: &UltradianClock, minute: u64) -> f64 {
+        (minute as f64 / clock.cycle_length).fract()
+    }
+
+    fn at(minute: u64) -> DateTime<Utc> {
+        Utc.timestamp_millis_opt((START_MS + minute * 60_000) as i64).unwrap()
+    }
+
+    fn locked_estimate(cycle_fraction: f64) -> PhaseEstimate {
+        PhaseEstimate {
+            cycle_fraction,
+            period_minutes: 90.0,
+            phase_sd_minutes: 2.0,
+            period_sd_minutes: 1.0,
+            samples: 100,
+            last_sample: None,
+            heart_rate: None,
+            normalized_innovation: 1.0,
+            days_observed: 1,
+            locked: true,
+        }
+    }
+
+    /// Signed difference between two cycle fractions, in minutes.
+    fn fraction_error(clock: &UltradianClock, a: f64, b: f64) -> f64 {
+        let diff = (a - b) * clock.cycle_length;
+        diff - clock.cycle_length * (diff / clock.cycle_length).round()
+    }
+
+    #[test]
+    fn no_estimate_before_first_sample() {
+        assert!(PhaseEstimator::default().estimate(Utc::now()).is_none());
+    }
+
+    #[test]
+    fn tracks_a_rhythm_ahead_of_the_schedule() {
+        let clock = UltradianClock::default();
+        let mut estimator = PhaseEstimator::default();
+        for minute in 0..360 {
+            estimator.observe(&sample(&clock, minute, 20.0), &clock, scheduled_fraction(&clock, minute));
+        }
+        let estimate = estimator.estimate(at(359)).unwrap();
+        let truth = ((359.0 + 20.0) / clock.cycle_length).fract();
+        assert!(fraction_error(&clock, estimate.cycle_fraction, truth).abs() < 3.0, "{:?}", estimate);
+        assert!((estimate.period_minutes - 90.0).abs() < 5.0);
+        assert!(estimate.locked);
+        assert_eq!(estimate.days_observed, 1);
+    }
+
+    #[test]
+    fn first_samples_do_not_lock() {
+        let clock = UltradianClock::default();
+        let mut estimator = PhaseEstimator::default();
+        for minute in 0..(MIN_LOCK_SAMPLES as u64 - 1) {
+            estimator.observe(&sample(&clock, minute, 0.0), &clock, scheduled_fraction(&clock, minute));
+        }
+        assert!(!estimator.estimate(at(MIN_LOCK_SAMPLES as u64)).unwrap().locked);
+    }
+
+    #[test]
+    fn unlocks_when_data_goes_stale() {
+        let clock = UltradianClock::default();
+        let mut estimator = PhaseEstimator::default();
+        for minute in 0..360 {
+            estimator.observe(&sample(&clock, minute, 0.0), &clock, scheduled_fraction(&clock, minute));
+        }
+        assert!(estimator.estimate(at(360)).unwrap().locked);
+        let stale = at(359) + Duration::minutes(MAX_LOCK_AGE_MINUTES as i64 + 1);
+        assert!(!estimator.estimate(stale).unwrap().locked);
+    }
+
+    #[test]
+    fn ignores_invalid_and_out_of_order_samples() {
+        let clock = UltradianClock::default();
+        let mut estimator = PhaseEstimator::default();
+        estimator.observe(&sample(&clock, 10, 0.0), &clock, 0.0);
+        estimator.observe(&sample(&clock, 5, 0.0), &clock, 0.0);
+        estimator.observe(&HeartRateData { rate: f64::NAN, timestamp: START_MS + 660_000 }, &clock, 0.0);
+        assert_eq!(estimator.estimate(at(11)).unwrap().samples, 1);
+    }
+
+    #[test]
+    fn long_gap_reseeds_from_schedule() {
+        let clock = UltradianClock::default();
+        let mut estimator = PhaseEstimator::default();
+        for minute in 0..120 {
+            estimator.observe(&sample(&clock, minute, 30.0), &clock, scheduled_fraction(&clock, minute));
+        }
+        let resumed = 120 + MAX_GAP_MINUTES as u64 + 60;
+        estimator.observe(&sample(&clock, resumed, 0.0), &clock, 0.25);
+        let estimate = estimator.estimate(at(resumed)).unwrap();
+        assert_eq!(estimate.samples, 1);
+        assert!(fraction_error(&clock, estimate.cycle_fraction, 0.25).abs() < 10.0);
+        assert!(!estimate.locked);
+    }
+
+    #[test]
+    fn apply_leaves_schedule_when_unlocked() {
+        let clock = UltradianClock::default();
+        let scheduled = clock.reading(100.0);
+        let estimate = PhaseEstimate { locked: false, ..locked_estimate(0.5) };
+        assert_eq!(estimate.apply(&clock, &scheduled), scheduled);
+        assert_eq!(estimate.offset_minutes(&clock, &scheduled), 0.0);
+    }
+
+    #[test]
+    fn apply_moves_into_next_cycle() {
+        let clock = UltradianClock::default();
+        // Scheduled late in cycle 3, estimated just after the start of a cycle.
+        let scheduled = clock.reading(2.0 * 90.0 + 88.0);
+        let reading = locked_estimate(0.02).apply(&clock, &scheduled);
+        assert_eq!(reading.cycle_number, 4);
+        assert!((reading.cycle_position - 1.8).abs() < 1e-9);
+        assert_eq!(reading.phase_label, "Rising Energy");
+    }
+
+    #[test]
+    fn apply_moves_into_previous_cycle() {
+        let clock = UltradianClock::default();
+        let scheduled = clock.reading(90.0 + 1.0);
+        let reading = locked_estimate(0.98).apply(&clock, &scheduled);
+        assert_eq!(reading.cycle_number, 1);
+        assert!((reading.cycle_position - 88.2).abs() < 1e-9);
+        assert_eq!(reading.phase_label, "Rest Phase");
+
+        // Before the first cycle there is no earlier one to fall back to.
+        let reading = locked_estimate(0.98).apply(&clock, &clock.reading(1.0));
+        assert_eq!(reading.cycle_number, 1);
+        assert!((reading.cycle_position - 88.2).abs() < 1e-9);
+    }
+}
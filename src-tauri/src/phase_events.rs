@@ -0,0 +1,114 @@
+// Detects phase boundary crossings between successive cycle readings.
+use chrono::{DateTime, FixedOffset};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+use crate::ultradian::{CycleReading, EnergyPhase};
+
+pub const PHASE_CHANGED_EVENT: &str = "phase-changed";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PhaseChangedEvent {
+    pub previous_energy_phase: EnergyPhase,
+    pub previous_label: String,
+    pub energy_phase: EnergyPhase,
+    pub phase_label: String,
+    pub phase_icon: String,
+    pub cycle_number: i32,
+    /// Minutes until the following transition.
+    pub time_remaining: f64,
+    pub timestamp: DateTime<FixedOffset>,
+}
+
+/// Remembers the last reading so the tray loop can tell when the energy phase,
+/// display band or cycle changes.
+#[derive(Debug, Default)]
+pub struct PhaseTracker {
+    last: Option<CycleReading>,
+}
+
+impl PhaseTracker {
+    /// Returns an event when `reading` is in a different phase than the previous
+    /// one. The first reading only seeds the tracker.
+    pub fn observe(&mut self, reading: &CycleReading, now: DateTime<Tz>) -> Option<PhaseChangedEvent> {
+        let previous = self.last.replace(reading.clone())?;
+        let changed = previous.energy_phase != reading.energy_phase
+            || previous.phase_label != reading.phase_label
+            || previous.cycle_number != reading.cycle_number;
+        if !changed {
+            return None;
+        }
+
+        Some(PhaseChangedEvent {
+            previous_energy_phase: previous.energy_phase,
+            previous_label: previous.phase_label,
+            energy_phase: reading.energy_phase,
+            phase_label: reading.phase_label.clone(),
+            phase_icon: reading.phase_icon.clone(),
+            cycle_number: reading.cycle_number,
+            time_remaining: reading.time_remaining,
+            timestamp: now.fixed_offset(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ultradian::UltradianClock;
+    use chrono::TimeZone;
+
+    fn now() -> DateTime<Tz> {
+        Tz::UTC.with_ymd_and_hms(2026, 3, 2, 9, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn first_reading_only_seeds() {
+        let clock = UltradianClock::default();
+        let mut tracker = PhaseTracker::default();
+        assert!(tracker.observe(&clock.reading(10.0), now()).is_none());
+    }
+
+    #[test]
+    fn same_phase_is_not_reported_again() {
+        let clock = UltradianClock::default();
+        let mut tracker = PhaseTracker::default();
+        tracker.observe(&clock.reading(16.0), now());
+        assert!(tracker.observe(&clock.reading(17.0), now()).is_none());
+        assert!(tracker.observe(&clock.reading(29.0), now()).is_none());
+    }
+
+    #[test]
+    fn band_change_emits_event() {
+        let clock = UltradianClock::default();
+        let mut tracker = PhaseTracker::default();
+        tracker.observe(&clock.reading(29.0), now());
+        let event = tracker.observe(&clock.reading(31.0), now()).unwrap();
+        assert_eq!(event.previous_label, "Building Energy");
+        assert_eq!(event.phase_label, "Peak Energy");
+        assert_eq!(event.energy_phase, EnergyPhase::High);
+        assert_eq!(event.timestamp, now().fixed_offset());
+        assert!(tracker.observe(&clock.reading(32.0), now()).is_none());
+    }
+
+    #[test]
+    fn energy_phase_change_within_band_emits_event() {
+        let clock = UltradianClock::default();
+        let mut tracker = PhaseTracker::default();
+        tracker.observe(&clock.reading(4.0), now());
+        let event = tracker.observe(&clock.reading(6.0), now()).unwrap();
+        assert_eq!(event.previous_label, event.phase_label);
+        assert_eq!(event.previous_energy_phase, EnergyPhase::Transition);
+        assert_eq!(event.energy_phase, EnergyPhase::High);
+    }
+
+    #[test]
+    fn new_cycle_emits_event() {
+        let clock = UltradianClock::default();
+        let mut tracker = PhaseTracker::default();
+        tracker.observe(&clock.reading(89.0), now());
+        let event = tracker.observe(&clock.reading(91.0), now()).unwrap();
+        assert_eq!(event.cycle_number, 2);
+        assert_eq!(event.phase_label, "Rising Energy");
+    }
+}
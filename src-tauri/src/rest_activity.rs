@@ -0,0 +1,221 @@
+// Nonparametric rest-activity metrics (van Someren et al.): interdaily
+// stability, relative amplitude and the least-active 5 h / most-active 10 h of
+// the average day, all from hourly bins of multi-day activity.
+use chrono::{NaiveDate, Timelike};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::settings::SettingsState;
+use crate::timeseries::TimeSeries;
+
+const HOURS_PER_DAY: usize = 24;
+const L5_HOURS: usize = 5;
+const M10_HOURS: usize = 10;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RestActivityMetrics {
+    /// 0..1; how closely each day repeats the average day.
+    pub interdaily_stability: f64,
+    /// 0..1; (M10 - L5) / (M10 + L5).
+    pub relative_amplitude: f64,
+    /// Mean activity of the least active 5 consecutive hours of the average day.
+    pub l5: f64,
+    /// Local hour (0-23) at which the L5 window starts.
+    pub l5_onset_hour: u32,
+    /// Mean activity of the most active 10 consecutive hours of the average day.
+    pub m10: f64,
+    pub m10_onset_hour: u32,
+    /// Average activity for each local hour of the day.
+    pub hourly_profile: Vec<f64>,
+    /// Hourly bins with at least one sample.
+    pub hours_observed: usize,
+    /// Distinct local dates with data.
+    pub days_observed: usize,
+}
+
+/// Mean activity per local clock hour. On the day clocks fall back the
+/// repeated hour shares one bin.
+fn hourly_bins(activity: &TimeSeries, zone: Tz) -> BTreeMap<(NaiveDate, u32), f64> {
+    let mut sums: BTreeMap<(NaiveDate, u32), (f64, usize)> = BTreeMap::new();
+    for (i, value) in activity.values.iter().enumerate() {
+        let time = activity.time_at(i, zone);
+        let entry = sums.entry((time.date_naive(), time.hour())).or_insert((0.0, 0));
+        entry.0 += value;
+        entry.1 += 1;
+    }
+    sums.into_iter().map(|(key, (sum, count))| (key, sum / count as f64)).collect()
+}
+
+/// Start hour and mean of the `width`-hour window with the lowest (or highest)
+/// mean, wrapping past midnight.
+fn extreme_window(profile: &[f64], width: usize, highest: bool) -> (u32, f64) {
+    let mut best = (0, if highest { f64::NEG_INFINITY } else { f64::INFINITY });
+    for start in 0..HOURS_PER_DAY {
+        let mean = (0..width).map(|k| profile[(start + k) % HOURS_PER_DAY]).sum::<f64>() / width as f64;
+        if (highest && mean > best.1) || (!highest && mean < best.1) {
+            best = (start as u32, mean);
+        }
+    }
+    best
+}
+
+pub fn rest_activity_metrics(activity: &TimeSeries, zone: Tz) -> Result<RestActivityMetrics, String> {
+    let bins = hourly_bins(activity, zone);
+    if bins.len() < HOURS_PER_DAY {
+        return Err(format!(
+            "Rest-activity metrics need at least {} hours of activity, got {}",
+            HOURS_PER_DAY,
+            bins.len()
+        ));
+    }
+
+    let mut hour_sums = [(0.0, 0usize); HOURS_PER_DAY];
+    for (&(_, hour), &value) in &bins {
+        hour_sums[hour as usize].0 += value;
+        hour_sums[hour as usize].1 += 1;
+    }
+    if let Some(missing) = hour_sums.iter().position(|&(_, count)| count == 0) {
+        return Err(format!("No activity recorded for hour {:02}:00 on any day", missing));
+    }
+    let hourly_profile: Vec<f64> = hour_sums.iter().map(|&(sum, count)| sum / count as f64).collect();
+
+    let n = bins.len() as f64;
+    let mean = bins.values().sum::<f64>() / n;
+    let total_variance = bins.values().map(|value| (value - mean).powi(2)).sum::<f64>();
+    let profile_variance = hourly_profile.iter().map(|value| (value - mean).powi(2)).sum::<f64>();
+    let interdaily_stability = if total_variance == 0.0 {
+        0.0
+    } else {
+        (n * profile_variance) / (HOURS_PER_DAY as f64 * total_variance)
+    };
+
+    let (l5_onset_hour, l5) = extreme_window(&hourly_profile, L5_HOURS, false);
+    let (m10_onset_hour, m10) = extreme_window(&hourly_profile, M10_HOURS, true);
+    let relative_amplitude = if m10 + l5 == 0.0 { 0.0 } else { (m10 - l5) / (m10 + l5) };
+
+    let mut days: Vec<NaiveDate> = bins.keys().map(|&(date, _)| date).collect();
+    days.dedup();
+
+    Ok(RestActivityMetrics {
+        interdaily_stability,
+        relative_amplitude,
+        l5,
+        l5_onset_hour,
+        m10,
+        m10_onset_hour,
+        hourly_profile,
+        hours_observed: bins.len(),
+        days_observed: days.len(),
+    })
+}
+
+#[tauri::command]
+pub async fn calculate_rest_activity_metrics(
+    settings_state: tauri::State<'_, SettingsState>,
+    activity: TimeSeries,
+) -> Result<RestActivityMetrics, String> {
+    activity.validate()?;
+    let zone = settings_state
+        .lock()
+        .map_err(|e| format!("Failed to lock settings: {}", e))?
+        .current
+        .zone();
+
+    tauri::async_runtime::spawn_blocking(move || rest_activity_metrics(&activity, zone))
+        .await
+        .map_err(|e| format!("Failed to calculate rest-activity metrics: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono_tz::{Europe::Berlin, UTC};
+
+    /// 2026-03-02 00:00 UTC.
+    const START: i64 = 1_772_409_600_000;
+    const HOUR_MS: i64 = 3_600_000;
+    const SAMPLE_MS: i64 = 15 * 60_000;
+
+    /// Quiet 22:00-02:59, busy 09:00-18:59 and moderate otherwise, so L5 wraps midnight.
+    fn profile(hour: usize) -> f64 {
+        match hour {
+            22 | 23 | 0..=2 => 1.0,
+            9..=18 => 100.0,
+            _ => 20.0,
+        }
+    }
+
+    /// Quarter-hourly activity over `days` days, skipping hours `skip` rejects.
+    fn activity(days: i64, skip: impl Fn(usize) -> bool, mut value: impl FnMut(usize) -> f64) -> TimeSeries {
+        let (mut timestamps, mut values) = (Vec::new(), Vec::new());
+        for i in 0..days * 24 * HOUR_MS / SAMPLE_MS {
+            let t = START + i * SAMPLE_MS;
+            let hour = ((t - START) / HOUR_MS % 24) as usize;
+            if !skip(hour) {
+                timestamps.push(t);
+                values.push(value(hour));
+            }
+        }
+        TimeSeries { timestamps, values }
+    }
+
+    #[test]
+    fn identical_days_are_perfectly_stable() {
+        let metrics = rest_activity_metrics(&activity(7, |_| false, profile), UTC).unwrap();
+        assert!((metrics.interdaily_stability - 1.0).abs() < 1e-9);
+        assert_eq!(metrics.l5, 1.0);
+        assert_eq!(metrics.l5_onset_hour, 22);
+        assert_eq!(metrics.m10, 100.0);
+        assert_eq!(metrics.m10_onset_hour, 9);
+        assert!((metrics.relative_amplitude - 99.0 / 101.0).abs() < 1e-12);
+        assert_eq!(metrics.hourly_profile, (0..24).map(profile).collect::<Vec<_>>());
+        assert_eq!(metrics.hours_observed, 7 * 24);
+        assert_eq!(metrics.days_observed, 7);
+    }
+
+    #[test]
+    fn noise_lowers_interdaily_stability() {
+        let mut seed = 7u64;
+        let noisy = activity(7, |_| false, |hour| {
+            seed = seed.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1_442_695_040_888_963_407);
+            profile(hour) + 200.0 * ((seed >> 11) as f64 / (1u64 << 53) as f64)
+        });
+        let metrics = rest_activity_metrics(&noisy, UTC).unwrap();
+        assert!(metrics.interdaily_stability > 0.2);
+        assert!(metrics.interdaily_stability < 0.95);
+        // The shape of the average day survives the noise
+        assert_eq!(metrics.l5_onset_hour, 22);
+        assert_eq!(metrics.m10_onset_hour, 9);
+    }
+
+    #[test]
+    fn rejects_short_or_incomplete_records() {
+        let short = activity(1, |hour| hour == 23, profile);
+        let err = rest_activity_metrics(&short, UTC).unwrap_err();
+        assert!(err.contains("at least 24 hours"), "{}", err);
+
+        let gap = activity(3, |hour| hour == 5, profile);
+        assert_eq!(
+            rest_activity_metrics(&gap, UTC).unwrap_err(),
+            "No activity recorded for hour 05:00 on any day"
+        );
+    }
+
+    #[test]
+    fn repeated_hour_shares_one_bin_on_fall_back() {
+        // Berlin falls back at 03:00 CEST on 2026-10-25; hourly samples from local midnight
+        let midnight = 1_792_879_200_000;
+        let series = TimeSeries {
+            timestamps: (0..25).map(|i| midnight + i * HOUR_MS).collect(),
+            values: (0..25).map(|i| i as f64).collect(),
+        };
+        let bins = hourly_bins(&series, Berlin);
+        let date = NaiveDate::from_ymd_opt(2026, 10, 25).unwrap();
+        assert_eq!(bins.len(), 24);
+        assert_eq!(bins[&(date, 1)], 1.0);
+        assert_eq!(bins[&(date, 2)], 2.5);
+        assert_eq!(bins[&(date, 3)], 4.0);
+        assert_eq!(bins[&(date, 23)], 24.0);
+    }
+}
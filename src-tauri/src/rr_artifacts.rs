@@ -0,0 +1,398 @@
+// RR-interval artifact detection and correction with the adaptive thresholds
+// of Lipponen & Tarvainen (2019): ectopic beats are interpolated, missed beats
+// split and extra beats merged.
+use serde::{Deserialize, Serialize};
+
+use crate::hrv::RrSeries;
+
+/// Tuning constants of the ectopic-beat decision boundary.
+const ECTOPIC_SLOPE: f64 = 0.13;
+const ECTOPIC_OFFSET: f64 = 0.17;
+/// Beats whose deviation from the local median exceeds this many thresholds
+/// are long or short.
+const MEDIAN_DEVIATION_LIMIT: f64 = 3.0;
+/// Thresholds never drop below this (ms), so a perfectly regular series does
+/// not flag millisecond jitter.
+const MIN_THRESHOLD_MS: f64 = 1.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArtifactKind {
+    /// Premature beat with its compensatory pause.
+    Ectopic,
+    /// An undetected beat, leaving an interval about twice the usual length.
+    Missed,
+    /// A spurious beat splitting one interval in two.
+    Extra,
+    /// Too long or too short without a recognisable pattern.
+    LongShort,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ArtifactConfig {
+    /// Threshold multiple of the quartile deviation; 5.2 in the original paper.
+    pub alpha: f64,
+    /// Beats in the rolling window the thresholds are estimated over.
+    pub threshold_window_beats: usize,
+    /// Beats in the rolling median of the RR series.
+    pub median_window_beats: usize,
+    /// Results with more corrected beats than this are flagged unreliable.
+    pub max_corrected_percent: f64,
+}
+
+impl Default for ArtifactConfig {
+    fn default() -> Self {
+        Self {
+            alpha: 5.2,
+            threshold_window_beats: 91,
+            median_window_beats: 11,
+            max_corrected_percent: 5.0,
+        }
+    }
+}
+
+impl ArtifactConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.alpha <= 0.0 || self.max_corrected_percent < 0.0 {
+            return Err("Artifact alpha must be positive and the corrected limit non-negative".to_string());
+        }
+        if self.threshold_window_beats < 3 || self.median_window_beats < 3 {
+            return Err("Artifact windows must span at least 3 beats".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RrArtifact {
+    /// Index into the input intervals.
+    pub index: usize,
+    pub kind: ArtifactKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RrCorrection {
+    pub series: RrSeries,
+    /// Whether each output interval was changed or created by correction.
+    pub corrected: Vec<bool>,
+    pub artifacts: Vec<RrArtifact>,
+    pub ectopic: usize,
+    pub missed: usize,
+    pub extra: usize,
+    pub long_short: usize,
+    /// Share of input intervals touched by correction.
+    pub corrected_beats_percent: f64,
+    pub reliable: bool,
+}
+
+/// Linearly interpolated quantile of sorted `values`.
+fn quantile(sorted: &[f64], q: f64) -> f64 {
+    let position = q * (sorted.len() - 1) as f64;
+    let (lower, upper) = (position.floor() as usize, position.ceil() as usize);
+    sorted[lower] + (position - lower as f64) * (sorted[upper] - sorted[lower])
+}
+
+/// `f` of the sorted values in a centred window of `width` at each index,
+/// shrinking at the edges.
+fn rolling(values: &[f64], width: usize, f: impl Fn(&[f64]) -> f64) -> Vec<f64> {
+    let half = width / 2;
+    let mut window = Vec::with_capacity(width);
+    (0..values.len())
+        .map(|i| {
+            window.clear();
+            window.extend_from_slice(&values[i.saturating_sub(half)..(i + half + 1).min(values.len())]);
+            window.sort_by(f64::total_cmp);
+            f(&window)
+        })
+        .collect()
+}
+
+/// Alpha times the rolling quartile deviation of |`values`|.
+fn threshold(values: &[f64], config: &ArtifactConfig) -> Vec<f64> {
+    let magnitudes: Vec<f64> = values.iter().map(|v| v.abs()).collect();
+    rolling(&magnitudes, config.threshold_window_beats, |w| {
+        (config.alpha * (quantile(w, 0.75) - quantile(w, 0.25)) / 2.0).max(MIN_THRESHOLD_MS)
+    })
+}
+
+/// Labels each input interval; ectopic beats mark both intervals they distort.
+pub fn detect_artifacts(rr: &[f64], config: &ArtifactConfig) -> Vec<RrArtifact> {
+    let n = rr.len();
+    if n < 4 {
+        return Vec::new();
+    }
+    let mut diffs: Vec<f64> = (0..n).map(|i| if i == 0 { 0.0 } else { rr[i] - rr[i - 1] }).collect();
+    diffs[0] = diffs[1..].iter().sum::<f64>() / (n - 1) as f64;
+    let th1 = threshold(&diffs, config);
+    let d: Vec<f64> = diffs.iter().zip(&th1).map(|(v, t)| v / t).collect();
+
+    let median = rolling(rr, config.median_window_beats, |w| quantile(w, 0.5));
+    // Short intervals weigh double, as they mostly come from extra beats
+    let deviations: Vec<f64> = rr.iter().zip(&median).map(|(r, m)| if r < m { 2.0 * (r - m) } else { r - m }).collect();
+    let th2 = threshold(&deviations, config);
+    let m: Vec<f64> = deviations.iter().zip(&th2).map(|(v, t)| v / t).collect();
+
+    // Reflected past either end
+    let last = n as isize - 1;
+    let at = |i: isize| d[if i < 0 { -i } else if i > last { 2 * last - i } else { i } as usize];
+    let mut artifacts: Vec<RrArtifact> = Vec::new();
+    // A later, more specific label for the same interval replaces the earlier one
+    let mut flag = |index: isize, kind| match artifacts.last_mut() {
+        _ if index < 0 || index > last => {}
+        Some(previous) if previous.index as isize == index => previous.kind = kind,
+        Some(previous) if previous.index as isize > index => {}
+        _ => artifacts.push(RrArtifact { index: index as usize, kind }),
+    };
+    let mut merged_with_previous = false;
+    for j in 0..n {
+        let i = j as isize;
+        if std::mem::take(&mut merged_with_previous) {
+            continue;
+        }
+        if d[j].abs() <= 1.0 && m[j].abs() <= MEDIAN_DEVIATION_LIMIT {
+            continue;
+        }
+        // Ectopic beats make the differences alternate in sign around here:
+        // short-long gives -+- and long-short +-+
+        let s12 = if d[j] > 0.0 { at(i - 1).max(at(i + 1)) } else { at(i - 1).min(at(i + 1)) };
+        let ectopic = (d[j] > 1.0 && s12 < -ECTOPIC_SLOPE * d[j] - ECTOPIC_OFFSET)
+            || (d[j] < -1.0 && s12 > -ECTOPIC_SLOPE * d[j] + ECTOPIC_OFFSET);
+        if ectopic {
+            // The difference spans the interval it indexes and the one before
+            flag(i - 1, ArtifactKind::Ectopic);
+            flag(i, ArtifactKind::Ectopic);
+            continue;
+        }
+
+        let s22 = if d[j] >= 0.0 { at(i + 1).min(at(i + 2)) } else { at(i + 1).max(at(i + 2)) };
+        let long_then_short = d[j] > 1.0 && s22 < -1.0;
+        let short_then_long = d[j] < -1.0 && s22 > 1.0;
+        if !(long_then_short || short_then_long || m[j].abs() > MEDIAN_DEVIATION_LIMIT) {
+            continue;
+        }
+        let kind = if long_then_short && (rr[j] / 2.0 - median[j]).abs() < th2[j] {
+            ArtifactKind::Missed
+        } else if short_then_long && j + 1 < n && (rr[j] + rr[j + 1] - median[j]).abs() < th2[j] {
+            merged_with_previous = true;
+            ArtifactKind::Extra
+        } else {
+            ArtifactKind::LongShort
+        };
+        flag(i, kind);
+    }
+    artifacts
+}
+
+pub fn correct_artifacts(series: &RrSeries, config: &ArtifactConfig) -> RrCorrection {
+    let rr = &series.intervals;
+    let n = rr.len();
+    let artifacts = detect_artifacts(rr, config);
+    let mut kinds = vec![None; n];
+    for artifact in &artifacts {
+        kinds[artifact.index] = Some(artifact.kind);
+    }
+    let median = rolling(rr, config.median_window_beats, |w| quantile(w, 0.5));
+
+    // Interpolate from the nearest clean intervals on each side
+    let interpolate = |j: usize| {
+        let previous = (0..j).rev().find(|&k| kinds[k].is_none());
+        let next = (j + 1..n).find(|&k| kinds[k].is_none());
+        match (previous, next) {
+            (Some(p), Some(q)) => rr[p] + (rr[q] - rr[p]) * (j - p) as f64 / (q - p) as f64,
+            (Some(k), None) | (None, Some(k)) => rr[k],
+            (None, None) => median[j],
+        }
+    };
+
+    let mut intervals = Vec::with_capacity(n);
+    let mut corrected = Vec::with_capacity(n);
+    let mut touched = 0;
+    let mut j = 0;
+    while j < n {
+        match kinds[j] {
+            None => {
+                intervals.push(rr[j]);
+                corrected.push(false);
+            }
+            Some(ArtifactKind::Extra) if j + 1 < n => {
+                intervals.push(rr[j] + rr[j + 1]);
+                corrected.push(true);
+                touched += 2;
+                j += 2;
+                continue;
+            }
+            Some(ArtifactKind::Missed) => {
+                let pieces = (rr[j] / median[j]).round().max(2.0);
+                for _ in 0..pieces as usize {
+                    intervals.push(rr[j] / pieces);
+                    corrected.push(true);
+                }
+                touched += 1;
+            }
+            Some(_) => {
+                intervals.push(interpolate(j));
+                corrected.push(true);
+                touched += 1;
+            }
+        }
+        j += 1;
+    }
+
+    let count = |kind| artifacts.iter().filter(|a| a.kind == kind).count();
+    let corrected_beats_percent = if n > 0 { touched as f64 / n as f64 * 100.0 } else { 0.0 };
+    RrCorrection {
+        series: RrSeries { start: series.start, intervals },
+        corrected,
+        ectopic: count(ArtifactKind::Ectopic),
+        missed: count(ArtifactKind::Missed),
+        extra: count(ArtifactKind::Extra),
+        long_short: count(ArtifactKind::LongShort),
+        artifacts,
+        corrected_beats_percent,
+        reliable: corrected_beats_percent <= config.max_corrected_percent,
+    }
+}
+
+#[tauri::command]
+pub fn correct_rr_intervals(series: RrSeries, config: Option<ArtifactConfig>) -> Result<RrCorrection, String> {
+    let config = config.unwrap_or_default();
+    config.validate()?;
+    series.validate()?;
+    Ok(correct_artifacts(&series, &config))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2026-03-02 00:00 UTC.
+    const START: i64 = 1_772_409_600_000;
+
+    /// A slow 25 ms swing around 1000 ms with a little noise.
+    fn clean(count: usize) -> Vec<f64> {
+        let mut seed = 3u64;
+        (0..count)
+            .map(|i| {
+                seed = seed.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1_442_695_040_888_963_407);
+                let noise = (seed >> 11) as f64 / (1u64 << 53) as f64 - 0.5;
+                1000.0 + 25.0 * (2.0 * std::f64::consts::PI * i as f64 / 15.0).sin() + 10.0 * noise
+            })
+            .collect()
+    }
+
+    fn correct(intervals: Vec<f64>) -> RrCorrection {
+        correct_artifacts(&RrSeries { start: START, intervals }, &ArtifactConfig::default())
+    }
+
+    fn kinds(correction: &RrCorrection) -> Vec<(usize, ArtifactKind)> {
+        correction.artifacts.iter().map(|a| (a.index, a.kind)).collect()
+    }
+
+    /// A premature beat at `index` followed by its compensatory pause.
+    fn with_ectopic(mut rr: Vec<f64>, index: usize) -> Vec<f64> {
+        rr[index] -= 300.0;
+        rr[index + 1] += 300.0;
+        rr
+    }
+
+    #[test]
+    fn clean_series_pass_unchanged() {
+        let rr = clean(200);
+        let correction = correct(rr.clone());
+        assert!(correction.artifacts.is_empty());
+        assert_eq!(correction.series.intervals, rr);
+        assert!(correction.corrected.iter().all(|&c| !c));
+        assert_eq!(correction.corrected_beats_percent, 0.0);
+        assert!(correction.reliable);
+    }
+
+    #[test]
+    fn ectopic_beat_is_interpolated() {
+        let rr = with_ectopic(clean(200), 50);
+        let correction = correct(rr.clone());
+        assert_eq!(kinds(&correction), [(50, ArtifactKind::Ectopic), (51, ArtifactKind::Ectopic)]);
+        assert_eq!(correction.ectopic, 2);
+        assert_eq!(correction.series.intervals.len(), 200);
+        // Both distorted intervals lie on the line between their clean neighbours
+        let step = (rr[52] - rr[49]) / 3.0;
+        assert!((correction.series.intervals[50] - (rr[49] + step)).abs() < 1e-9);
+        assert!((correction.series.intervals[51] - (rr[49] + 2.0 * step)).abs() < 1e-9);
+        assert_eq!(correction.corrected.iter().filter(|&&c| c).count(), 2);
+        assert!((correction.corrected_beats_percent - 1.0).abs() < 1e-9);
+        assert!(correction.reliable);
+    }
+
+    #[test]
+    fn missed_beat_is_split() {
+        let mut rr = clean(200);
+        let doubled = rr[100] + rr[101];
+        rr.splice(100..102, [doubled]);
+        let correction = correct(rr);
+        assert_eq!(kinds(&correction), [(100, ArtifactKind::Missed)]);
+        assert_eq!(correction.missed, 1);
+        assert_eq!(correction.series.intervals.len(), 200);
+        assert_eq!(correction.series.intervals[100..102], [doubled / 2.0; 2]);
+        assert_eq!(correction.corrected[99..103], [false, true, true, false]);
+        assert!((correction.corrected_beats_percent - 100.0 / 199.0).abs() < 1e-9);
+        assert!(correction.reliable);
+    }
+
+    #[test]
+    fn extra_beat_is_merged() {
+        let mut rr = clean(200);
+        let whole = rr[150];
+        rr.splice(150..151, [0.4 * whole, 0.6 * whole]);
+        let correction = correct(rr);
+        assert_eq!(kinds(&correction), [(150, ArtifactKind::Extra)]);
+        assert_eq!(correction.extra, 1);
+        assert_eq!(correction.series.intervals.len(), 200);
+        assert!((correction.series.intervals[150] - whole).abs() < 1e-9);
+        assert_eq!(correction.corrected.iter().filter(|&&c| c).count(), 1);
+        // Both input intervals were consumed by the merge
+        assert!((correction.corrected_beats_percent - 200.0 / 201.0).abs() < 1e-9);
+        assert!(correction.reliable);
+    }
+
+    #[test]
+    fn each_kind_is_found_in_one_series() {
+        let mut rr = with_ectopic(clean(200), 50);
+        let doubled = rr[100] + rr[101];
+        rr.splice(100..102, [doubled]);
+        let whole = rr[150];
+        rr.splice(150..151, [0.4 * whole, 0.6 * whole]);
+        let correction = correct(rr);
+        assert_eq!(
+            kinds(&correction),
+            [
+                (50, ArtifactKind::Ectopic),
+                (51, ArtifactKind::Ectopic),
+                (100, ArtifactKind::Missed),
+                (150, ArtifactKind::Extra),
+            ]
+        );
+        assert_eq!(correction.series.intervals.len(), 200);
+        // Two ectopic, one missed and two merged intervals out of 200
+        assert!((correction.corrected_beats_percent - 2.5).abs() < 1e-9);
+        assert!(correction.reliable);
+        assert_eq!(correction.long_short, 0);
+    }
+
+    #[test]
+    fn too_many_corrections_are_unreliable() {
+        let rr = (1..8).fold(clean(200), |rr, k| with_ectopic(rr, 25 * k));
+        let correction = correct(rr);
+        assert_eq!(correction.ectopic, 14);
+        assert!((correction.corrected_beats_percent - 7.0).abs() < 1e-9);
+        assert!(!correction.reliable);
+    }
+
+    #[test]
+    fn short_series_and_bad_configs() {
+        assert!(detect_artifacts(&[1000.0, 400.0, 1000.0], &ArtifactConfig::default()).is_empty());
+        assert!(ArtifactConfig::default().validate().is_ok());
+        assert!(ArtifactConfig { alpha: 0.0, ..Default::default() }.validate().is_err());
+        assert!(ArtifactConfig { median_window_beats: 2, ..Default::default() }.validate().is_err());
+        assert!(ArtifactConfig { max_corrected_percent: -1.0, ..Default::default() }.validate().is_err());
+    }
+}
@@ -0,0 +1,288 @@
+// User settings for the ultradian model and tray, persisted as versioned JSON
+// in the app config directory.
+use chrono::NaiveTime;
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use crate::anchor::{AnchorMode, AnchorState};
+use crate::notifications::NotificationSettings;
+use crate::timezone;
+use crate::ultradian::{PhaseBand, TrayDisplayMode, UltradianClock};
+
+pub const SETTINGS_VERSION: u32 = 1;
+const SETTINGS_FILE: &str = "settings.json";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub version: u32,
+    pub cycle_length_minutes: f64,
+    /// Rising transition at the start of each cycle.
+    pub rise_transition_minutes: f64,
+    /// High-energy window after the rising transition.
+    pub high_energy_minutes: f64,
+    /// Falling transition after the high window; the remainder of the cycle is low energy.
+    pub fall_transition_minutes: f64,
+    /// Display table, each band ending at `until` minutes into the cycle.
+    pub phases: Vec<PhaseBand>,
+    pub tray_update_interval_seconds: u64,
+    pub tray_display_mode: TrayDisplayMode,
+    pub anchor_mode: AnchorMode,
+    pub wake_time: NaiveTime,
+    pub notifications: NotificationSettings,
+    /// IANA time zone name; the system zone is used when unset.
+    pub timezone: Option<String>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        let clock = UltradianClock::default();
+        Self {
+            version: SETTINGS_VERSION,
+            cycle_length_minutes: clock.cycle_length,
+            rise_transition_minutes: clock.rise_end,
+            high_energy_minutes: clock.high_end - clock.rise_end,
+            fall_transition_minutes: clock.fall_end - clock.high_end,
+            phases: clock.bands,
+            tray_update_interval_seconds: 1,
+            tray_display_mode: TrayDisplayMode::Countdown,
+            anchor_mode: AnchorMode::Midnight,
+            wake_time: NaiveTime::from_hms_opt(7, 0, 0).unwrap(),
+            notifications: NotificationSettings::default(),
+            timezone: None,
+        }
+    }
+}
+
+impl Settings {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(45.0..=180.0).contains(&self.cycle_length_minutes) {
+            return Err(format!(
+                "Cycle length must be between 45 and 180 minutes, got {}",
+                self.cycle_length_minutes
+            ));
+        }
+        // The rising transition scales intensity by its length, so it cannot be empty
+        if self.rise_transition_minutes <= 0.0 {
+            return Err("Rising transition must be longer than 0 minutes".to_string());
+        }
+        if self.fall_transition_minutes < 0.0 {
+            return Err("Transition lengths cannot be negative".to_string());
+        }
+        if self.high_energy_minutes <= 0.0 {
+            return Err("High-energy window must be longer than 0 minutes".to_string());
+        }
+        let active = self.rise_transition_minutes + self.high_energy_minutes + self.fall_transition_minutes;
+        if active >= self.cycle_length_minutes {
+            return Err(format!(
+                "Transitions and high-energy window ({} min) must leave room for a low phase in a {} min cycle",
+                active, self.cycle_length_minutes
+            ));
+        }
+
+        if self.phases.is_empty() {
+            return Err("At least one phase label is required".to_string());
+        }
+        let mut previous = 0.0;
+        for phase in &self.phases {
+            if phase.until <= previous {
+                return Err(format!("Phase '{}' must end after the previous phase", phase.label));
+            }
+            if phase.label.trim().is_empty() || phase.icon.trim().is_empty() {
+                return Err("Phase labels and icons cannot be empty".to_string());
+            }
+            previous = phase.until;
+        }
+        if previous < self.cycle_length_minutes {
+            return Err(format!(
+                "Phases end at {} min but the cycle is {} min long",
+                previous, self.cycle_length_minutes
+            ));
+        }
+
+        if !(1..=60).contains(&self.tray_update_interval_seconds) {
+            return Err("Tray update interval must be between 1 and 60 seconds".to_string());
+        }
+        if let Some(name) = &self.timezone {
+            name.parse::<Tz>().map_err(|_| format!("Unknown time zone '{}'", name))?;
+        }
+        self.notifications.validate(self.cycle_length_minutes)
+    }
+
+    pub fn zone(&self) -> Tz {
+        timezone::active_zone(self.timezone.as_deref())
+    }
+
+    pub fn clock(&self) -> UltradianClock {
+        let rise_end = self.rise_transition_minutes;
+        let high_end = rise_end + self.high_energy_minutes;
+        UltradianClock {
+            cycle_length: self.cycle_length_minutes,
+            rise_end,
+            high_end,
+            fall_end: high_end + self.fall_transition_minutes,
+            bands: self.phases.clone(),
+        }
+    }
+}
+
+/// Current settings together with the file they are saved to.
+pub struct SettingsStore {
+    path: PathBuf,
+    pub current: Settings,
+}
+
+impl SettingsStore {
+    /// Loads settings from `config_dir`, falling back to defaults when the file
+    /// is missing, unreadable or fails validation.
+    pub fn load(config_dir: &Path) -> Self {
+        let path = config_dir.join(SETTINGS_FILE);
+        let current = match fs::read_to_string(&path) {
+            Ok(contents) => match parse_settings(&contents) {
+                Ok(settings) => settings,
+                Err(e) => {
+                    eprintln!("Warning: ignoring settings file {}: {}", path.display(), e);
+                    Settings::default()
+                }
+            },
+            Err(_) => Settings::default(),
+        };
+        Self { path, current }
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir).map_err(|e| format!("Failed to create config directory: {}", e))?;
+        }
+        let json = serde_json::to_string_pretty(&self.current)
+            .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+        fs::write(&self.path, json).map_err(|e| format!("Failed to write settings: {}", e))
+    }
+
+    /// Validates, persists and installs new settings.
+    pub fn replace(&mut self, mut settings: Settings) -> Result<(), String> {
+        settings.version = SETTINGS_VERSION;
+        settings.validate()?;
+        let previous = std::mem::replace(&mut self.current, settings);
+        if let Err(e) = self.save() {
+            self.current = previous;
+            return Err(e);
+        }
+        Ok(())
+    }
+}
+
+fn parse_settings(contents: &str) -> Result<Settings, String> {
+    let mut settings: Settings = serde_json::from_str(contents).map_err(|e| e.to_string())?;
+    if settings.version > SETTINGS_VERSION {
+        return Err(format!("unsupported settings version {}", settings.version));
+    }
+    // Older files only lack fields, which serde fills with defaults.
+    settings.version = SETTINGS_VERSION;
+    settings.validate()?;
+    Ok(settings)
+}
+
+pub type SettingsState = Arc<Mutex<SettingsStore>>;
+
+#[tauri::command]
+pub fn get_settings(state: tauri::State<'_, SettingsState>) -> Result<Settings, String> {
+    let store = state.lock().map_err(|e| format!("Failed to lock settings: {}", e))?;
+    Ok(store.current.clone())
+}
+
+#[tauri::command]
+pub fn update_settings(
+    state: tauri::State<'_, SettingsState>,
+    anchor_state: tauri::State<'_, AnchorState>,
+    settings: Settings,
+) -> Result<Settings, String> {
+    let mut store = state.lock().map_err(|e| format!("Failed to lock settings: {}", e))?;
+    store.replace(settings)?;
+    if let Ok(mut anchor) = anchor_state.lock() {
+        anchor.mode = store.current.anchor_mode;
+        anchor.wake_time = store.current.wake_time;
+    }
+    Ok(store.current.clone())
+}
+
+#[tauri::command]
+pub fn reset_settings(
+    state: tauri::State<'_, SettingsState>,
+    anchor_state: tauri::State<'_, AnchorState>,
+) -> Result<Settings, String> {
+    update_settings(state, anchor_state, Settings::default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_are_valid() {
+        assert!(Settings::default().validate().is_ok());
+    }
+
+    #[test]
+    fn rejects_empty_rising_transition() {
+        let settings = Settings { rise_transition_minutes: 0.0, ..Settings::default() };
+        assert!(settings.validate().is_err());
+        let clock = Settings { rise_transition_minutes: 1.0, ..Settings::default() }.clock();
+        assert!(clock.energy_intensity(0.0).is_finite());
+    }
+
+    #[test]
+    fn allows_empty_falling_transition() {
+        let settings = Settings { fall_transition_minutes: 0.0, ..Settings::default() };
+        assert!(settings.validate().is_ok());
+        let clock = settings.clock();
+        assert!((0..=90).all(|m| clock.energy_intensity(m as f64).is_finite()));
+    }
+
+    #[test]
+    fn rejects_active_window_filling_the_cycle() {
+        let settings = Settings { high_energy_minutes: 80.0, ..Settings::default() };
+        assert!(settings.validate().is_err());
+    }
+
+    #[test]
+    fn rejects_cycle_length_out_of_range() {
+        assert!(Settings { cycle_length_minutes: 30.0, ..Settings::default() }.validate().is_err());
+        assert!(Settings { cycle_length_minutes: 200.0, ..Settings::default() }.validate().is_err());
+    }
+
+    #[test]
+    fn rejects_bad_phase_table() {
+        let mut settings = Settings::default();
+        settings.phases.swap(0, 1);
+        assert!(settings.validate().is_err());
+
+        let mut settings = Settings::default();
+        settings.phases.pop();
+        assert!(settings.validate().is_err(), "phases must cover the whole cycle");
+
+        let mut settings = Settings::default();
+        settings.phases[0].label = " ".to_string();
+        assert!(settings.validate().is_err());
+    }
+
+    #[test]
+    fn rejects_unknown_time_zone() {
+        let settings = Settings { timezone: Some("Mars/Olympus".to_string()), ..Settings::default() };
+        assert!(settings.validate().is_err());
+        let settings = Settings { timezone: Some("Europe/Berlin".to_string()), ..Settings::default() };
+        assert!(settings.validate().is_ok());
+    }
+
+    #[test]
+    fn parses_older_files_with_missing_fields() {
+        let settings = parse_settings(r#"{"version": 0, "tray_update_interval_seconds": 5}"#).unwrap();
+        assert_eq!(settings.version, SETTINGS_VERSION);
+        assert_eq!(settings.tray_update_interval_seconds, 5);
+        assert_eq!(settings.phases, Settings::default().phases);
+        assert!(parse_settings(r#"{"version": 99}"#).is_err());
+    }
+}
@@ -0,0 +1,312 @@
+// Sleep-period metrics from scored epochs: onset latency, total sleep, WASO,
+// awakenings, fragmentation and efficiency against the actual time in bed.
+use serde::{Deserialize, Serialize};
+
+use crate::sleep_scoring::{self, EpochLabel};
+
+/// Sleep bouts closer than this belong to the same detected rest interval.
+const DETECTION_MAX_WAKE_GAP_MINUTES: f64 = 60.0;
+
+/// The parts of a sleep scoring the metrics need.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScoredEpochs {
+    pub epoch_seconds: u32,
+    pub epoch_starts: Vec<i64>,
+    pub labels: Vec<EpochLabel>,
+}
+
+impl ScoredEpochs {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.epoch_seconds == 0 {
+            return Err("Epoch length must be positive".to_string());
+        }
+        if self.epoch_starts.len() != self.labels.len() {
+            return Err(format!(
+                "Scoring has {} epoch starts but {} labels",
+                self.epoch_starts.len(),
+                self.labels.len()
+            ));
+        }
+        if self.epoch_starts.windows(2).any(|pair| pair[1] <= pair[0]) {
+            return Err("Epoch starts must increase".to_string());
+        }
+        Ok(())
+    }
+
+    fn epoch_ms(&self) -> i64 {
+        self.epoch_seconds as i64 * 1000
+    }
+
+    fn minutes(&self, epochs: usize) -> f64 {
+        epochs as f64 * self.epoch_seconds as f64 / 60.0
+    }
+}
+
+/// Bedtime and rise time (epoch ms), e.g. from a sleep diary or HealthKit's
+/// in-bed records.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct InBedWindow {
+    pub bed_time: i64,
+    pub rise_time: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SleepPeriodMetrics {
+    pub bed_time: i64,
+    pub rise_time: i64,
+    /// True when the window was detected from the scoring rather than given;
+    /// it then starts and ends with sleep, so latency and terminal wake are 0.
+    pub window_detected: bool,
+    /// First sleep epoch in the window.
+    pub sleep_onset: Option<i64>,
+    /// End of the last sleep epoch in the window.
+    pub final_wake: Option<i64>,
+    pub sleep_onset_latency_minutes: Option<f64>,
+    pub total_sleep_minutes: f64,
+    pub time_in_bed_minutes: f64,
+    /// Time in bed without samples; excluded from efficiency.
+    pub missing_minutes: f64,
+    /// Wake between sleep onset and final wake.
+    pub waso_minutes: f64,
+    /// Wake between final wake and rise time.
+    pub terminal_wake_minutes: f64,
+    pub awakenings: usize,
+    pub mean_awakening_minutes: f64,
+    pub longest_awakening_minutes: f64,
+    /// Awakenings per hour of total sleep.
+    pub sleep_fragmentation_index: f64,
+    /// Percentage of observed time in bed spent asleep.
+    pub sleep_efficiency: f64,
+}
+
+/// The longest stretch of sleep bouts separated only by short wake, spanning
+/// no missing data.
+pub fn detect_rest_interval(scoring: &ScoredEpochs) -> Option<InBedWindow> {
+    let max_gap_ms = (DETECTION_MAX_WAKE_GAP_MINUTES * 60_000.0) as i64;
+    let starts = &scoring.epoch_starts;
+    let mut best: Option<(usize, usize)> = None;
+    let mut current: Option<(usize, usize)> = None;
+    for (label, start, len) in sleep_scoring::label_runs(&scoring.labels) {
+        if label != EpochLabel::Sleep {
+            if label == EpochLabel::NoData {
+                current = None;
+            }
+            continue;
+        }
+        let end = start + len - 1;
+        let merged = match current {
+            Some((first, last)) if starts[start] - starts[last] - scoring.epoch_ms() <= max_gap_ms => (first, end),
+            _ => (start, end),
+        };
+        let span = |(first, last): (usize, usize)| starts[last] - starts[first];
+        if best.is_none_or(|b| span(merged) > span(b)) {
+            best = Some(merged);
+        }
+        current = Some(merged);
+    }
+    best.map(|(first, last)| InBedWindow {
+        bed_time: starts[first],
+        rise_time: starts[last] + scoring.epoch_ms(),
+    })
+}
+
+pub fn sleep_period_metrics(scoring: &ScoredEpochs, window: Option<InBedWindow>) -> Result<SleepPeriodMetrics, String> {
+    let window_detected = window.is_none();
+    let window = match window {
+        Some(window) => window,
+        None => detect_rest_interval(scoring).ok_or("No sleep was scored, so there is no rest interval to analyse")?,
+    };
+    if window.rise_time <= window.bed_time {
+        return Err("Rise time must be after bedtime".to_string());
+    }
+
+    let in_bed: Vec<usize> = (0..scoring.labels.len())
+        .filter(|&i| scoring.epoch_starts[i] >= window.bed_time && scoring.epoch_starts[i] < window.rise_time)
+        .collect();
+    let (Some(&first), Some(&last)) = (in_bed.first(), in_bed.last()) else {
+        return Err("No scored epochs fall between bedtime and rise time".to_string());
+    };
+    let labels = &scoring.labels[first..=last];
+    let time_in_bed_minutes = (window.rise_time - window.bed_time) as f64 / 60_000.0;
+    // Time in bed before the first or after the last scored epoch counts as missing
+    let uncovered_minutes = (time_in_bed_minutes - scoring.minutes(labels.len())).max(0.0);
+    let missing_minutes =
+        uncovered_minutes + scoring.minutes(labels.iter().filter(|&&l| l == EpochLabel::NoData).count());
+    let total_sleep_minutes = scoring.minutes(labels.iter().filter(|&&l| l == EpochLabel::Sleep).count());
+    let observed_minutes = time_in_bed_minutes - missing_minutes;
+    let sleep_efficiency = if observed_minutes > 0.0 { total_sleep_minutes / observed_minutes * 100.0 } else { 0.0 };
+
+    let mut metrics = SleepPeriodMetrics {
+        bed_time: window.bed_time,
+        rise_time: window.rise_time,
+        window_detected,
+        sleep_onset: None,
+        final_wake: None,
+        sleep_onset_latency_minutes: None,
+        total_sleep_minutes,
+        time_in_bed_minutes,
+        missing_minutes,
+        waso_minutes: 0.0,
+        terminal_wake_minutes: 0.0,
+        awakenings: 0,
+        mean_awakening_minutes: 0.0,
+        longest_awakening_minutes: 0.0,
+        sleep_fragmentation_index: 0.0,
+        sleep_efficiency,
+    };
+    let (Some(onset), Some(offset)) = (
+        labels.iter().position(|&l| l == EpochLabel::Sleep),
+        labels.iter().rposition(|&l| l == EpochLabel::Sleep),
+    ) else {
+        return Ok(metrics);
+    };
+
+    let sleep_onset = scoring.epoch_starts[first + onset];
+    let final_wake = scoring.epoch_starts[first + offset] + scoring.epoch_ms();
+    let awakenings: Vec<f64> = sleep_scoring::label_runs(&labels[onset..=offset])
+        .into_iter()
+        .filter(|&(label, _, _)| label == EpochLabel::Wake)
+        .map(|(_, _, len)| scoring.minutes(len))
+        .collect();
+
+    metrics.sleep_onset = Some(sleep_onset);
+    metrics.final_wake = Some(final_wake);
+    metrics.sleep_onset_latency_minutes = Some((sleep_onset - window.bed_time) as f64 / 60_000.0);
+    metrics.terminal_wake_minutes = (window.rise_time - final_wake).max(0) as f64 / 60_000.0;
+    metrics.waso_minutes = awakenings.iter().sum();
+    metrics.awakenings = awakenings.len();
+    if !awakenings.is_empty() {
+        metrics.mean_awakening_minutes = metrics.waso_minutes / awakenings.len() as f64;
+        metrics.longest_awakening_minutes = awakenings.iter().copied().fold(0.0, f64::max);
+    }
+    metrics.sleep_fragmentation_index = awakenings.len() as f64 / (total_sleep_minutes / 60.0);
+    Ok(metrics)
+}
+
+/// Without a window the main rest interval is detected from the scoring.
+#[tauri::command]
+pub fn calculate_sleep_period_metrics(
+    scoring: ScoredEpochs,
+    window: Option<InBedWindow>,
+) -> Result<SleepPeriodMetrics, String> {
+    scoring.validate()?;
+    sleep_period_metrics(&scoring, window)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use EpochLabel::{NoData, Sleep, Wake};
+
+    /// 2026-03-02 22:00 UTC.
+    const BED: i64 = 1_772_488_800_000;
+    const MINUTE: i64 = 60_000;
+
+    fn scored(spec: &[(EpochLabel, usize)]) -> ScoredEpochs {
+        let labels: Vec<EpochLabel> = spec.iter().flat_map(|&(label, len)| std::iter::repeat_n(label, len)).collect();
+        ScoredEpochs {
+            epoch_seconds: 60,
+            epoch_starts: (0..labels.len() as i64).map(|i| BED + i * MINUTE).collect(),
+            labels,
+        }
+    }
+
+    fn window(bed_minute: i64, rise_minute: i64) -> InBedWindow {
+        InBedWindow { bed_time: BED + bed_minute * MINUTE, rise_time: BED + rise_minute * MINUTE }
+    }
+
+    #[test]
+    fn measures_latency_waso_and_fragmentation_in_a_given_window() {
+        let scoring = scored(&[(Wake, 10), (Sleep, 20), (Wake, 5), (Sleep, 10), (Wake, 3), (Sleep, 7), (Wake, 5)]);
+        let metrics = sleep_period_metrics(&scoring, Some(window(0, 60))).unwrap();
+
+        assert!(!metrics.window_detected);
+        assert_eq!(metrics.sleep_onset, Some(BED + 10 * MINUTE));
+        assert_eq!(metrics.final_wake, Some(BED + 55 * MINUTE));
+        assert_eq!(metrics.sleep_onset_latency_minutes, Some(10.0));
+        assert_eq!(metrics.total_sleep_minutes, 37.0);
+        assert_eq!(metrics.time_in_bed_minutes, 60.0);
+        assert_eq!(metrics.missing_minutes, 0.0);
+        assert_eq!(metrics.waso_minutes, 8.0);
+        assert_eq!(metrics.terminal_wake_minutes, 5.0);
+        assert_eq!(metrics.awakenings, 2);
+        assert_eq!(metrics.mean_awakening_minutes, 4.0);
+        assert_eq!(metrics.longest_awakening_minutes, 5.0);
+        assert!((metrics.sleep_fragmentation_index - 2.0 / (37.0 / 60.0)).abs() < 1e-9);
+        assert!((metrics.sleep_efficiency - 37.0 / 60.0 * 100.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn missing_time_is_left_out_of_efficiency() {
+        let scoring = scored(&[(Sleep, 20), (NoData, 10), (Sleep, 20), (Wake, 10)]);
+        // The window runs 30 minutes past the last scored epoch
+        let metrics = sleep_period_metrics(&scoring, Some(window(0, 90))).unwrap();
+
+        assert_eq!(metrics.missing_minutes, 40.0);
+        assert_eq!(metrics.total_sleep_minutes, 40.0);
+        assert!((metrics.sleep_efficiency - 80.0).abs() < 1e-9);
+        // Missing data inside the sleep period is not an awakening
+        assert_eq!(metrics.waso_minutes, 0.0);
+        assert_eq!(metrics.awakenings, 0);
+        assert_eq!(metrics.terminal_wake_minutes, 40.0);
+    }
+
+    #[test]
+    fn a_window_without_sleep_has_no_onset() {
+        let scoring = scored(&[(Sleep, 10), (Wake, 30)]);
+        let metrics = sleep_period_metrics(&scoring, Some(window(10, 40))).unwrap();
+        assert_eq!(metrics.sleep_onset, None);
+        assert_eq!(metrics.sleep_onset_latency_minutes, None);
+        assert_eq!(metrics.sleep_efficiency, 0.0);
+        assert_eq!(metrics.sleep_fragmentation_index, 0.0);
+    }
+
+    #[test]
+    fn detection_merges_bouts_separated_by_up_to_an_hour_of_wake() {
+        let scoring = scored(&[(Sleep, 30), (Wake, 60), (Sleep, 30), (Wake, 61), (Sleep, 100)]);
+        // 30 + 60 + 30 = 120 minutes beats the separate 100-minute bout
+        assert_eq!(detect_rest_interval(&scoring), Some(window(0, 120)));
+
+        let metrics = sleep_period_metrics(&scoring, None).unwrap();
+        assert!(metrics.window_detected);
+        assert_eq!(metrics.sleep_onset_latency_minutes, Some(0.0));
+        assert_eq!(metrics.terminal_wake_minutes, 0.0);
+        assert_eq!(metrics.waso_minutes, 60.0);
+        assert_eq!(metrics.awakenings, 1);
+        assert!((metrics.sleep_efficiency - 50.0).abs() < 1e-9);
+
+        // Past an hour the bouts stay apart and the longer one wins
+        let scoring = scored(&[(Sleep, 30), (Wake, 61), (Sleep, 60), (Wake, 90), (Sleep, 50)]);
+        assert_eq!(detect_rest_interval(&scoring), Some(window(91, 151)));
+    }
+
+    #[test]
+    fn detection_does_not_bridge_missing_data() {
+        let scoring = scored(&[(Sleep, 30), (NoData, 5), (Sleep, 40)]);
+        assert_eq!(detect_rest_interval(&scoring), Some(window(35, 75)));
+
+        // Wake next to the gap does not reconnect the bouts either
+        let scoring = scored(&[(Sleep, 50), (Wake, 5), (NoData, 5), (Wake, 5), (Sleep, 40)]);
+        assert_eq!(detect_rest_interval(&scoring), Some(window(0, 50)));
+    }
+
+    #[test]
+    fn rejects_scorings_it_cannot_measure() {
+        let awake = scored(&[(Wake, 30), (NoData, 10)]);
+        assert_eq!(detect_rest_interval(&awake), None);
+        assert!(sleep_period_metrics(&awake, None).is_err());
+
+        let scoring = scored(&[(Sleep, 30)]);
+        assert!(sleep_period_metrics(&scoring, Some(window(20, 20))).is_err());
+        assert!(sleep_period_metrics(&scoring, Some(window(40, 60))).is_err());
+
+        let mut unordered = scoring.clone();
+        unordered.epoch_starts.swap(3, 4);
+        assert!(unordered.validate().is_err());
+        let mut short = scoring.clone();
+        short.labels.pop();
+        assert!(short.validate().is_err());
+        assert!(ScoredEpochs { epoch_seconds: 0, ..scoring }.validate().is_err());
+    }
+}
@@ -0,0 +1,397 @@
+// Sleep Regularity Index (Phillips et al. 2017): how often the sleep/wake
+// state matches 24 hours later, with a per-day breakdown and the spread of
+// bed, wake and midsleep times across days.
+use chrono::{DateTime, Duration, FixedOffset, NaiveDate, NaiveTime, Timelike};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::Range;
+
+use crate::settings::SettingsState;
+use crate::sleep_metrics::{self, ScoredEpochs};
+use crate::sleep_scoring::EpochLabel;
+use crate::timezone;
+
+const DAY_MS: i64 = 86_400_000;
+/// Sleep records are laid onto a grid of this resolution.
+const RECORD_EPOCH_SECONDS: u32 = 60;
+/// About eight weeks of 30-second epochs.
+const MAX_EPOCHS: usize = 200_000;
+/// Days with less of their 24 hours observed count as missing.
+const MIN_DAY_COVERAGE: f64 = 0.8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SleepState {
+    Asleep,
+    InBed,
+    Awake,
+}
+
+/// One HealthKit sleep analysis sample (epoch ms).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SleepRecord {
+    pub start: i64,
+    pub end: i64,
+    pub state: SleepState,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SleepDayStatus {
+    Observed,
+    /// Observed, but no sleep was scored or recorded.
+    NoSleep,
+    /// Too little of the day was observed; left out of every statistic.
+    Missing,
+}
+
+/// A noon-to-noon local day, named after the date of its evening.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SleepDay {
+    pub date: NaiveDate,
+    pub status: SleepDayStatus,
+    /// Fraction of the day with data.
+    pub coverage: f64,
+    /// Regularity between this day and the next, when both are usable.
+    pub sleep_regularity_index: Option<f64>,
+    pub sleep_minutes: f64,
+    /// Main sleep period of the day.
+    pub bed_time: Option<DateTime<FixedOffset>>,
+    pub wake_time: Option<DateTime<FixedOffset>>,
+    pub midsleep: Option<DateTime<FixedOffset>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SleepRegularity {
+    /// -100 (always opposite) to 100 (identical every day).
+    pub sleep_regularity_index: f64,
+    pub days: Vec<SleepDay>,
+    pub observed_days: usize,
+    pub missing_days: usize,
+    /// Epoch pairs 24 hours apart that entered the index.
+    pub compared_epochs: usize,
+    /// Spread of local clock times across days; `None` with fewer than two.
+    pub bed_time_sd_minutes: Option<f64>,
+    pub wake_time_sd_minutes: Option<f64>,
+    pub midsleep_sd_minutes: Option<f64>,
+}
+
+fn noon() -> NaiveTime {
+    NaiveTime::from_hms_opt(12, 0, 0).unwrap_or_default()
+}
+
+/// Date of the noon-to-noon local day containing `timestamp`.
+fn sleep_day(timestamp: i64, zone: Tz) -> NaiveDate {
+    let local = DateTime::from_timestamp_millis(timestamp).unwrap_or_default().with_timezone(&zone);
+    (local.naive_local() - Duration::hours(12)).date()
+}
+
+fn day_start(date: NaiveDate, zone: Tz) -> i64 {
+    timezone::resolve_on(zone, date, noon()).timestamp_millis()
+}
+
+/// Minutes after local noon, so evening and morning times sit on one line.
+pub fn minutes_after_noon(timestamp: i64, zone: Tz) -> f64 {
+    let local = DateTime::from_timestamp_millis(timestamp).unwrap_or_default().with_timezone(&zone);
+    let minutes = local.hour() as f64 * 60.0 + local.minute() as f64 + local.second() as f64 / 60.0;
+    (minutes - 720.0).rem_euclid(1440.0)
+}
+
+fn standard_deviation(values: &[f64]) -> Option<f64> {
+    if values.len() < 2 {
+        return None;
+    }
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    Some((values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (values.len() - 1) as f64).sqrt())
+}
+
+/// Lays sleep records onto a minute grid from noon to noon. Days without an
+/// asleep or in-bed record are missing, whatever awake records they hold;
+/// time between records on recorded days counts as wake.
+pub fn records_to_epochs(records: &[SleepRecord], zone: Tz) -> Result<ScoredEpochs, String> {
+    if let Some(record) = records.iter().find(|r| r.end <= r.start) {
+        return Err(format!("Sleep record starting at {} does not end after it starts", record.start));
+    }
+    let (Some(first), Some(last)) = (records.iter().map(|r| r.start).min(), records.iter().map(|r| r.end).max()) else {
+        return Err("No sleep records to analyse".to_string());
+    };
+    let epoch_ms = RECORD_EPOCH_SECONDS as i64 * 1000;
+    let origin = day_start(sleep_day(first, zone), zone);
+    let end = day_start(sleep_day(last - 1, zone) + Duration::days(1), zone);
+    let count = ((end - origin) / epoch_ms) as usize;
+    if count > MAX_EPOCHS {
+        return Err(format!("Sleep records span {} epochs, more than {}", count, MAX_EPOCHS));
+    }
+
+    let epoch_starts: Vec<i64> = (0..count).map(|i| origin + i as i64 * epoch_ms).collect();
+    let mut labels = vec![EpochLabel::Wake; count];
+    let mut recorded_days = BTreeSet::new();
+    for record in records {
+        if record.state != SleepState::Awake {
+            recorded_days.insert(sleep_day(record.start, zone));
+            recorded_days.insert(sleep_day(record.end - 1, zone));
+        }
+        if record.state == SleepState::Asleep {
+            let from = ((record.start - origin).max(0) + epoch_ms - 1) / epoch_ms;
+            let to = (((record.end - origin) + epoch_ms - 1) / epoch_ms).min(count as i64);
+            for label in &mut labels[from as usize..to.max(from) as usize] {
+                *label = EpochLabel::Sleep;
+            }
+        }
+    }
+    for (label, &start) in labels.iter_mut().zip(&epoch_starts) {
+        if !recorded_days.contains(&sleep_day(start, zone)) {
+            *label = EpochLabel::NoData;
+        }
+    }
+    Ok(ScoredEpochs { epoch_seconds: RECORD_EPOCH_SECONDS, epoch_starts, labels })
+}
+
+/// Splits the scoring into noon-to-noon days with their main sleep period,
+/// alongside the epoch range each day covers.
+pub fn sleep_days(scoring: &ScoredEpochs, zone: Tz) -> Vec<(SleepDay, Range<usize>)> {
+    let epoch_ms = scoring.epoch_seconds as i64 * 1000;
+    let labels = &scoring.labels;
+    let mut ranges: BTreeMap<NaiveDate, Range<usize>> = BTreeMap::new();
+    for (i, &start) in scoring.epoch_starts.iter().enumerate() {
+        ranges.entry(sleep_day(start, zone)).or_insert(i..i).end = i + 1;
+    }
+
+    let local = |timestamp: i64| {
+        DateTime::from_timestamp_millis(timestamp).map(|time| time.with_timezone(&zone).fixed_offset())
+    };
+    ranges
+        .into_iter()
+        .map(|(date, range)| {
+            let expected = (day_start(date + Duration::days(1), zone) - day_start(date, zone)) / epoch_ms;
+            let observed = labels[range.clone()].iter().filter(|&&l| l != EpochLabel::NoData).count();
+            let coverage = (observed as f64 / expected as f64).min(1.0);
+            let sleep_epochs = labels[range.clone()].iter().filter(|&&l| l == EpochLabel::Sleep).count();
+            let status = if coverage < MIN_DAY_COVERAGE {
+                SleepDayStatus::Missing
+            } else if sleep_epochs == 0 {
+                SleepDayStatus::NoSleep
+            } else {
+                SleepDayStatus::Observed
+            };
+
+            let main_sleep = if status == SleepDayStatus::Observed {
+                let day = ScoredEpochs {
+                    epoch_seconds: scoring.epoch_seconds,
+                    epoch_starts: scoring.epoch_starts[range.clone()].to_vec(),
+                    labels: labels[range.clone()].to_vec(),
+                };
+                sleep_metrics::detect_rest_interval(&day)
+            } else {
+                None
+            };
+            let day = SleepDay {
+                date,
+                status,
+                coverage,
+                sleep_regularity_index: None,
+                sleep_minutes: sleep_epochs as f64 * scoring.epoch_seconds as f64 / 60.0,
+                bed_time: main_sleep.and_then(|w| local(w.bed_time)),
+                wake_time: main_sleep.and_then(|w| local(w.rise_time)),
+                midsleep: main_sleep.and_then(|w| local(w.bed_time + (w.rise_time - w.bed_time) / 2)),
+            };
+            (day, range)
+        })
+        .collect()
+}
+
+pub fn sleep_regularity(scoring: &ScoredEpochs, zone: Tz) -> Result<SleepRegularity, String> {
+    let epoch_ms = scoring.epoch_seconds as i64 * 1000;
+    if DAY_MS % epoch_ms != 0 {
+        return Err(format!("{} s epochs do not divide a day", scoring.epoch_seconds));
+    }
+    if scoring.epoch_starts.windows(2).any(|pair| pair[1] - pair[0] != epoch_ms) {
+        return Err("Sleep regularity needs contiguous epochs; mark gaps as no data".to_string());
+    }
+    let lag = (DAY_MS / epoch_ms) as usize;
+    let labels = &scoring.labels;
+
+    let (mut days, ranges): (Vec<SleepDay>, Vec<Range<usize>>) = sleep_days(scoring, zone).into_iter().unzip();
+    let mut usable = vec![false; labels.len()];
+    let mut day_of = vec![0usize; labels.len()];
+    for (d, range) in ranges.iter().enumerate() {
+        for i in range.clone() {
+            usable[i] = days[d].status != SleepDayStatus::Missing && labels[i] != EpochLabel::NoData;
+            day_of[i] = d;
+        }
+    }
+
+    // Compare every usable epoch with the one exactly 24 hours later
+    let mut agreements = vec![(0usize, 0usize); days.len()];
+    for i in 0..labels.len().saturating_sub(lag) {
+        if usable[i] && usable[i + lag] {
+            let entry = &mut agreements[day_of[i]];
+            entry.1 += 1;
+            if (labels[i] == EpochLabel::Sleep) == (labels[i + lag] == EpochLabel::Sleep) {
+                entry.0 += 1;
+            }
+        }
+    }
+    let index = |(same, total): (usize, usize)| 200.0 * same as f64 / total as f64 - 100.0;
+    for (day, &(same, total)) in days.iter_mut().zip(&agreements) {
+        if total > 0 {
+            day.sleep_regularity_index = Some(index((same, total)));
+        }
+    }
+    let (same, total) = agreements.iter().fold((0, 0), |acc, &(s, t)| (acc.0 + s, acc.1 + t));
+    if total == 0 {
+        return Err("Sleep regularity needs at least two consecutive observed days".to_string());
+    }
+
+    let spread = |pick: fn(&SleepDay) -> Option<DateTime<FixedOffset>>| {
+        let minutes: Vec<f64> =
+            days.iter().filter_map(pick).map(|time| minutes_after_noon(time.timestamp_millis(), zone)).collect();
+        standard_deviation(&minutes)
+    };
+
+    Ok(SleepRegularity {
+        sleep_regularity_index: index((same, total)),
+        observed_days: days.iter().filter(|d| d.status != SleepDayStatus::Missing).count(),
+        missing_days: days.iter().filter(|d| d.status == SleepDayStatus::Missing).count(),
+        compared_epochs: total,
+        bed_time_sd_minutes: spread(|d| d.bed_time),
+        wake_time_sd_minutes: spread(|d| d.wake_time),
+        midsleep_sd_minutes: spread(|d| d.midsleep),
+        days,
+    })
+}
+
+/// Takes either scored epochs spanning several days or HealthKit sleep records.
+#[tauri::command]
+pub async fn calculate_sleep_regularity(
+    settings_state: tauri::State<'_, SettingsState>,
+    scoring: Option<ScoredEpochs>,
+    sleep_records: Option<Vec<SleepRecord>>,
+) -> Result<SleepRegularity, String> {
+    let zone = settings_state
+        .lock()
+        .map_err(|e| format!("Failed to lock settings: {}", e))?
+        .current
+        .zone();
+    if let Some(scoring) = &scoring {
+        scoring.validate()?;
+    }
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let scoring = match (scoring, sleep_records) {
+            (Some(scoring), None) => scoring,
+            (None, Some(records)) => records_to_epochs(&records, zone)?,
+            _ => return Err("Provide either scored epochs or sleep records".to_string()),
+        };
+        sleep_regularity(&scoring, zone)
+    })
+    .await
+    .map_err(|e| format!("Failed to calculate sleep regularity: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2026-03-02 00:00 UTC.
+    const START: i64 = 1_772_409_600_000;
+    const HOUR: i64 = 3_600_000;
+
+    fn asleep(day: i64, from_hour: i64, to_hour: i64) -> SleepRecord {
+        let midnight = START + day * DAY_MS;
+        SleepRecord { start: midnight + from_hour * HOUR, end: midnight + to_hour * HOUR, state: SleepState::Asleep }
+    }
+
+    /// Asleep 23:00-07:00 from the evening of 2 March for `nights` nights.
+    fn nights(nights: i64) -> Vec<SleepRecord> {
+        (0..nights).map(|day| asleep(day, 23, 31)).collect()
+    }
+
+    fn regularity(records: &[SleepRecord]) -> SleepRegularity {
+        sleep_regularity(&records_to_epochs(records, Tz::UTC).unwrap(), Tz::UTC).unwrap()
+    }
+
+    #[test]
+    fn identical_days_score_100() {
+        let result = regularity(&nights(5));
+        assert_eq!(result.sleep_regularity_index, 100.0);
+        assert_eq!(result.observed_days, 5);
+        assert_eq!(result.missing_days, 0);
+        assert_eq!(result.compared_epochs, 4 * 1440);
+        assert!(result.days[..4].iter().all(|d| d.sleep_regularity_index == Some(100.0)));
+        assert_eq!(result.days[4].sleep_regularity_index, None);
+        assert_eq!(result.days[0].sleep_minutes, 480.0);
+        assert_eq!(result.bed_time_sd_minutes, Some(0.0));
+        assert_eq!(result.midsleep_sd_minutes, Some(0.0));
+        let midsleep = result.days[0].midsleep.unwrap();
+        assert_eq!(midsleep.timestamp_millis(), START + 27 * HOUR);
+    }
+
+    #[test]
+    fn inverted_days_score_minus_100() {
+        // Asleep for the first half of one noon-to-noon day, the second half of the next
+        let records: Vec<SleepRecord> =
+            (0..4).map(|day| if day % 2 == 0 { asleep(day, 12, 24) } else { asleep(day, 24, 36) }).collect();
+        let result = regularity(&records);
+        assert_eq!(result.sleep_regularity_index, -100.0);
+        assert_eq!(result.compared_epochs, 3 * 1440);
+    }
+
+    #[test]
+    fn a_shifted_night_lowers_both_neighbouring_pairs() {
+        let mut records = nights(4);
+        records[2] = asleep(2, 25, 33);
+        let result = regularity(&records);
+        // Four hours of 24 disagree with each neighbour of the shifted night
+        let shifted = 200.0 * 20.0 / 24.0 - 100.0;
+        assert_eq!(result.days[0].sleep_regularity_index, Some(100.0));
+        assert!((result.days[1].sleep_regularity_index.unwrap() - shifted).abs() < 1e-9);
+        assert!((result.days[2].sleep_regularity_index.unwrap() - shifted).abs() < 1e-9);
+        assert!((result.sleep_regularity_index - (100.0 + 2.0 * shifted) / 3.0).abs() < 1e-9);
+        // Bedtimes 23:00, 23:00, 01:00 and 23:00 have a 60-minute sample sd
+        assert!((result.bed_time_sd_minutes.unwrap() - 60.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn a_night_without_sleep_records_is_missing() {
+        let mut records = nights(5);
+        // Only an awake record on the evening of 4 March
+        records[2] = SleepRecord { state: SleepState::Awake, ..asleep(2, 15, 16) };
+        let scoring = records_to_epochs(&records, Tz::UTC).unwrap();
+        let day = 1440;
+        assert!(scoring.labels[2 * day..3 * day].iter().all(|&l| l == EpochLabel::NoData));
+
+        let result = sleep_regularity(&scoring, Tz::UTC).unwrap();
+        assert_eq!(result.days[2].status, SleepDayStatus::Missing);
+        assert_eq!(result.days[2].coverage, 0.0);
+        assert_eq!(result.missing_days, 1);
+        assert_eq!(result.observed_days, 4);
+        assert_eq!(result.sleep_regularity_index, 100.0);
+        assert_eq!(result.compared_epochs, 2 * 1440);
+        assert_eq!(result.days[1].sleep_regularity_index, None);
+    }
+
+    #[test]
+    fn in_bed_without_sleep_is_observed() {
+        let mut records = nights(3);
+        records[1] = SleepRecord { state: SleepState::InBed, ..records[1].clone() };
+        let result = regularity(&records);
+        assert_eq!(result.days[1].status, SleepDayStatus::NoSleep);
+        assert_eq!(result.days[1].bed_time, None);
+        // Each pair disagrees for the eight hours of one night
+        assert!((result.sleep_regularity_index - (200.0 * 16.0 / 24.0 - 100.0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn rejects_unusable_input() {
+        assert!(records_to_epochs(&[], Tz::UTC).is_err());
+        assert!(records_to_epochs(&[asleep(0, 7, 7)], Tz::UTC).is_err());
+        // One night has nothing to compare with
+        let single = records_to_epochs(&nights(1), Tz::UTC).unwrap();
+        assert!(sleep_regularity(&single, Tz::UTC).is_err());
+        let mut gapped = records_to_epochs(&nights(2), Tz::UTC).unwrap();
+        gapped.epoch_starts[5] += 1;
+        assert!(sleep_regularity(&gapped, Tz::UTC).is_err());
+    }
+}
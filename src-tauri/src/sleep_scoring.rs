@@ -0,0 +1,460 @@
+// Sleep/wake scoring of actigraphy counts with published algorithms
+// (Cole-Kripke 1992, Sadeh 1994, Oakley 1997) and Webster's rescoring rules.
+use serde::{Deserialize, Serialize};
+
+use crate::timeseries::TimeSeries;
+
+/// About eight weeks of 30-second epochs.
+const MAX_EPOCHS: usize = 200_000;
+/// ActiLife's preprocessing for Cole-Kripke and Sadeh caps counts here.
+const COUNT_CAP: f64 = 300.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SleepScoringAlgorithm {
+    /// 60-second epochs, ActiGraph coefficients.
+    ColeKripke,
+    /// 60-second epochs.
+    Sadeh,
+    /// Actiware's weighting; 15, 30, 60 or 120-second epochs.
+    Oakley,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EpochLabel {
+    Sleep,
+    Wake,
+    /// No samples fell in the epoch.
+    NoData,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SleepScoringConfig {
+    pub algorithm: SleepScoringAlgorithm,
+    pub epoch_seconds: u32,
+    /// Apply Webster's rules, which turn brief sleep inside long wake into wake.
+    pub rescore: bool,
+    /// Oakley wake threshold on weighted counts; Actiware uses 20 (low),
+    /// 40 (medium) or 80 (high).
+    pub oakley_threshold: f64,
+}
+
+impl Default for SleepScoringConfig {
+    fn default() -> Self {
+        Self {
+            algorithm: SleepScoringAlgorithm::ColeKripke,
+            epoch_seconds: 60,
+            rescore: true,
+            oakley_threshold: 40.0,
+        }
+    }
+}
+
+impl SleepScoringConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        let supported: &[u32] = match self.algorithm {
+            SleepScoringAlgorithm::ColeKripke | SleepScoringAlgorithm::Sadeh => &[60],
+            SleepScoringAlgorithm::Oakley => &[15, 30, 60, 120],
+        };
+        if !supported.contains(&self.epoch_seconds) {
+            return Err(format!(
+                "{:?} scoring supports epochs of {:?} seconds, not {}",
+                self.algorithm, supported, self.epoch_seconds
+            ));
+        }
+        if self.oakley_threshold <= 0.0 {
+            return Err("Oakley threshold must be positive".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SleepScoring {
+    pub algorithm: SleepScoringAlgorithm,
+    pub epoch_seconds: u32,
+    /// Start of each epoch (epoch ms), aligned to multiples of the epoch length.
+    pub epoch_starts: Vec<i64>,
+    /// Summed activity counts per epoch.
+    pub counts: Vec<f64>,
+    /// The algorithm's score before thresholding, `None` for empty epochs.
+    pub scores: Vec<Option<f64>>,
+    /// Final labels, after rescoring when enabled.
+    pub labels: Vec<EpochLabel>,
+    /// Epochs changed from sleep to wake by rescoring.
+    pub rescored_epochs: usize,
+    pub sleep_epochs: usize,
+    pub wake_epochs: usize,
+}
+
+/// Sums the counts falling in each epoch; `None` where no sample did.
+fn epoch_counts(activity: &TimeSeries, epoch_ms: i64) -> Result<(Vec<i64>, Vec<Option<f64>>), String> {
+    let origin = activity.timestamps[0].div_euclid(epoch_ms) * epoch_ms;
+    let last = activity.timestamps[activity.len() - 1];
+    let count = ((last - origin) / epoch_ms) as usize + 1;
+    if count > MAX_EPOCHS {
+        return Err(format!("Recording spans {} epochs, more than {}", count, MAX_EPOCHS));
+    }
+    let mut counts = vec![None; count];
+    for (&timestamp, &value) in activity.timestamps.iter().zip(&activity.values) {
+        let slot = &mut counts[((timestamp - origin) / epoch_ms) as usize];
+        *slot = Some(slot.unwrap_or(0.0) + value);
+    }
+    let starts = (0..count).map(|i| origin + i as i64 * epoch_ms).collect();
+    Ok((starts, counts))
+}
+
+/// Weighted sum of `counts` around `i`, `weights[k]` applying at offset
+/// `k - before`. Epochs past either end contribute nothing.
+fn weighted(counts: &[f64], i: usize, before: usize, weights: &[f64]) -> f64 {
+    weights
+        .iter()
+        .enumerate()
+        .filter_map(|(k, w)| (i + k).checked_sub(before).and_then(|j| counts.get(j)).map(|c| w * c))
+        .sum()
+}
+
+/// Score per epoch and whether that score means sleep.
+fn score(algorithm: SleepScoringAlgorithm, epoch_seconds: u32, threshold: f64, counts: &[f64]) -> Vec<(f64, bool)> {
+    match algorithm {
+        SleepScoringAlgorithm::ColeKripke => {
+            const WEIGHTS: [f64; 7] = [106.0, 54.0, 58.0, 76.0, 230.0, 74.0, 67.0];
+            let scaled: Vec<f64> = counts.iter().map(|c| (c / 100.0).min(COUNT_CAP)).collect();
+            (0..counts.len())
+                .map(|i| {
+                    let d = 0.001 * weighted(&scaled, i, 4, &WEIGHTS);
+                    (d, d < 1.0)
+                })
+                .collect()
+        }
+        SleepScoringAlgorithm::Sadeh => {
+            let capped: Vec<f64> = counts.iter().map(|c| c.min(COUNT_CAP)).collect();
+            let n = capped.len();
+            (0..n)
+                .map(|i| {
+                    // Mean and count of 50-100 epochs over an 11-minute centred window
+                    let window = &capped[i.saturating_sub(5)..(i + 6).min(n)];
+                    let mean = window.iter().sum::<f64>() / 11.0;
+                    let nat = window.iter().filter(|&&c| (50.0..100.0).contains(&c)).count() as f64;
+                    // Standard deviation over the current and previous five epochs
+                    let trailing = &capped[i.saturating_sub(5)..=i];
+                    let trailing_mean = trailing.iter().sum::<f64>() / trailing.len() as f64;
+                    let sd = if trailing.len() > 1 {
+                        (trailing.iter().map(|c| (c - trailing_mean).powi(2)).sum::<f64>() / (trailing.len() - 1) as f64)
+                            .sqrt()
+                    } else {
+                        0.0
+                    };
+                    let ps = 7.601 - 0.065 * mean - 1.08 * nat - 0.056 * sd - 0.703 * (capped[i] + 1.0).ln();
+                    (ps, ps >= 0.0)
+                })
+                .collect()
+        }
+        SleepScoringAlgorithm::Oakley => {
+            // Actiware weights the two minutes either side of each epoch
+            let (before, weights): (usize, &[f64]) = match epoch_seconds {
+                15 => (8, &[0.04, 0.04, 0.04, 0.04, 0.2, 0.2, 0.2, 0.2, 4.0, 0.2, 0.2, 0.2, 0.2, 0.04, 0.04, 0.04, 0.04]),
+                30 => (4, &[0.04, 0.04, 0.2, 0.2, 2.0, 0.2, 0.2, 0.04, 0.04]),
+                60 => (2, &[0.04, 0.2, 1.0, 0.2, 0.04]),
+                _ => (1, &[0.12, 0.5, 0.12]),
+            };
+            (0..counts.len())
+                .map(|i| {
+                    let total = weighted(counts, i, before, weights);
+                    (total, total <= threshold)
+                })
+                .collect()
+        }
+    }
+}
+
+/// Consecutive equal labels as (label, first index, length).
+pub fn label_runs(labels: &[EpochLabel]) -> Vec<(EpochLabel, usize, usize)> {
+    let mut runs: Vec<(EpochLabel, usize, usize)> = Vec::new();
+    for (i, &label) in labels.iter().enumerate() {
+        match runs.last_mut() {
+            Some((last, _, len)) if *last == label => *len += 1,
+            _ => runs.push((label, i, 1)),
+        }
+    }
+    runs
+}
+
+/// Webster et al. (1982): after 4, 10 or 15 minutes of wake the next 1, 3 or 4
+/// minutes of sleep become wake, and sleep of at most 6 (or 10) minutes between
+/// 10 (or 20) minutes of wake on both sides becomes wake.
+fn rescore(labels: &mut [EpochLabel], epoch_seconds: u32) -> usize {
+    let epochs = |minutes: u32| (minutes * 60).div_ceil(epoch_seconds) as usize;
+    let mut changed = 0;
+    let mut wake = |labels: &mut [EpochLabel], range: std::ops::Range<usize>| {
+        for label in &mut labels[range] {
+            if *label == EpochLabel::Sleep {
+                *label = EpochLabel::Wake;
+                changed += 1;
+            }
+        }
+    };
+
+    let rules = [(15, 4), (10, 3), (4, 1)];
+    let original = label_runs(labels);
+    for pair in original.windows(2) {
+        if let [(EpochLabel::Wake, _, wake_len), (EpochLabel::Sleep, start, sleep_len)] = *pair {
+            if let Some(&(_, minutes)) = rules.iter().find(|&&(after, _)| wake_len >= epochs(after)) {
+                wake(labels, start..start + epochs(minutes).min(sleep_len));
+            }
+        }
+    }
+
+    let bridged = label_runs(labels);
+    for triple in bridged.windows(3) {
+        if let [(EpochLabel::Wake, _, before), (EpochLabel::Sleep, start, len), (EpochLabel::Wake, _, after)] = *triple {
+            let surrounded = |minutes| before >= epochs(minutes) && after >= epochs(minutes);
+            if (len <= epochs(6) && surrounded(10)) || (len <= epochs(10) && surrounded(20)) {
+                wake(labels, start..start + len);
+            }
+        }
+    }
+    changed
+}
+
+pub fn score_sleep(activity: &TimeSeries, config: &SleepScoringConfig) -> Result<SleepScoring, String> {
+    if activity.is_empty() {
+        return Err("No activity to score".to_string());
+    }
+    if let Some(interval) = activity.median_interval_minutes() {
+        if interval * 60.0 > config.epoch_seconds as f64 {
+            return Err(format!(
+                "Samples arrive every {} s, too coarse for {} s epochs",
+                interval * 60.0,
+                config.epoch_seconds
+            ));
+        }
+    }
+
+    let (epoch_starts, present) = epoch_counts(activity, config.epoch_seconds as i64 * 1000)?;
+    let counts: Vec<f64> = present.iter().map(|c| c.unwrap_or(0.0)).collect();
+    let raw = score(config.algorithm, config.epoch_seconds, config.oakley_threshold, &counts);
+
+    let mut labels: Vec<EpochLabel> = raw
+        .iter()
+        .zip(&present)
+        .map(|(&(_, sleep), c)| match (c, sleep) {
+            (None, _) => EpochLabel::NoData,
+            (Some(_), true) => EpochLabel::Sleep,
+            (Some(_), false) => EpochLabel::Wake,
+        })
+        .collect();
+    let rescored_epochs = if config.rescore { rescore(&mut labels, config.epoch_seconds) } else { 0 };
+
+    Ok(SleepScoring {
+        algorithm: config.algorithm,
+        epoch_seconds: config.epoch_seconds,
+        epoch_starts,
+        scores: raw.iter().zip(&present).map(|(&(s, _), c)| c.map(|_| s)).collect(),
+        counts,
+        rescored_epochs,
+        sleep_epochs: labels.iter().filter(|&&l| l == EpochLabel::Sleep).count(),
+        wake_epochs: labels.iter().filter(|&&l| l == EpochLabel::Wake).count(),
+        labels,
+    })
+}
+
+/// Activity counts in, one sleep/wake label per epoch out.
+#[tauri::command]
+pub async fn score_sleep_wake(activity: TimeSeries, config: Option<SleepScoringConfig>) -> Result<SleepScoring, String> {
+    let config = config.unwrap_or_default();
+    config.validate()?;
+    activity.validate()?;
+
+    tauri::async_runtime::spawn_blocking(move || score_sleep(&activity, &config))
+        .await
+        .map_err(|e| format!("Failed to score sleep: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use EpochLabel::{Sleep, Wake};
+
+    /// 2026-03-02 00:00 UTC.
+    const START: i64 = 1_772_409_600_000;
+
+    fn minutes(counts: &[f64]) -> TimeSeries {
+        TimeSeries {
+            timestamps: (0..counts.len() as i64).map(|i| START + i * 60_000).collect(),
+            values: counts.to_vec(),
+        }
+    }
+
+    fn runs(spec: &[(EpochLabel, usize)]) -> Vec<EpochLabel> {
+        spec.iter().flat_map(|&(label, len)| std::iter::repeat_n(label, len)).collect()
+    }
+
+    fn scores(algorithm: SleepScoringAlgorithm, epoch_seconds: u32, counts: &[f64]) -> Vec<f64> {
+        score(algorithm, epoch_seconds, 40.0, counts).into_iter().map(|(s, _)| s).collect()
+    }
+
+    #[test]
+    fn cole_kripke_weights_the_surrounding_minutes() {
+        let mut counts = vec![0.0; 9];
+        counts[4] = 1000.0;
+        let d = scores(SleepScoringAlgorithm::ColeKripke, 60, &counts);
+        // 0.001 * weight * count / 100, looking four epochs back and two ahead
+        assert_eq!(d[1], 0.0);
+        assert!((d[2] - 0.67).abs() < 1e-9, "{}", d[2]);
+        assert!((d[3] - 0.74).abs() < 1e-9, "{}", d[3]);
+        assert!((d[4] - 2.3).abs() < 1e-9, "{}", d[4]);
+        assert!((d[5] - 0.76).abs() < 1e-9, "{}", d[5]);
+        assert!((d[8] - 1.06).abs() < 1e-9, "{}", d[8]);
+
+        let raw = score(SleepScoringAlgorithm::ColeKripke, 60, 40.0, &counts);
+        let sleep: Vec<bool> = raw.iter().map(|&(_, s)| s).collect();
+        assert_eq!(sleep, [true, true, true, true, false, true, true, true, false]);
+
+        // Scaled counts are capped at 300
+        let capped = scores(SleepScoringAlgorithm::ColeKripke, 60, &[0.0, 0.0, 0.0, 0.0, 1e6]);
+        assert!((capped[4] - 69.0).abs() < 1e-9, "{}", capped[4]);
+    }
+
+    #[test]
+    fn sadeh_scores_quiet_minutes_as_sleep() {
+        let quiet = scores(SleepScoringAlgorithm::Sadeh, 60, &[10.0; 21]);
+        // Mean 10, no 50-100 epochs, no variation
+        let expected = 7.601 - 0.065 * 10.0 - 0.703 * 11f64.ln();
+        assert!((quiet[10] - expected).abs() < 1e-9, "{}", quiet[10]);
+        assert!(quiet[10] >= 0.0);
+
+        let active = scores(SleepScoringAlgorithm::Sadeh, 60, &[60.0; 21]);
+        // Mean 60 and all eleven epochs between 50 and 100
+        let expected = 7.601 - 0.065 * 60.0 - 1.08 * 11.0 - 0.703 * 61f64.ln();
+        assert!((active[10] - expected).abs() < 1e-9, "{}", active[10]);
+        assert!(active[10] < 0.0);
+
+        // Counts above the cap score like 300
+        let capped = scores(SleepScoringAlgorithm::Sadeh, 60, &[5000.0; 21]);
+        let at_cap = scores(SleepScoringAlgorithm::Sadeh, 60, &[300.0; 21]);
+        assert_eq!(capped, at_cap);
+    }
+
+    #[test]
+    fn sadeh_includes_the_trailing_standard_deviation() {
+        let counts = [0.0, 0.0, 0.0, 0.0, 0.0, 30.0, 0.0, 0.0, 0.0, 0.0, 0.0];
+        let ps = scores(SleepScoringAlgorithm::Sadeh, 60, &counts);
+        // Window mean 30/11; trailing six epochs hold one 30, sd sqrt(150)
+        let expected = 7.601 - 0.065 * 30.0 / 11.0 - 0.056 * 150f64.sqrt() - 0.703 * 31f64.ln();
+        assert!((ps[5] - expected).abs() < 1e-9, "{}", ps[5]);
+    }
+
+    #[test]
+    fn oakley_weights_depend_on_the_epoch_length() {
+        let spike = [0.0, 0.0, 100.0, 0.0, 0.0];
+        let sixty = scores(SleepScoringAlgorithm::Oakley, 60, &spike);
+        let expected = [4.0, 20.0, 100.0, 20.0, 4.0];
+        for (got, want) in sixty.iter().zip(expected) {
+            assert!((got - want).abs() < 1e-9, "{:?}", sixty);
+        }
+        let sleep: Vec<bool> = score(SleepScoringAlgorithm::Oakley, 60, 40.0, &spike).iter().map(|&(_, s)| s).collect();
+        assert_eq!(sleep, [true, true, false, true, true]);
+
+        let thirty = scores(SleepScoringAlgorithm::Oakley, 30, &[0.0, 0.0, 0.0, 0.0, 10.0, 0.0, 0.0, 0.0, 0.0]);
+        let expected = [0.4, 0.4, 2.0, 2.0, 20.0, 2.0, 2.0, 0.4, 0.4];
+        for (got, want) in thirty.iter().zip(expected) {
+            assert!((got - want).abs() < 1e-9, "{:?}", thirty);
+        }
+
+        let two_minutes = scores(SleepScoringAlgorithm::Oakley, 120, &[0.0, 50.0, 0.0]);
+        let expected = [6.0, 25.0, 6.0];
+        for (got, want) in two_minutes.iter().zip(expected) {
+            assert!((got - want).abs() < 1e-9, "{:?}", two_minutes);
+        }
+    }
+
+    #[test]
+    fn webster_rescores_sleep_after_long_wake() {
+        // 4, 10 and 15 minutes of wake turn the next 1, 3 and 4 minutes of sleep to wake
+        for (wake, rescored) in [(3, 0), (4, 1), (9, 1), (10, 3), (14, 3), (15, 4)] {
+            let mut labels = runs(&[(Wake, wake), (Sleep, 8)]);
+            assert_eq!(rescore(&mut labels, 60), rescored, "after {} minutes", wake);
+            assert_eq!(labels, runs(&[(Wake, wake + rescored), (Sleep, 8 - rescored)]));
+        }
+
+        // Short sleep runs are rescored entirely
+        let mut labels = runs(&[(Wake, 15), (Sleep, 2), (Wake, 1)]);
+        assert_eq!(rescore(&mut labels, 60), 2);
+        assert_eq!(labels, runs(&[(Wake, 18)]));
+
+        // Rules are in minutes, so 30-second epochs double the counts
+        let mut labels = runs(&[(Wake, 8), (Sleep, 8)]);
+        assert_eq!(rescore(&mut labels, 30), 2);
+        assert_eq!(labels, runs(&[(Wake, 10), (Sleep, 6)]));
+    }
+
+    #[test]
+    fn webster_bridges_short_sleep_between_wake() {
+        // Six minutes between ten of wake: three go after the first wake, then
+        // the remaining three are bridged; the final run loses three too.
+        let mut labels = runs(&[(Sleep, 20), (Wake, 10), (Sleep, 6), (Wake, 10), (Sleep, 20)]);
+        assert_eq!(rescore(&mut labels, 60), 9);
+        assert_eq!(labels, runs(&[(Sleep, 20), (Wake, 29), (Sleep, 17)]));
+
+        // Twelve minutes between twenty of wake: four go after the first wake,
+        // leaving eight, bridged because both sides reach twenty minutes.
+        let mut labels = runs(&[(Sleep, 20), (Wake, 20), (Sleep, 12), (Wake, 20), (Sleep, 20)]);
+        assert_eq!(rescore(&mut labels, 60), 16);
+        assert_eq!(labels, runs(&[(Sleep, 20), (Wake, 56), (Sleep, 16)]));
+
+        // With fifteen minutes either side the leftover eight are too long to bridge
+        let mut labels = runs(&[(Sleep, 20), (Wake, 15), (Sleep, 12), (Wake, 15), (Sleep, 20)]);
+        assert_eq!(rescore(&mut labels, 60), 8);
+        assert_eq!(labels, runs(&[(Sleep, 20), (Wake, 19), (Sleep, 8), (Wake, 19), (Sleep, 16)]));
+    }
+
+    #[test]
+    fn empty_epochs_are_labelled_no_data() {
+        let mut activity = minutes(&[0.0; 10]);
+        activity.timestamps.extend((15..20).map(|i| START + i * 60_000));
+        activity.values.extend([0.0; 5]);
+        let config = SleepScoringConfig { rescore: false, ..Default::default() };
+        let scoring = score_sleep(&activity, &config).unwrap();
+
+        assert_eq!(scoring.labels.len(), 20);
+        assert_eq!(scoring.labels[10..15], [EpochLabel::NoData; 5]);
+        assert_eq!(scoring.scores[12], None);
+        assert_eq!(scoring.sleep_epochs, 15);
+        assert_eq!(scoring.wake_epochs, 0);
+        assert_eq!(scoring.epoch_starts[1] - scoring.epoch_starts[0], 60_000);
+    }
+
+    #[test]
+    fn rescoring_is_counted_in_the_result() {
+        let mut counts = vec![1000.0; 15];
+        counts.extend([0.0; 30]);
+        let scoring = score_sleep(&minutes(&counts), &SleepScoringConfig::default()).unwrap();
+        // At least the four quiet minutes after fifteen active ones are rescored
+        assert!(scoring.rescored_epochs >= 4, "{}", scoring.rescored_epochs);
+        assert_eq!(scoring.sleep_epochs + scoring.wake_epochs, 45);
+        let raw = SleepScoringConfig { rescore: false, ..Default::default() };
+        let unscored = score_sleep(&minutes(&counts), &raw).unwrap();
+        assert_eq!(scoring.sleep_epochs + scoring.rescored_epochs, unscored.sleep_epochs);
+    }
+
+    #[test]
+    fn rejects_coarse_sampling_and_unsupported_epochs() {
+        let coarse = TimeSeries {
+            timestamps: (0..10).map(|i| START + i * 120_000).collect(),
+            values: vec![0.0; 10],
+        };
+        assert!(score_sleep(&coarse, &SleepScoringConfig::default()).is_err());
+        assert!(score_sleep(&minutes(&[]), &SleepScoringConfig::default()).is_err());
+
+        let thirty = SleepScoringConfig { epoch_seconds: 30, ..Default::default() };
+        let sadeh = SleepScoringConfig { algorithm: SleepScoringAlgorithm::Sadeh, ..thirty.clone() };
+        assert!(sadeh.validate().is_err());
+        let oakley = SleepScoringConfig { algorithm: SleepScoringAlgorithm::Oakley, ..thirty };
+        assert!(oakley.validate().is_ok());
+        assert!(SleepScoringConfig { oakley_threshold: 0.0, ..oakley }.validate().is_err());
+    }
+}
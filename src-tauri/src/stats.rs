@@ -0,0 +1,210 @@
+// Distribution functions and small dense linear algebra for the analysis
+// modules, kept dependency-free.
+use std::f64::consts::PI;
+
+const LANCZOS: [f64; 9] = [
+    0.999_999_999_999_809_9,
+    676.520_368_121_885_1,
+    -1_259.139_216_722_402_8,
+    771.323_428_777_653_1,
+    -176.615_029_162_140_6,
+    12.507_343_278_686_905,
+    -0.138_571_095_265_720_12,
+    9.984_369_578_019_572e-6,
+    1.505_632_735_149_311_6e-7,
+];
+
+/// Natural log of the gamma function (Lanczos approximation, g = 7).
+pub fn ln_gamma(x: f64) -> f64 {
+    if x < 0.5 {
+        return (PI / (PI * x).sin()).ln() - ln_gamma(1.0 - x);
+    }
+    let x = x - 1.0;
+    let t = x + 7.5;
+    let series = LANCZOS[1..]
+        .iter()
+        .enumerate()
+        .fold(LANCZOS[0], |acc, (i, c)| acc + c / (x + i as f64 + 1.0));
+    0.5 * (2.0 * PI).ln() + (x + 0.5) * t.ln() - t + series.ln()
+}
+
+/// Regularized incomplete beta function I_x(a, b).
+pub fn incomplete_beta(x: f64, a: f64, b: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x >= 1.0 {
+        return 1.0;
+    }
+    let front = (ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1.0 - x).ln()).exp();
+    // The continued fraction converges quickly only below the mean.
+    if x < (a + 1.0) / (a + b + 2.0) {
+        front * beta_fraction(x, a, b) / a
+    } else {
+        1.0 - front * beta_fraction(1.0 - x, b, a) / b
+    }
+}
+
+/// Lentz's method for the incomplete beta continued fraction.
+fn beta_fraction(x: f64, a: f64, b: f64) -> f64 {
+    const TINY: f64 = 1e-300;
+    const EPSILON: f64 = 1e-14;
+    let mut c = 1.0;
+    let mut d = 1.0 - (a + b) * x / (a + 1.0);
+    if d.abs() < TINY {
+        d = TINY;
+    }
+    d = 1.0 / d;
+    let mut result = d;
+    for m in 1..=300 {
+        let m = m as f64;
+        let even = m * (b - m) * x / ((a + 2.0 * m - 1.0) * (a + 2.0 * m));
+        d = 1.0 + even * d;
+        d = if d.abs() < TINY { 1.0 / TINY } else { 1.0 / d };
+        c = 1.0 + even / c;
+        if c.abs() < TINY {
+            c = TINY;
+        }
+        result *= d * c;
+
+        let odd = -(a + m) * (a + b + m) * x / ((a + 2.0 * m) * (a + 2.0 * m + 1.0));
+        d = 1.0 + odd * d;
+        d = if d.abs() < TINY { 1.0 / TINY } else { 1.0 / d };
+        c = 1.0 + odd / c;
+        if c.abs() < TINY {
+            c = TINY;
+        }
+        let step = d * c;
+        result *= step;
+        if (step - 1.0).abs() < EPSILON {
+            break;
+        }
+    }
+    result
+}
+
+/// P(T <= t) for Student's t with `df` degrees of freedom.
+pub fn student_t_cdf(t: f64, df: f64) -> f64 {
+    let tail = 0.5 * incomplete_beta(df / (df + t * t), df / 2.0, 0.5);
+    if t >= 0.0 {
+        1.0 - tail
+    } else {
+        tail
+    }
+}
+
+/// The `p` quantile of Student's t, found by bisection.
+pub fn student_t_quantile(p: f64, df: f64) -> f64 {
+    let (mut lo, mut hi) = (-1e3, 1e3);
+    for _ in 0..200 {
+        let mid = 0.5 * (lo + hi);
+        if student_t_cdf(mid, df) < p {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    0.5 * (lo + hi)
+}
+
+/// P(F > f) for the F distribution with (`df1`, `df2`) degrees of freedom.
+pub fn f_survival(f: f64, df1: f64, df2: f64) -> f64 {
+    if f <= 0.0 {
+        return 1.0;
+    }
+    incomplete_beta(df2 / (df2 + df1 * f), df2 / 2.0, df1 / 2.0)
+}
+
+/// Inverse of a small square matrix by Gauss-Jordan elimination with partial
+/// pivoting; `None` when it is singular.
+pub fn invert(matrix: &[Vec<f64>]) -> Option<Vec<Vec<f64>>> {
+    let n = matrix.len();
+    let mut a: Vec<Vec<f64>> = matrix.to_vec();
+    let mut inverse: Vec<Vec<f64>> = (0..n).map(|i| (0..n).map(|j| if i == j { 1.0 } else { 0.0 }).collect()).collect();
+    let scale = matrix.iter().flatten().fold(0.0f64, |max, value| max.max(value.abs()));
+
+    for col in 0..n {
+        let pivot = (col..n).max_by(|&i, &j| a[i][col].abs().total_cmp(&a[j][col].abs()))?;
+        if a[pivot][col].abs() <= scale * 1e-12 {
+            return None;
+        }
+        a.swap(col, pivot);
+        inverse.swap(col, pivot);
+
+        let divisor = a[col][col];
+        for j in 0..n {
+            a[col][j] /= divisor;
+            inverse[col][j] /= divisor;
+        }
+        for row in 0..n {
+            if row != col {
+                let factor = a[row][col];
+                if factor != 0.0 {
+                    for j in 0..n {
+                        a[row][j] -= factor * a[col][j];
+                        inverse[row][j] -= factor * inverse[col][j];
+                    }
+                }
+            }
+        }
+    }
+    Some(inverse)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ln_gamma_matches_factorials() {
+        assert!((ln_gamma(5.0) - 24f64.ln()).abs() < 1e-12);
+        assert!((ln_gamma(0.5) - PI.sqrt().ln()).abs() < 1e-12);
+        assert!(ln_gamma(1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn incomplete_beta_matches_closed_forms() {
+        assert!((incomplete_beta(0.5, 2.0, 2.0) - 0.5).abs() < 1e-12);
+        // I_x(1, b) = 1 - (1 - x)^b
+        assert!((incomplete_beta(0.3, 1.0, 3.0) - (1.0 - 0.7f64.powi(3))).abs() < 1e-12);
+        assert_eq!(incomplete_beta(0.0, 2.0, 3.0), 0.0);
+        assert_eq!(incomplete_beta(1.0, 2.0, 3.0), 1.0);
+    }
+
+    #[test]
+    fn student_t_quantiles_match_tables() {
+        for (p, df, table) in [
+            (0.975, 1.0, 12.706),
+            (0.975, 10.0, 2.228),
+            (0.95, 30.0, 1.697),
+            (0.995, 5.0, 4.032),
+            (0.975, 1e6, 1.960),
+        ] {
+            assert!((student_t_quantile(p, df) - table).abs() < 1e-3, "t({}, {})", p, df);
+        }
+        assert!((student_t_quantile(0.025, 10.0) + 2.228).abs() < 1e-3);
+        assert!((student_t_cdf(0.0, 7.0) - 0.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn f_survival_matches_critical_values() {
+        for (f, df1, df2) in [(4.965, 1.0, 10.0), (3.885, 2.0, 12.0), (2.690, 4.0, 30.0)] {
+            assert!((f_survival(f, df1, df2) - 0.05).abs() < 5e-4, "F({}, {}, {})", f, df1, df2);
+        }
+        assert!((f_survival(10.044, 1.0, 10.0) - 0.01).abs() < 1e-4);
+        assert_eq!(f_survival(0.0, 2.0, 10.0), 1.0);
+    }
+
+    #[test]
+    fn invert_solves_and_detects_singular() {
+        let matrix = vec![vec![4.0, 7.0], vec![2.0, 6.0]];
+        let inverse = invert(&matrix).unwrap();
+        let expected = [[0.6, -0.7], [-0.2, 0.4]];
+        for i in 0..2 {
+            for j in 0..2 {
+                assert!((inverse[i][j] - expected[i][j]).abs() < 1e-12);
+            }
+        }
+        assert!(invert(&[vec![1.0, 2.0], vec![2.0, 4.0]]).is_none());
+    }
+}
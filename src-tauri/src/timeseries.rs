@@ -0,0 +1,89 @@
+// Timestamped samples passed to the analysis commands, mirroring the
+// frontend's `TimeSeries`. Timestamps are epoch milliseconds and the sampling
+// interval may be anything, including irregular.
+use chrono::{DateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TimeSeries {
+    pub timestamps: Vec<i64>,
+    pub values: Vec<f64>,
+}
+
+impl TimeSeries {
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Parallel arrays of equal length, timestamps in increasing order and
+    /// finite values.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.timestamps.len() != self.values.len() {
+            return Err(format!(
+                "Time series has {} timestamps but {} values",
+                self.timestamps.len(),
+                self.values.len()
+            ));
+        }
+        if let Some(i) = self.timestamps.windows(2).position(|pair| pair[1] <= pair[0]) {
+            return Err(format!("Time series timestamps must increase, see sample {}", i + 1));
+        }
+        if let Some(i) = self.values.iter().position(|value| !value.is_finite()) {
+            return Err(format!("Time series value {} is not a finite number", i));
+        }
+        Ok(())
+    }
+
+    pub fn span_minutes(&self) -> f64 {
+        match (self.timestamps.first(), self.timestamps.last()) {
+            (Some(first), Some(last)) => (last - first) as f64 / 60_000.0,
+            _ => 0.0,
+        }
+    }
+
+    /// Typical spacing between samples; the median so a few gaps do not skew it.
+    pub fn median_interval_minutes(&self) -> Option<f64> {
+        let mut gaps: Vec<i64> = self.timestamps.windows(2).map(|pair| pair[1] - pair[0]).collect();
+        if gaps.is_empty() {
+            return None;
+        }
+        gaps.sort_unstable();
+        Some(gaps[gaps.len() / 2] as f64 / 60_000.0)
+    }
+
+    /// Centred moving average over `window_minutes` of time rather than a fixed
+    /// number of samples, narrowing at the edges of the series.
+    pub fn smoothed(&self, window_minutes: f64) -> Vec<f64> {
+        let half = (window_minutes * 60_000.0 / 2.0) as i64;
+        if half <= 0 {
+            return self.values.clone();
+        }
+        let mut result = Vec::with_capacity(self.len());
+        let (mut lo, mut hi, mut sum) = (0, 0, 0.0);
+        for &t in &self.timestamps {
+            while hi < self.len() && self.timestamps[hi] <= t + half {
+                sum += self.values[hi];
+                hi += 1;
+            }
+            while self.timestamps[lo] < t - half {
+                sum -= self.values[lo];
+                lo += 1;
+            }
+            result.push(sum / (hi - lo) as f64);
+        }
+        result
+    }
+
+    /// Instant of sample `index` in `zone`.
+    pub fn time_at(&self, index: usize, zone: Tz) -> DateTime<Tz> {
+        Utc.timestamp_millis_opt(self.timestamps[index])
+            .single()
+            .unwrap_or_default()
+            .with_timezone(&zone)
+    }
+}
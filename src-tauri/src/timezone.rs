@@ -0,0 +1,142 @@
+// Explicit time zones for phase math. Every computation works on absolute
+// instants; wall-clock times are only resolved to instants here, with defined
+// rules for DST gaps and overlaps.
+use chrono::{DateTime, Duration, LocalResult, NaiveDate, NaiveDateTime, NaiveTime, Offset, TimeZone, Utc};
+use chrono_tz::Tz;
+
+/// The zone phase math runs in: `preferred` (an IANA name from settings) when
+/// set, otherwise the system zone. Re-resolved on every use so travelling
+/// picks up the new zone.
+pub fn active_zone(preferred: Option<&str>) -> Tz {
+    if let Some(zone) = preferred.and_then(|name| name.parse::<Tz>().ok()) {
+        return zone;
+    }
+    iana_time_zone::get_timezone()
+        .ok()
+        .and_then(|name| name.parse::<Tz>().ok())
+        .unwrap_or(Tz::UTC)
+}
+
+pub fn now_in(zone: Tz) -> DateTime<Tz> {
+    Utc::now().with_timezone(&zone)
+}
+
+/// Instant of a wall-clock time in `zone`. Repeated times (DST ending) use the
+/// first occurrence; skipped times (DST starting) are read with the offset in
+/// force before the gap, landing as far past the gap as they were into it.
+pub fn resolve(zone: Tz, naive: NaiveDateTime) -> DateTime<Tz> {
+    match zone.from_local_datetime(&naive) {
+        LocalResult::Single(time) => time,
+        LocalResult::Ambiguous(earliest, _) => earliest,
+        LocalResult::None => {
+            let before = zone
+                .from_local_datetime(&(naive - Duration::hours(3)))
+                .earliest()
+                .map(|time| time.offset().fix().local_minus_utc())
+                .unwrap_or(0);
+            zone.from_utc_datetime(&(naive - Duration::seconds(before as i64)))
+        }
+    }
+}
+
+pub fn resolve_on(zone: Tz, date: NaiveDate, time: NaiveTime) -> DateTime<Tz> {
+    resolve(zone, date.and_time(time))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::anchor::{AnchorMode, CycleAnchor};
+    use crate::ultradian::UltradianClock;
+    use chrono_tz::{America::New_York, Australia::Lord_Howe, Europe::Berlin};
+
+    fn wall(zone: Tz, y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Tz> {
+        resolve(zone, NaiveDate::from_ymd_opt(y, m, d).unwrap().and_hms_opt(h, min, 0).unwrap())
+    }
+
+    fn utc(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, h, min, 0).unwrap()
+    }
+
+    #[test]
+    fn skipped_time_lands_after_gap() {
+        // New York springs forward at 02:00 EST -> 03:00 EDT on 2026-03-08.
+        let time = wall(New_York, 2026, 3, 8, 2, 30);
+        assert_eq!(time.with_timezone(&Utc), utc(2026, 3, 8, 7, 30));
+        assert_eq!(time.format("%H:%M %Z").to_string(), "03:30 EDT");
+    }
+
+    #[test]
+    fn repeated_time_uses_first_occurrence() {
+        // New York falls back at 02:00 EDT -> 01:00 EST on 2026-11-01.
+        let time = wall(New_York, 2026, 11, 1, 1, 30);
+        assert_eq!(time.with_timezone(&Utc), utc(2026, 11, 1, 5, 30));
+    }
+
+    #[test]
+    fn half_hour_shifts_resolve() {
+        // Lord Howe Island moves by 30 minutes, 02:00 -> 02:30 on 2026-10-04.
+        let time = wall(Lord_Howe, 2026, 10, 4, 2, 15);
+        assert_eq!(time.format("%H:%M").to_string(), "02:45");
+    }
+
+    #[test]
+    fn cycles_stay_continuous_across_spring_forward() {
+        let clock = UltradianClock::default();
+        let anchor = CycleAnchor::new(AnchorMode::Midnight, NaiveTime::MIN);
+
+        // 01:59 EST and 03:00 EDT are one minute apart.
+        let before = utc(2026, 3, 8, 6, 59).with_timezone(&New_York);
+        let after = utc(2026, 3, 8, 7, 0).with_timezone(&New_York);
+        assert_eq!(anchor.anchor_for(before), anchor.anchor_for(after));
+
+        let first = clock.reading_at(before, anchor.anchor_for(before));
+        let second = clock.reading_at(after, anchor.anchor_for(after));
+        assert!((second.cycle_position - first.cycle_position - 1.0).abs() < 1e-9);
+
+        // Only two real hours have passed since midnight at 03:00 EDT.
+        assert_eq!(second.cycle_number, 2);
+        assert!((second.cycle_position - 30.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn cycles_stay_continuous_across_fall_back() {
+        let clock = UltradianClock::default();
+        let anchor = CycleAnchor::new(AnchorMode::Midnight, NaiveTime::MIN);
+
+        // 02:00 EST is three real hours after midnight EDT, with 01:00-02:00 lived twice.
+        let after_repeat = utc(2026, 11, 1, 7, 0).with_timezone(&New_York);
+        assert_eq!(after_repeat.format("%H:%M %Z").to_string(), "02:00 EST");
+        let reading = clock.reading_at(after_repeat, anchor.anchor_for(after_repeat));
+        assert_eq!(reading.cycle_number, 3);
+        assert!(reading.cycle_position.abs() < 1e-9);
+    }
+
+    #[test]
+    fn wake_time_in_gap_moves_past_it() {
+        let anchor = CycleAnchor::new(AnchorMode::WakeTime, NaiveTime::from_hms_opt(2, 30, 0).unwrap());
+        let now = wall(New_York, 2026, 3, 8, 9, 0);
+        assert_eq!(anchor.anchor_for(now).with_timezone(&Utc), utc(2026, 3, 8, 7, 30));
+    }
+
+    #[test]
+    fn before_wake_counts_from_previous_day() {
+        let anchor = CycleAnchor::new(AnchorMode::WakeTime, NaiveTime::from_hms_opt(7, 0, 0).unwrap());
+        let now = wall(Berlin, 2026, 3, 29, 1, 0);
+        assert_eq!(anchor.anchor_for(now), wall(Berlin, 2026, 3, 28, 7, 0));
+        // Berlin springs forward at 02:00 that night, so the next reset is 23 hours later.
+        let next = anchor.next_reset_after(now);
+        assert_eq!(next, wall(Berlin, 2026, 3, 29, 7, 0));
+        assert_eq!(next - anchor.anchor_for(now), Duration::hours(23));
+    }
+
+    #[test]
+    fn travelling_reanchors_in_new_zone() {
+        let anchor = CycleAnchor::new(AnchorMode::WakeTime, NaiveTime::from_hms_opt(7, 0, 0).unwrap());
+        let instant = utc(2026, 6, 10, 15, 0);
+        let berlin = anchor.anchor_for(instant.with_timezone(&Berlin));
+        let new_york = anchor.anchor_for(instant.with_timezone(&New_York));
+        assert_eq!(berlin.with_timezone(&Utc), utc(2026, 6, 10, 5, 0));
+        assert_eq!(new_york.with_timezone(&Utc), utc(2026, 6, 10, 11, 0));
+    }
+}
@@ -0,0 +1,327 @@
+// Tray menu with the current phase, the next transitions and quick actions.
+// The tray loop rebuilds it whenever what it shows changes; menu events are
+// handled here so the actions work without the frontend.
+use chrono::{DateTime, Duration, Utc};
+use chrono_tz::Tz;
+use std::sync::{Arc, Mutex};
+use tauri::menu::{CheckMenuItem, Menu, MenuItem, PredefinedMenuItem, Submenu};
+use tauri::{AppHandle, Manager, Wry};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::anchor::{AnchorState, CycleAnchor};
+use crate::focus::{self, FocusState};
+use crate::forecast;
+use crate::notifications::NotificationState;
+use crate::phase_estimator::EstimatorState;
+use crate::settings::SettingsState;
+use crate::ultradian::{CycleReading, TrayDisplayMode, UltradianClock};
+
+const SHOW_ID: &str = "show";
+const QUIT_ID: &str = "quit";
+const START_FOCUS_ID: &str = "start_focus";
+const STOP_FOCUS_ID: &str = "stop_focus";
+const SNOOZE_ID: &str = "snooze_notifications";
+const UNSNOOZE_ID: &str = "unsnooze_notifications";
+const PAUSE_TRACKING_ID: &str = "pause_tracking";
+const RESUME_TRACKING_ID: &str = "resume_tracking";
+
+const DISPLAY_MODES: [(TrayDisplayMode, &str, &str); 3] = [
+    (TrayDisplayMode::Countdown, "display_countdown", "Countdown"),
+    (TrayDisplayMode::PhaseName, "display_phase_name", "Phase Name"),
+    (TrayDisplayMode::IconOnly, "display_icon_only", "Icon Only"),
+];
+
+/// Transitions listed under the current phase.
+const UPCOMING_TRANSITIONS: usize = 3;
+/// How far ahead to look for them; comfortably more than three transitions.
+const UPCOMING_HORIZON_HOURS: i64 = 6;
+const SNOOZE_MINUTES: i64 = 60;
+
+/// Tray title while tracking is paused and no focus session is running.
+pub const PAUSED_TITLE: &str = "⏸ Paused";
+
+/// Runtime tray switches that are not worth persisting.
+#[derive(Debug, Default)]
+pub struct TrayControls {
+    /// Stops phase events, notifications and heart rate tracking.
+    pub tracking_paused: bool,
+}
+
+pub type TrayControlState = Arc<Mutex<TrayControls>>;
+
+/// Everything the menu shows apart from the live countdown. The menu is
+/// rebuilt when this changes, which includes every phase transition.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrayMenuView {
+    /// `(time, icon, label)` of each upcoming transition.
+    pub upcoming: Vec<(String, String, String)>,
+    pub focus_running: bool,
+    pub focus_available: bool,
+    pub snoozed_until: Option<String>,
+    pub display_mode: TrayDisplayMode,
+    pub tracking_paused: bool,
+}
+
+/// `(time, icon, label)` of the next few transitions after `now`, shifted by
+/// the same phase estimate offset as the current reading.
+pub fn upcoming_transitions(
+    clock: &UltradianClock,
+    anchor: &CycleAnchor,
+    now: DateTime<Tz>,
+    offset_minutes: f64,
+) -> Vec<(String, String, String)> {
+    forecast::forecast(clock, anchor, now, Duration::hours(UPCOMING_HORIZON_HOURS), 1.0, offset_minutes)
+        .into_iter()
+        .skip(1)
+        .take(UPCOMING_TRANSITIONS)
+        .map(|segment| {
+            (
+                segment.start.format("%-H:%M").to_string(),
+                segment.phase_icon,
+                segment.phase_label,
+            )
+        })
+        .collect()
+}
+
+/// Tray title: a running focus session's countdown takes precedence over the
+/// paused marker, which replaces the phase reading while tracking is paused.
+pub fn tray_title(
+    reading: &CycleReading,
+    focus_title: Option<String>,
+    tracking_paused: bool,
+    display_mode: TrayDisplayMode,
+) -> String {
+    match focus_title {
+        Some(title) => title,
+        None if tracking_paused => PAUSED_TITLE.to_string(),
+        None => reading.display_title(display_mode),
+    }
+}
+
+/// Menu line for the current phase; updated every tick between rebuilds.
+pub fn current_phase_text(reading: &CycleReading, tracking_paused: bool) -> String {
+    if tracking_paused {
+        return "Tracking paused".to_string();
+    }
+    format!(
+        "{} {} · {} min left",
+        reading.phase_icon,
+        reading.phase_label,
+        reading.time_remaining.ceil()
+    )
+}
+
+/// A built menu plus the item the tray loop keeps updating.
+pub struct TrayMenu {
+    pub menu: Menu<Wry>,
+    pub current: MenuItem<Wry>,
+}
+
+pub fn build_menu(app: &AppHandle, view: &TrayMenuView, current_text: &str) -> tauri::Result<TrayMenu> {
+    let menu = Menu::new(app)?;
+
+    let current = MenuItem::with_id(app, "current_phase", current_text, false, None::<&str>)?;
+    menu.append(&current)?;
+    for (index, (time, icon, label)) in view.upcoming.iter().enumerate() {
+        let item = MenuItem::with_id(
+            app,
+            format!("upcoming_{}", index),
+            format!("{}  {} {}", time, icon, label),
+            false,
+            None::<&str>,
+        )?;
+        menu.append(&item)?;
+    }
+    menu.append(&PredefinedMenuItem::separator(app)?)?;
+
+    let focus_item = if view.focus_running {
+        MenuItem::with_id(app, STOP_FOCUS_ID, "Stop Focus Session", true, None::<&str>)?
+    } else {
+        MenuItem::with_id(app, START_FOCUS_ID, "Start Focus Session", view.focus_available, None::<&str>)?
+    };
+    menu.append(&focus_item)?;
+
+    let snooze_item = match &view.snoozed_until {
+        Some(until) => MenuItem::with_id(
+            app,
+            UNSNOOZE_ID,
+            format!("Resume Notifications (snoozed until {})", until),
+            true,
+            None::<&str>,
+        )?,
+        None => MenuItem::with_id(app, SNOOZE_ID, "Snooze Notifications for 1 Hour", true, None::<&str>)?,
+    };
+    menu.append(&snooze_item)?;
+
+    let display = Submenu::with_id(app, "display_mode", "Tray Display", true)?;
+    for (mode, id, label) in DISPLAY_MODES {
+        let item = CheckMenuItem::with_id(app, id, label, true, view.display_mode == mode, None::<&str>)?;
+        display.append(&item)?;
+    }
+    menu.append(&display)?;
+
+    let tracking_item = if view.tracking_paused {
+        MenuItem::with_id(app, RESUME_TRACKING_ID, "Resume Tracking", true, None::<&str>)?
+    } else {
+        MenuItem::with_id(app, PAUSE_TRACKING_ID, "Pause Tracking", true, None::<&str>)?
+    };
+    menu.append(&tracking_item)?;
+    menu.append(&PredefinedMenuItem::separator(app)?)?;
+
+    menu.append(&MenuItem::with_id(app, SHOW_ID, "Show Dashboard", true, None::<&str>)?)?;
+    menu.append(&MenuItem::with_id(app, QUIT_ID, "Quit", true, None::<&str>)?)?;
+
+    Ok(TrayMenu { menu, current })
+}
+
+pub fn handle_menu_event(app: &AppHandle, id: &str) {
+    let result = match id {
+        SHOW_ID => {
+            if let Some(window) = app.get_webview_window("main") {
+                let _ = window.show();
+                let _ = window.set_focus();
+            }
+            Ok(())
+        }
+        QUIT_ID => {
+            app.exit(0);
+            Ok(())
+        }
+        START_FOCUS_ID => focus::start_fitted(
+            app.state::<SettingsState>().inner(),
+            app.state::<AnchorState>().inner(),
+            app.state::<EstimatorState>().inner(),
+            app.state::<FocusState>().inner(),
+            None,
+        )
+        .map(|_| ()),
+        STOP_FOCUS_ID => app
+            .state::<FocusState>()
+            .lock()
+            .map_err(|e| format!("Failed to lock focus timer: {}", e))
+            .and_then(|mut timer| timer.stop(Utc::now()))
+            .map(|_| ()),
+        SNOOZE_ID => app
+            .state::<NotificationState>()
+            .lock()
+            .map(|mut scheduler| scheduler.snooze(Utc::now() + Duration::minutes(SNOOZE_MINUTES)))
+            .map_err(|e| format!("Failed to lock notifications: {}", e)),
+        UNSNOOZE_ID => app
+            .state::<NotificationState>()
+            .lock()
+            .map(|mut scheduler| scheduler.clear_snooze())
+            .map_err(|e| format!("Failed to lock notifications: {}", e)),
+        PAUSE_TRACKING_ID | RESUME_TRACKING_ID => app
+            .state::<TrayControlState>()
+            .lock()
+            .map(|mut controls| controls.tracking_paused = id == PAUSE_TRACKING_ID)
+            .map_err(|e| format!("Failed to lock tray controls: {}", e)),
+        _ => match DISPLAY_MODES.iter().find(|(_, mode_id, _)| *mode_id == id) {
+            Some((mode, _, _)) => set_display_mode(app, *mode),
+            None => Ok(()),
+        },
+    };
+
+    if let Err(e) = result {
+        let _ = app.notification().builder().title("Circada").body(e).show();
+    }
+}
+
+fn set_display_mode(app: &AppHandle, mode: TrayDisplayMode) -> Result<(), String> {
+    let settings_state = app.state::<SettingsState>();
+    let mut store = settings_state.lock().map_err(|e| format!("Failed to lock settings: {}", e))?;
+    let mut settings = store.current.clone();
+    settings.tray_display_mode = mode;
+    store.replace(settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::anchor::AnchorMode;
+    use chrono::{NaiveTime, TimeZone};
+    use chrono_tz::Europe::Berlin;
+
+    fn at(day: u32, hour: u32, minute: u32) -> DateTime<Tz> {
+        Berlin.with_ymd_and_hms(2026, 3, day, hour, minute, 0).unwrap()
+    }
+
+    fn entry(time: &str, icon: &str, label: &str) -> (String, String, String) {
+        (time.to_string(), icon.to_string(), label.to_string())
+    }
+
+    #[test]
+    fn upcoming_transitions_follow_the_estimate_offset() {
+        let clock = UltradianClock::default();
+        let anchor = CycleAnchor::new(AnchorMode::Midnight, NaiveTime::MIN);
+        // 10:10 is 70 min into cycle 7
+        let now = at(2, 10, 10);
+
+        assert_eq!(
+            upcoming_transitions(&clock, &anchor, now, 0.0),
+            [
+                entry("10:15", "😴", "Rest Phase"),
+                entry("10:30", "↗", "Rising Energy"),
+                entry("10:35", "↗", "Rising Energy"),
+            ]
+        );
+        assert_eq!(
+            upcoming_transitions(&clock, &anchor, now, 10.0),
+            [
+                entry("10:20", "↗", "Rising Energy"),
+                entry("10:25", "↗", "Rising Energy"),
+                entry("10:35", "↑", "Building Energy"),
+            ]
+        );
+        assert_eq!(
+            upcoming_transitions(&clock, &anchor, now, -20.0),
+            [
+                entry("10:20", "↘", "Winding Down"),
+                entry("10:25", "↘", "Winding Down"),
+                entry("10:35", "😴", "Rest Phase"),
+            ]
+        );
+    }
+
+    #[test]
+    fn upcoming_transitions_cross_midnight() {
+        let clock = UltradianClock::default();
+        let midnight = CycleAnchor::new(AnchorMode::Midnight, NaiveTime::MIN);
+        assert_eq!(
+            upcoming_transitions(&clock, &midnight, at(2, 23, 50), 0.0),
+            [
+                entry("0:00", "↗", "Rising Energy"),
+                entry("0:05", "↗", "Rising Energy"),
+                entry("0:15", "↑", "Building Energy"),
+            ]
+        );
+
+        // A wake-time day runs on past midnight: 23:50 is 20 min into cycle 12 since 07:00
+        let wake = CycleAnchor::new(AnchorMode::WakeTime, NaiveTime::from_hms_opt(7, 0, 0).unwrap());
+        assert_eq!(
+            upcoming_transitions(&clock, &wake, at(2, 23, 50), 0.0),
+            [
+                entry("0:00", "🔥", "Peak Energy"),
+                entry("0:15", "⚡", "Peak Flow"),
+                entry("0:30", "↘", "Winding Down"),
+            ]
+        );
+    }
+
+    #[test]
+    fn phase_text_and_title_variants() {
+        let clock = UltradianClock::default();
+        let reading = clock.at_position(30.2, 1);
+        assert_eq!(current_phase_text(&reading, false), "🔥 Peak Energy · 30 min left");
+        assert_eq!(current_phase_text(&reading, true), "Tracking paused");
+
+        assert_eq!(tray_title(&reading, None, false, TrayDisplayMode::PhaseName), "🔥 Peak Energy");
+        assert_eq!(tray_title(&reading, None, false, TrayDisplayMode::IconOnly), "🔥");
+        assert_eq!(tray_title(&reading, None, true, TrayDisplayMode::PhaseName), PAUSED_TITLE);
+        // A running focus session keeps its countdown even while tracking is paused
+        let focus = || Some("🎯 24:13".to_string());
+        assert_eq!(tray_title(&reading, focus(), false, TrayDisplayMode::Countdown), "🎯 24:13");
+        assert_eq!(tray_title(&reading, focus(), true, TrayDisplayMode::Countdown), "🎯 24:13");
+    }
+}
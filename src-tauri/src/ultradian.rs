@@ -0,0 +1,304 @@
+// Ultradian cycle model shared by the tray updater, widget data and any other
+// command that needs to know where in the 90-minute cycle we currently are.
+use chrono::{DateTime, Duration};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EnergyPhase {
+    Transition,
+    High,
+    Low,
+}
+
+impl EnergyPhase {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EnergyPhase::Transition => "transition",
+            EnergyPhase::High => "high",
+            EnergyPhase::Low => "low",
+        }
+    }
+}
+
+/// One entry of the six-phase display table. A band covers every cycle
+/// position up to and including `until` minutes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PhaseBand {
+    pub until: f64,
+    pub icon: String,
+    pub label: String,
+}
+
+impl PhaseBand {
+    fn new(until: f64, icon: &str, label: &str) -> Self {
+        Self {
+            until,
+            icon: icon.to_string(),
+            label: label.to_string(),
+        }
+    }
+}
+
+/// What the tray title shows for a reading.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrayDisplayMode {
+    /// Icon and time to the next transition, e.g. `↗ 12:34`.
+    Countdown,
+    /// Icon and band label, e.g. `⚡ Peak Flow`.
+    PhaseName,
+    IconOnly,
+}
+
+/// Snapshot of the cycle at a given moment.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CycleReading {
+    pub cycle_position: f64,
+    pub cycle_number: i32,
+    pub energy_phase: EnergyPhase,
+    pub energy_intensity: f64,
+    pub phase_icon: String,
+    pub phase_label: String,
+    /// Minutes until the next energy transition (end of the high window or end of cycle).
+    pub time_remaining: f64,
+}
+
+impl CycleReading {
+    pub fn minutes_left(&self) -> i32 {
+        self.time_remaining.floor() as i32
+    }
+
+    pub fn seconds_left(&self) -> i32 {
+        ((self.time_remaining - self.minutes_left() as f64) * 60.0).floor() as i32
+    }
+
+    /// Wall-clock time of the next transition, `now` being the time of this reading.
+    pub fn next_transition_at(&self, now: DateTime<Tz>) -> DateTime<Tz> {
+        now + Duration::milliseconds((self.time_remaining * 60_000.0).round() as i64)
+    }
+
+    /// Tray title in the `↗ 12:34` format.
+    pub fn tray_title(&self) -> String {
+        format!("{} {:02}:{:02}", self.phase_icon, self.minutes_left(), self.seconds_left())
+    }
+
+    pub fn display_title(&self, mode: TrayDisplayMode) -> String {
+        match mode {
+            TrayDisplayMode::Countdown => self.tray_title(),
+            TrayDisplayMode::PhaseName => format!("{} {}", self.phase_icon, self.phase_label),
+            TrayDisplayMode::IconOnly => self.phase_icon.clone(),
+        }
+    }
+
+    /// Widget background colour (HSL) derived from the energy phase.
+    pub fn background_color(&self) -> String {
+        match self.energy_phase {
+            EnergyPhase::High => format!(
+                "hsl({}, 70%, {}%)",
+                120.0 + self.energy_intensity * 60.0,
+                50.0 + self.energy_intensity * 20.0
+            ),
+            EnergyPhase::Low => format!("hsl(220, 70%, {}%)", 30.0 + self.energy_intensity * 30.0),
+            EnergyPhase::Transition => format!("hsl(170, 70%, {}%)", 40.0 + self.energy_intensity * 20.0),
+        }
+    }
+}
+
+/// The ultradian cycle: a rising transition, a high-energy window, a falling
+/// transition and a low-energy rest window, repeated every `cycle_length` minutes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UltradianClock {
+    pub cycle_length: f64,
+    /// End of the rising transition at the start of each cycle.
+    pub rise_end: f64,
+    /// End of the high-energy window.
+    pub high_end: f64,
+    /// End of the falling transition; the rest of the cycle is low energy.
+    pub fall_end: f64,
+    pub bands: Vec<PhaseBand>,
+}
+
+impl Default for UltradianClock {
+    fn default() -> Self {
+        Self {
+            cycle_length: 90.0,
+            rise_end: 5.0,
+            high_end: 60.0,
+            fall_end: 65.0,
+            bands: vec![
+                PhaseBand::new(15.0, "↗", "Rising Energy"),
+                PhaseBand::new(30.0, "↑", "Building Energy"),
+                PhaseBand::new(45.0, "🔥", "Peak Energy"),
+                PhaseBand::new(60.0, "⚡", "Peak Flow"),
+                PhaseBand::new(75.0, "↘", "Winding Down"),
+                PhaseBand::new(90.0, "😴", "Rest Phase"),
+            ],
+        }
+    }
+}
+
+impl UltradianClock {
+    pub fn energy_phase(&self, cycle_position: f64) -> EnergyPhase {
+        if cycle_position <= self.rise_end {
+            EnergyPhase::Transition
+        } else if cycle_position <= self.high_end {
+            EnergyPhase::High
+        } else if cycle_position <= self.fall_end {
+            EnergyPhase::Transition
+        } else {
+            EnergyPhase::Low
+        }
+    }
+
+    pub fn energy_intensity(&self, cycle_position: f64) -> f64 {
+        if cycle_position <= self.rise_end {
+            0.4 + (cycle_position / self.rise_end) * 0.4
+        } else if cycle_position <= self.high_end {
+            let progress = (cycle_position - self.rise_end) / (self.high_end - self.rise_end);
+            0.5 + 0.4 * (progress * PI).sin()
+        } else if cycle_position <= self.fall_end {
+            0.8 - ((cycle_position - self.high_end) / (self.fall_end - self.high_end)) * 0.4
+        } else {
+            let progress = (cycle_position - self.fall_end) / (self.cycle_length - self.fall_end);
+            0.2 + 0.2 * (progress * PI).sin()
+        }
+    }
+
+    /// Display band for a cycle position; positions past the last band use the last one.
+    pub fn band(&self, cycle_position: f64) -> &PhaseBand {
+        self.bands
+            .iter()
+            .find(|band| cycle_position <= band.until)
+            .or_else(|| self.bands.last())
+            .expect("phase table must not be empty")
+    }
+
+    /// Minutes until the next transition: the end of the high window while we
+    /// are still rising or high, otherwise the end of the cycle.
+    pub fn time_remaining(&self, cycle_position: f64) -> f64 {
+        if cycle_position <= self.high_end {
+            self.high_end - cycle_position
+        } else {
+            self.cycle_length - cycle_position
+        }
+    }
+
+    /// Minutes from `cycle_position` to the next point where either the energy
+    /// phase or the display band changes.
+    pub fn minutes_to_boundary(&self, cycle_position: f64) -> f64 {
+        const EPSILON: f64 = 1e-3;
+        [self.rise_end, self.high_end, self.fall_end, self.cycle_length]
+            .into_iter()
+            .chain(self.bands.iter().map(|band| band.until))
+            .filter(|&boundary| boundary > cycle_position + EPSILON && boundary <= self.cycle_length)
+            .fold(self.cycle_length, f64::min)
+            - cycle_position
+    }
+
+    /// Reading for a number of minutes elapsed since the cycle anchor.
+    pub fn reading(&self, elapsed_minutes: f64) -> CycleReading {
+        let elapsed_minutes = elapsed_minutes.max(0.0);
+        let cycle_number = (elapsed_minutes / self.cycle_length).floor() as i32 + 1;
+        self.at_position(elapsed_minutes % self.cycle_length, cycle_number)
+    }
+
+    /// Reading for a known position within a cycle.
+    pub fn at_position(&self, cycle_position: f64, cycle_number: i32) -> CycleReading {
+        let band = self.band(cycle_position);
+
+        CycleReading {
+            cycle_position,
+            cycle_number,
+            energy_phase: self.energy_phase(cycle_position),
+            energy_intensity: self.energy_intensity(cycle_position),
+            phase_icon: band.icon.clone(),
+            phase_label: band.label.clone(),
+            time_remaining: self.time_remaining(cycle_position),
+        }
+    }
+
+    /// Reading `offset_minutes` ahead of `elapsed_minutes` since the anchor, for
+    /// a phase estimate that runs ahead of or behind the schedule. The cycle
+    /// number follows the shifted position; shifting back past the anchor wraps
+    /// within cycle 1.
+    pub fn shifted_reading(&self, elapsed_minutes: f64, offset_minutes: f64) -> CycleReading {
+        let shifted = elapsed_minutes + offset_minutes;
+        if shifted >= 0.0 {
+            return self.reading(shifted);
+        }
+        self.at_position(shifted.rem_euclid(self.cycle_length), 1)
+    }
+
+    /// Reading at the instant `now`, with cycles counted from `anchor`.
+    pub fn reading_at(&self, now: DateTime<Tz>, anchor: DateTime<Tz>) -> CycleReading {
+        self.reading(minutes_between(anchor, now))
+    }
+}
+
+fn minutes_between(from: DateTime<Tz>, to: DateTime<Tz>) -> f64 {
+    (to - from).num_milliseconds() as f64 / 60_000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn phase_edges_are_inclusive() {
+        let clock = UltradianClock::default();
+        assert_eq!(clock.energy_phase(0.0), EnergyPhase::Transition);
+        assert_eq!(clock.energy_phase(5.0), EnergyPhase::Transition);
+        assert_eq!(clock.energy_phase(5.01), EnergyPhase::High);
+        assert_eq!(clock.energy_phase(60.0), EnergyPhase::High);
+        assert_eq!(clock.energy_phase(60.01), EnergyPhase::Transition);
+        assert_eq!(clock.energy_phase(65.0), EnergyPhase::Transition);
+        assert_eq!(clock.energy_phase(65.01), EnergyPhase::Low);
+        assert_eq!(clock.energy_phase(89.99), EnergyPhase::Low);
+    }
+
+    #[test]
+    fn cycle_wraps_at_ninety_minutes() {
+        let clock = UltradianClock::default();
+        let reading = clock.reading(90.0);
+        assert_eq!(reading.cycle_position, 0.0);
+        assert_eq!(reading.cycle_number, 2);
+        assert_eq!(reading.energy_phase, EnergyPhase::Transition);
+        assert_eq!(reading.phase_label, "Rising Energy");
+
+        let last = clock.reading(89.5);
+        assert_eq!(last.cycle_number, 1);
+        assert_eq!(last.phase_label, "Rest Phase");
+    }
+
+    #[test]
+    fn time_remaining_targets_next_transition() {
+        let clock = UltradianClock::default();
+        assert_eq!(clock.time_remaining(5.0), 55.0);
+        assert_eq!(clock.time_remaining(60.0), 0.0);
+        assert_eq!(clock.time_remaining(65.0), 25.0);
+        assert_eq!(clock.reading(29.5).tray_title(), "↑ 30:30");
+    }
+
+    #[test]
+    fn intensity_matches_transition_edges() {
+        let clock = UltradianClock::default();
+        assert!((clock.energy_intensity(0.0) - 0.4).abs() < 1e-9);
+        assert!((clock.energy_intensity(5.0) - 0.8).abs() < 1e-9);
+        assert!((clock.energy_intensity(60.0) - 0.5).abs() < 1e-9);
+        assert!((clock.energy_intensity(65.0) - 0.4).abs() < 1e-9);
+        assert!((clock.energy_intensity(90.0) - 0.2).abs() < 1e-9);
+    }
+
+    #[test]
+    fn bands_follow_six_phase_table() {
+        let clock = UltradianClock::default();
+        let icons: Vec<_> = [15.0, 15.5, 30.5, 45.5, 60.5, 75.5]
+            .iter()
+            .map(|&pos| clock.band(pos).icon.as_str())
+            .collect();
+        assert_eq!(icons, ["↗", "↑", "🔥", "⚡", "↘", "😴"]);
+    }
+}
@@ -0,0 +1,414 @@
+// Morlet continuous wavelet transform (Torrence & Compo 1998) for rhythms whose
+// period drifts through the day, with a ridge tracing the dominant period and
+// its phase over time.
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+use crate::timeseries::TimeSeries;
+
+/// The wavelet is truncated this many scales either side of its centre.
+const KERNEL_HALF_WIDTH: f64 = 4.0;
+/// Upper bound on the resampled grid, to keep the transform interactive.
+const MAX_GRID_POINTS: usize = 50_000;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WaveletConfig {
+    pub min_period_minutes: f64,
+    pub max_period_minutes: f64,
+    /// Scales per doubling of the period.
+    pub voices_per_octave: usize,
+    /// Morlet centre frequency; 6 balances time and period resolution.
+    pub omega0: f64,
+    /// Grid spacing the series is interpolated onto, never finer than its own
+    /// median sampling interval.
+    pub resample_minutes: f64,
+    /// Gaps between samples longer than this are left empty rather than
+    /// interpolated.
+    pub max_gap_minutes: f64,
+    /// Ridge cost per voice the period moves between neighbouring grid points,
+    /// in units of normalized power (white noise averages 1). Higher values
+    /// give a smoother ridge.
+    pub ridge_penalty: f64,
+}
+
+impl Default for WaveletConfig {
+    fn default() -> Self {
+        Self {
+            min_period_minutes: 60.0,
+            max_period_minutes: 240.0,
+            voices_per_octave: 12,
+            omega0: 6.0,
+            resample_minutes: 5.0,
+            max_gap_minutes: 15.0,
+            ridge_penalty: 1.0,
+        }
+    }
+}
+
+impl WaveletConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(self.min_period_minutes > 0.0 && self.min_period_minutes < self.max_period_minutes) {
+            return Err("Wavelet periods must satisfy 0 < min < max".to_string());
+        }
+        if self.voices_per_octave == 0 {
+            return Err("Voices per octave must be at least 1".to_string());
+        }
+        if self.omega0 < 5.0 {
+            return Err("Morlet centre frequency must be at least 5".to_string());
+        }
+        if self.resample_minutes <= 0.0 || self.max_gap_minutes < 0.0 || self.ridge_penalty < 0.0 {
+            return Err("Resampling interval must be positive and gap and ridge penalty not negative".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Dominant rhythm at each grid time, `None` inside gaps.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WaveletRidge {
+    pub period_minutes: Vec<Option<f64>>,
+    /// Radians in [0, 2π); 0 is the crest of the oscillation.
+    pub phase_radians: Vec<Option<f64>>,
+    /// In the units of the input series.
+    pub amplitude: Vec<Option<f64>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Scalogram {
+    /// Grid times (epoch ms).
+    pub times: Vec<i64>,
+    /// Shortest first.
+    pub periods_minutes: Vec<f64>,
+    /// `power[p][t]` for period `p` at time `t`, |W|² over the series variance
+    /// as in Torrence & Compo, so white noise averages 1 at every period. Zero
+    /// inside gaps.
+    pub power: Vec<Vec<f64>>,
+    /// Longest period at each time not affected by the series edges or gaps;
+    /// power above it should be shaded in plots.
+    pub cone_of_influence_minutes: Vec<f64>,
+    /// Grid times that fall inside a gap.
+    pub missing: Vec<bool>,
+    pub ridge: WaveletRidge,
+    pub resample_minutes: f64,
+}
+
+/// Linear interpolation onto a regular grid; points in long gaps are `None`.
+fn resample(series: &TimeSeries, step_minutes: f64, max_gap_minutes: f64) -> (Vec<i64>, Vec<Option<f64>>) {
+    let step = (step_minutes * 60_000.0).round() as i64;
+    let max_gap = (max_gap_minutes * 60_000.0) as i64;
+    let (first, last) = (series.timestamps[0], series.timestamps[series.len() - 1]);
+    let mut times = Vec::new();
+    let mut values = Vec::new();
+    let mut k = 0;
+    let mut t = first;
+    while t <= last {
+        while k + 1 < series.len() && series.timestamps[k + 1] <= t {
+            k += 1;
+        }
+        let value = if series.timestamps[k] == t {
+            Some(series.values[k])
+        } else {
+            let (t0, t1) = (series.timestamps[k], series.timestamps[k + 1]);
+            (t1 - t0 <= max_gap).then(|| {
+                let weight = (t - t0) as f64 / (t1 - t0) as f64;
+                series.values[k] + weight * (series.values[k + 1] - series.values[k])
+            })
+        };
+        times.push(t);
+        values.push(value);
+        t += step;
+    }
+    (times, values)
+}
+
+/// Minutes from each grid point to the nearest series edge or gap.
+fn distance_to_edge(missing: &[bool], step_minutes: f64) -> Vec<f64> {
+    let n = missing.len();
+    let mut distance = vec![0usize; n];
+    let mut since = 0;
+    for i in 0..n {
+        since = if missing[i] { 0 } else { since + 1 };
+        distance[i] = since;
+    }
+    let mut until = 0;
+    for i in (0..n).rev() {
+        until = if missing[i] { 0 } else { until + 1 };
+        distance[i] = distance[i].min(until);
+    }
+    distance.iter().map(|&d| d.saturating_sub(1) as f64 * step_minutes).collect()
+}
+
+/// Morlet coefficients at one scale. The 1/s normalization makes the modulus
+/// equal the amplitude of a sinusoid at the matching scale; multiply the
+/// squared modulus by `tc_power_factor` for Torrence & Compo's power.
+fn transform_at(y: &[f64], scale_steps: f64, omega0: f64) -> Vec<(f64, f64)> {
+    let half_width = (KERNEL_HALF_WIDTH * scale_steps).ceil() as isize;
+    let norm = 2.0 / ((2.0 * PI).sqrt() * scale_steps);
+    let kernel: Vec<(f64, f64)> = (-half_width..=half_width)
+        .map(|k| {
+            let u = k as f64 / scale_steps;
+            let envelope = (-u * u / 2.0).exp() * norm;
+            let (sin, cos) = (omega0 * u).sin_cos();
+            // Conjugate wavelet, so a rising phase reads as increasing angle
+            (envelope * cos, -envelope * sin)
+        })
+        .collect();
+
+    let n = y.len() as isize;
+    (0..n)
+        .map(|i| {
+            let (mut re, mut im) = (0.0, 0.0);
+            for (j, &(kre, kim)) in kernel.iter().enumerate() {
+                let index = i + j as isize - half_width;
+                if index >= 0 && index < n {
+                    let value = y[index as usize];
+                    re += value * kre;
+                    im += value * kim;
+                }
+            }
+            (re, im)
+        })
+        .collect()
+}
+
+/// Period of the sinusoid whose power peaks at `scale` (Torrence & Compo 1998,
+/// table 1), in the same units as the scale.
+fn fourier_factor(omega0: f64) -> f64 {
+    4.0 * PI / (omega0 + (2.0 + omega0 * omega0).sqrt())
+}
+
+/// Converts the squared 1/s-normalized modulus to Torrence & Compo's unit-
+/// energy power, which scales with √s in amplitude.
+fn tc_power_factor(scale_steps: f64) -> f64 {
+    PI.sqrt() / 2.0 * scale_steps
+}
+
+/// Path through the scales maximising power minus a penalty on period jumps,
+/// by dynamic programming. Returns one scale index per grid point.
+fn ridge_path(power: &[Vec<f64>], penalty: f64) -> Vec<usize> {
+    let (scales, n) = (power.len(), power[0].len());
+    let mut score: Vec<f64> = (0..scales).map(|s| power[s][0]).collect();
+    let mut back = vec![vec![0usize; scales]; n];
+    for t in 1..n {
+        // The penalty is linear in the jump, so two sweeps find the best
+        // predecessor of every scale
+        let mut best: Vec<(f64, usize)> = score.iter().copied().zip(0..scales).collect();
+        for s in 1..scales {
+            if best[s - 1].0 - penalty > best[s].0 {
+                best[s] = (best[s - 1].0 - penalty, best[s - 1].1);
+            }
+        }
+        for s in (0..scales - 1).rev() {
+            if best[s + 1].0 - penalty > best[s].0 {
+                best[s] = (best[s + 1].0 - penalty, best[s + 1].1);
+            }
+        }
+        for s in 0..scales {
+            back[t][s] = best[s].1;
+            score[s] = best[s].0 + power[s][t];
+        }
+    }
+    let mut path = vec![0; n];
+    path[n - 1] = (0..scales).max_by(|&a, &b| score[a].total_cmp(&score[b])).unwrap_or(0);
+    for t in (1..n).rev() {
+        path[t - 1] = back[t][path[t]];
+    }
+    path
+}
+
+pub fn morlet_scalogram(series: &TimeSeries, config: &WaveletConfig) -> Result<Scalogram, String> {
+    if series.len() < 3 {
+        return Err("A wavelet transform needs at least 3 samples".to_string());
+    }
+    let step_minutes = config.resample_minutes.max(series.median_interval_minutes().unwrap_or(0.0));
+    let grid_points = (series.span_minutes() / step_minutes) as usize + 1;
+    if grid_points > MAX_GRID_POINTS {
+        return Err(format!(
+            "Series spans {} points at {} min, more than {}; use a coarser resampling interval",
+            grid_points, step_minutes, MAX_GRID_POINTS
+        ));
+    }
+    if config.min_period_minutes < 2.0 * step_minutes {
+        return Err(format!(
+            "Shortest period must be at least twice the {} min resampling interval",
+            step_minutes
+        ));
+    }
+
+    let (times, values) = resample(series, step_minutes, config.max_gap_minutes);
+    let present: Vec<f64> = values.iter().flatten().copied().collect();
+    let mean = present.iter().sum::<f64>() / present.len() as f64;
+    let variance = present.iter().map(|value| (value - mean).powi(2)).sum::<f64>() / present.len() as f64;
+    if variance == 0.0 {
+        return Err("Series is constant, so it has no periodic component".to_string());
+    }
+    // Gaps contribute nothing once the mean is removed
+    let y: Vec<f64> = values.iter().map(|value| value.map_or(0.0, |v| v - mean)).collect();
+    let missing: Vec<bool> = values.iter().map(Option::is_none).collect();
+
+    let octaves = (config.max_period_minutes / config.min_period_minutes).log2();
+    let count = (octaves * config.voices_per_octave as f64).round() as usize + 1;
+    let periods_minutes: Vec<f64> = (0..count)
+        .map(|k| config.min_period_minutes * 2f64.powf(k as f64 / config.voices_per_octave as f64))
+        .collect();
+
+    let factor = fourier_factor(config.omega0);
+    let scales_steps: Vec<f64> = periods_minutes.iter().map(|period| period / factor / step_minutes).collect();
+    let coefficients: Vec<Vec<(f64, f64)>> = scales_steps
+        .iter()
+        .map(|&scale_steps| {
+            let mut row = transform_at(&y, scale_steps, config.omega0);
+            for (c, &gap) in row.iter_mut().zip(&missing) {
+                if gap {
+                    *c = (0.0, 0.0);
+                }
+            }
+            row
+        })
+        .collect();
+    let power: Vec<Vec<f64>> = coefficients
+        .iter()
+        .zip(&scales_steps)
+        .map(|(row, &scale_steps)| {
+            let to_power = tc_power_factor(scale_steps) / variance;
+            row.iter().map(|&(re, im)| (re * re + im * im) * to_power).collect()
+        })
+        .collect();
+
+    // Edge effects reach √2 scales into the series (the Morlet e-folding time)
+    let cone_of_influence_minutes = distance_to_edge(&missing, step_minutes)
+        .into_iter()
+        .map(|d| factor * d / 2f64.sqrt())
+        .collect();
+
+    let path = ridge_path(&power, config.ridge_penalty);
+    let mut ridge = WaveletRidge::default();
+    for (t, &s) in path.iter().enumerate() {
+        if missing[t] {
+            ridge.period_minutes.push(None);
+            ridge.phase_radians.push(None);
+            ridge.amplitude.push(None);
+            continue;
+        }
+        // Refine between voices with a parabola through log-period
+        let mut log_period = periods_minutes[s].log2();
+        if s > 0 && s + 1 < count {
+            let (a, b, c) = (power[s - 1][t], power[s][t], power[s + 1][t]);
+            let curvature = a - 2.0 * b + c;
+            if curvature < 0.0 {
+                let offset = (0.5 * (a - c) / curvature).clamp(-0.5, 0.5);
+                log_period += offset / config.voices_per_octave as f64;
+            }
+        }
+        let (re, im) = coefficients[s][t];
+        ridge.period_minutes.push(Some(2f64.powf(log_period)));
+        ridge.phase_radians.push(Some(im.atan2(re).rem_euclid(2.0 * PI)));
+        ridge.amplitude.push(Some(re.hypot(im)));
+    }
+
+    Ok(Scalogram {
+        times,
+        periods_minutes,
+        power,
+        cone_of_influence_minutes,
+        missing,
+        ridge,
+        resample_minutes: step_minutes,
+    })
+}
+
+/// Activity or heart rate in, scalogram and ridge out, ready for a heatmap.
+#[tauri::command]
+pub async fn calculate_wavelet_scalogram(series: TimeSeries, config: Option<WaveletConfig>) -> Result<Scalogram, String> {
+    let config = config.unwrap_or_default();
+    config.validate()?;
+    series.validate()?;
+
+    tauri::async_runtime::spawn_blocking(move || morlet_scalogram(&series, &config))
+        .await
+        .map_err(|e| format!("Failed to calculate wavelet scalogram: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const START: i64 = 1_772_409_600_000; // 2026-03-02 00:00 UTC
+
+    fn sampled(minutes: usize, mut signal: impl FnMut(f64) -> f64) -> TimeSeries {
+        TimeSeries {
+            timestamps: (0..minutes).map(|m| START + m as i64 * 60_000).collect(),
+            values: (0..minutes).map(|m| signal(m as f64)).collect(),
+        }
+    }
+
+    /// Grid points whose cone of influence admits `period`, i.e. free of edge effects.
+    fn inside_cone(scalogram: &Scalogram, period: f64) -> Vec<usize> {
+        (0..scalogram.times.len())
+            .filter(|&t| scalogram.cone_of_influence_minutes[t] >= period)
+            .collect()
+    }
+
+    #[test]
+    fn pure_tone_ridge_sits_on_its_period() {
+        for period in [75.0, 100.0, 150.0] {
+            let series = sampled(1440, |m| 5.0 + 2.0 * (2.0 * PI * m / period).cos());
+            let scalogram = morlet_scalogram(&series, &WaveletConfig::default()).unwrap();
+            for &t in &inside_cone(&scalogram, period) {
+                let ridge = scalogram.ridge.period_minutes[t].unwrap();
+                assert!((ridge / period - 1.0).abs() < 0.01, "period {} ridge {}", period, ridge);
+            }
+            // Well clear of the edges the modulus is the amplitude
+            for &t in &inside_cone(&scalogram, 2.0 * period) {
+                let amplitude = scalogram.ridge.amplitude[t].unwrap();
+                assert!((amplitude - 2.0).abs() < 0.05, "period {} amplitude {}", period, amplitude);
+            }
+        }
+    }
+
+    #[test]
+    fn ridge_phase_is_zero_at_the_crest() {
+        let series = sampled(1000, |m| (2.0 * PI * m / 100.0).cos());
+        let scalogram = morlet_scalogram(&series, &WaveletConfig::default()).unwrap();
+        // Grid point 100 is minute 500, a crest; 105 is a quarter cycle later.
+        let crest = scalogram.ridge.phase_radians[100].unwrap();
+        assert!(!(0.05..=2.0 * PI - 0.05).contains(&crest), "{}", crest);
+        let quarter = scalogram.ridge.phase_radians[105].unwrap();
+        assert!((quarter - PI / 2.0).abs() < 0.05, "{}", quarter);
+    }
+
+    #[test]
+    fn white_noise_power_averages_one() {
+        let mut seed: u64 = 17;
+        let series = sampled(5 * 8000, |_| {
+            seed = seed.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1_442_695_040_888_963_407);
+            (seed >> 11) as f64 / (1u64 << 53) as f64
+        });
+        let scalogram = morlet_scalogram(&series, &WaveletConfig::default()).unwrap();
+        for (p, &period) in scalogram.periods_minutes.iter().enumerate().step_by(6) {
+            let inside = inside_cone(&scalogram, period);
+            let mean = inside.iter().map(|&t| scalogram.power[p][t]).sum::<f64>() / inside.len() as f64;
+            assert!((mean - 1.0).abs() < 0.2, "period {} mean power {}", period, mean);
+        }
+    }
+
+    #[test]
+    fn gaps_are_left_empty() {
+        let mut series = sampled(720, |m| (2.0 * PI * m / 90.0).cos());
+        series.timestamps.drain(400..440);
+        series.values.drain(400..440);
+        let scalogram = morlet_scalogram(&series, &WaveletConfig::default()).unwrap();
+        assert!(scalogram.missing[85]);
+        assert!(scalogram.ridge.period_minutes[85].is_none());
+        assert!(scalogram.power.iter().all(|row| row[85] == 0.0));
+        assert_eq!(scalogram.cone_of_influence_minutes[85], 0.0);
+    }
+
+    #[test]
+    fn rejects_periods_below_twice_the_grid() {
+        let series = sampled(600, |m| m.sin());
+        let config = WaveletConfig { min_period_minutes: 8.0, ..WaveletConfig::default() };
+        assert!(morlet_scalogram(&series, &config).is_err());
+    }
+}
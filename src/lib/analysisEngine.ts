@@ -1,9 +1,16 @@
 import type { DataProvider } from "./dataProvider";
-import { detectAwakeningPattern } from "./phaseDetection";
-import { calcIntradailyVariability, calcSleepEfficiency } from "./tauriBridge";
-import { detectUltradianCycles } from "./ultradian";
-import type { DerivedMetric, CircadianAnalysis } from "./types";
-import { ADHD_THRESHOLDS } from "./constants";
+import {
+  calcIntradailyVariability,
+  calcSleepPeriodMetrics,
+  detectAwakening,
+  detectUltradianCyclesNative,
+  estimateCircadianPhase,
+  scoreSleepWake,
+  type CircadianPhaseMarkers,
+  type SleepScoring,
+} from "./tauriBridge";
+import type { DerivedMetric, CircadianAnalysis, TimeSeries } from "./types";
+import { ADHD_THRESHOLDS, REFERENCE_CBT_MIN_HOUR } from "./constants";
 
 /**
  * Core orchestrator that pulls data from a provider, executes algorithms, and
@@ -19,75 +26,104 @@ export class CircadianAnalysisEngine {
   async run(): Promise<CircadianAnalysis> {
     const data = await this.provider.getData();
 
-    if (!data.activity || !data.temperature) {
-      throw new Error("Provider did not supply required activity & temperature streams");
+    if (!data.activity) {
+      throw new Error("Provider did not supply the required activity stream");
     }
 
-    // Morning awakening detection (pure TS)
-    const awakening = detectAwakeningPattern({
-      activity: data.activity,
-      temperature: data.temperature,
-    });
+    // Morning awakening detection (Rust); only the latest recorded morning counts
+    const awakening = await detectAwakening(data.activity);
+    const morning = awakening.mornings.filter((m) => m.status !== "no_data").pop();
+    const phaseDelay = morning?.status === "detected" ? morning.phaseDelayHours : undefined;
 
     // --- Sleep Efficiency & IV ---
-    // First, determine a dynamic sleep threshold from the whole day's activity
-    const sortedAct = [...data.activity.values].sort((a, b) => a - b);
-    let sleepThreshold = sortedAct[Math.floor(sortedAct.length * 0.15)]; // start with 15th percentile
-
-    // Next, identify the sleep window to isolate sleep-related activity.
-    // NOTE: This is a simplification. A robust implementation would use a proper
-    // sleep window detection algorithm (e.g., from `sleepAnalysis` HealthKit data).
-    // For our mock data, we'll assume a window from 10 PM to 7 AM.
-    const sleepWindowIndices = data.activity.timestamps
-      .map((ts, i) => ({ ts, i }))
-      .filter(({ ts }) => {
-        const hour = new Date(ts).getHours();
-        return hour >= 22 || hour < 7;
-      })
-      .map(({ i }) => i);
-
-    const sleepActivity = sleepWindowIndices.map(i => data.activity.values[i]);
-
-    // If we have sleepActivity extracted, set threshold to its max to ensure all sleep minutes count.
-    if (sleepActivity.length > 0) {
-      sleepThreshold = Math.max(...sleepActivity) + 0.1; // small margin
-    }
-
-    // Now, calculate metrics using only the relevant data subsets
+    // Score each minute as sleep or wake, then measure the main rest interval.
+    // Oakley's weighting suits the low wrist counts HealthKit reports.
     const iv = await calcIntradailyVariability(data.activity.values);
-    const sleepEffRes = await calcSleepEfficiency(sleepActivity, sleepThreshold);
+    const { scoring, sleepEfficiency } = await this.sleepPeriod(data.activity);
 
-    // Ultradian cycles (TS)
-    const ultradian = detectUltradianCycles(data.activity);
+    // Circadian phase from the temperature minimum, discounting movement and sleep
+    const markers = await this.estimatePhase(data.temperature, data.heartRate, data.activity, scoring);
+
+    // Ultradian cycles (Rust); an activity stream it rejects yields no result
+    const ultradian = await detectUltradianCyclesNative(data.activity).catch(() => undefined);
 
     // ADHD pattern heuristic – simplistic weighting for now
     const adhdScore = this.computeAdhdScore(
-      awakening.phaseDelay,
+      phaseDelay,
       iv,
-      sleepEffRes.sleepEfficiency,
+      sleepEfficiency,
     );
 
     const analysis: CircadianAnalysis = {
       intradailyVariability: this.metric(iv),
-      sleepEfficiency: this.metric(sleepEffRes.sleepEfficiency),
-      temperaturePhaseDelay: this.metric(awakening.phaseDelay),
+      sleepEfficiency: sleepEfficiency !== undefined ? this.metric(sleepEfficiency) : undefined,
+      temperaturePhaseDelay: markers && this.phaseDelayMetric(markers),
       adhdPatternScore: this.metric(adhdScore, 0.5),
-      ultradian: this.metric(ultradian, 0.8),
+      ultradian: ultradian && this.metric(ultradian, 0.8),
     };
 
     return analysis;
   }
 
   // ---------- helpers ----------
+  private async sleepPeriod(
+    activity: TimeSeries,
+  ): Promise<{ scoring?: SleepScoring; sleepEfficiency?: number }> {
+    let scoring: SleepScoring;
+    try {
+      scoring = await scoreSleepWake(activity, { algorithm: "oakley" });
+    } catch {
+      // Sampling coarser than a minute cannot be scored
+      return {};
+    }
+    try {
+      const metrics = await calcSleepPeriodMetrics(scoring);
+      return { scoring, sleepEfficiency: metrics.sleepEfficiency };
+    } catch {
+      // No sleep was scored, so there is no rest interval to measure
+      return { scoring };
+    }
+  }
+
+  private async estimatePhase(
+    temperature: TimeSeries | undefined,
+    heartRate: TimeSeries | undefined,
+    activity: TimeSeries,
+    sleep: SleepScoring | undefined,
+  ): Promise<CircadianPhaseMarkers | undefined> {
+    if (!temperature && !heartRate) return undefined;
+    try {
+      return await estimateCircadianPhase({ temperature, heartRate, activity, sleep });
+    } catch {
+      // Too short a recording or no detectable rhythm
+      return undefined;
+    }
+  }
+
+  /** Hours after the reference CBTmin; confidence falls to 0 as the interval widens to ±3 h. */
+  private phaseDelayMetric(markers: CircadianPhaseMarkers): DerivedMetric<number> {
+    const delay = ((markers.cbtMinClock - REFERENCE_CBT_MIN_HOUR + 36) % 24) - 12;
+    const halfWidthHours = (markers.cbtMinCi[1] - markers.cbtMinCi[0]) / 2 / 3_600_000;
+    return this.metric(delay, Math.max(0, 1 - halfWidthHours / 3));
+  }
+
   private metric<T extends number>(value: T, confidence = 1): DerivedMetric<T> {
     return { value, confidence };
   }
 
-  private computeAdhdScore(phaseDelay: number, iv: number, sleepEff: number): number {
+  /** Share of the criteria met, over those that could be evaluated. */
+  private computeAdhdScore(phaseDelay: number | undefined, iv: number, sleepEff: number | undefined): number {
     let score = 0;
-    if (phaseDelay > ADHD_THRESHOLDS.morningPhaseDelay) score += 1;
+    let criteria = 1;
+    if (phaseDelay !== undefined) {
+      criteria += 1;
+      if (phaseDelay > ADHD_THRESHOLDS.morningPhaseDelay) score += 1;
+    }
     if (iv > ADHD_THRESHOLDS.intradailyVariability) score += 1;
-    if (sleepEff < ADHD_THRESHOLDS.sleepEfficiency) score += 1;
-    return score / 3; // normalize 0-1
+    if (sleepEff !== undefined) {
+      criteria += 1;
+      if (sleepEff < ADHD_THRESHOLDS.sleepEfficiency) score += 1;
+    }
+    return score / criteria; // normalize 0-1
   }
 } 
\ No newline at end of file
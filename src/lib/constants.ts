@@ -8,4 +8,8 @@ export const ADHD_THRESHOLDS: ADHDThresholds = {
   morningPhaseDelay: 1.0,
   intradailyVariability: 0.8,
   sleepEfficiency: 80,
-}; 
\ No newline at end of file
+}; 
+
+// Core body temperature typically bottoms out about 2.5 h before habitual
+// wake, so the default 07:00 target wake puts the expected minimum near 04:30.
+export const REFERENCE_CBT_MIN_HOUR = 4.5;
@@ -70,21 +70,30 @@ export {
 
 /**
  * Heart Rate Variability (HRV) analysis for stress and autonomic
- * nervous system assessment in relation to circadian rhythms.
+ * nervous system assessment in relation to circadian rhythms, computed in
+ * Rust after RR artifact correction.
  */
 export {
-  calculateHRV,
-  type HRVMetrics,
-} from './hrv';
+  calcHrv,
+  correctRrIntervals,
+  getLiveHrv,
+  pushRrIntervals,
+  type HrvAnalysis,
+  type HrvMetrics,
+  type RrCorrection,
+} from './tauriBridge';
 
 /**
- * Advanced phase detection algorithms that identify circadian rhythm
- * patterns and potential disruptions or phase shifts.
+ * Morning awakening detection, computed in Rust, reporting each morning's
+ * activity onset and its delay from the target wake time.
  */
 export {
-  detectPhaseShifts,
-  calculateIntradailyVariability,
-} from './phaseDetection';
+  detectAwakening,
+  type AwakeningAnalysis,
+  type AwakeningConfig,
+  type AwakeningStatus,
+  type MorningAwakening,
+} from './tauriBridge';
 
 /**
  * Mathematical utilities for signal processing, statistical analysis,
@@ -1,3 +1,5 @@
+import type { TimeSeries, UltradianAnalysis } from "./types";
+
 let invokeFn: (cmd: string, args: any) => Promise<any>;
 
 // Prefer Tauri invoke when available; otherwise fall back to a stub that rejects.
@@ -19,14 +21,937 @@ export async function calcIntradailyVariability(activity: number[]): Promise<num
   return res.intradaily_variability;
 }
 
-export async function calcSleepEfficiency(activity: number[], sleepThreshold: number): Promise<{ sleepEfficiency: number; totalSleepMinutes: number; timeInBedMinutes: number; }> {
-  const res = await invokeFn("calculate_sleep_efficiency", {
-    activityData: activity,
-    sleepThreshold,
-  }) as { sleep_efficiency: number; total_sleep_minutes: number; time_in_bed_minutes: number };
+export interface CycleDetectionConfig {
+  smoothingMinutes?: number;
+  amplitudeThreshold?: number;
+  wakingStartHour?: number;
+  wakingEndHour?: number;
+  minPeakGapMinutes?: number;
+  maxPeakGapMinutes?: number;
+  minSamples?: number;
+}
+
+export async function detectUltradianCyclesNative(activity: TimeSeries, config: CycleDetectionConfig = {}): Promise<UltradianAnalysis> {
+  const res = await invokeFn("detect_ultradian_cycles", {
+    activity,
+    config: {
+      smoothing_minutes: config.smoothingMinutes,
+      amplitude_threshold: config.amplitudeThreshold,
+      waking_start_hour: config.wakingStartHour,
+      waking_end_hour: config.wakingEndHour,
+      min_peak_gap_minutes: config.minPeakGapMinutes,
+      max_peak_gap_minutes: config.maxPeakGapMinutes,
+      min_samples: config.minSamples,
+    },
+  }) as {
+    cycles: { start: number; end: number; peak_time: number; amplitude: number }[];
+    avg_duration_minutes: number;
+    cycle_count: number;
+    debug: {
+      sample_interval_minutes: number | null;
+      smoothed_activity: number[];
+      candidate_peaks: number[];
+      filtered_peaks: number[];
+      reduced_peaks: number[];
+    };
+  };
   return {
-    sleepEfficiency: res.sleep_efficiency,
+    cycles: res.cycles.map(c => ({ start: c.start, end: c.end, peakTime: c.peak_time, amplitude: c.amplitude })),
+    avgDurationMinutes: res.avg_duration_minutes,
+    cycleCount: res.cycle_count,
+    _debug: {
+      sampleIntervalMinutes: res.debug.sample_interval_minutes ?? undefined,
+      smoothedActivity: res.debug.smoothed_activity,
+      candidatePeaks: res.debug.candidate_peaks,
+      filteredPeaks: res.debug.filtered_peaks,
+      reducedPeaks: res.debug.reduced_peaks,
+    },
+  };
+}
+
+export interface RestActivityMetrics {
+  interdailyStability: number;
+  relativeAmplitude: number;
+  l5: number;
+  l5OnsetHour: number;
+  m10: number;
+  m10OnsetHour: number;
+  hourlyProfile: number[];
+  hoursObserved: number;
+  daysObserved: number;
+}
+
+export async function calcRestActivityMetrics(activity: TimeSeries): Promise<RestActivityMetrics> {
+  const res = await invokeFn("calculate_rest_activity_metrics", { activity }) as {
+    interdaily_stability: number;
+    relative_amplitude: number;
+    l5: number;
+    l5_onset_hour: number;
+    m10: number;
+    m10_onset_hour: number;
+    hourly_profile: number[];
+    hours_observed: number;
+    days_observed: number;
+  };
+  return {
+    interdailyStability: res.interdaily_stability,
+    relativeAmplitude: res.relative_amplitude,
+    l5: res.l5,
+    l5OnsetHour: res.l5_onset_hour,
+    m10: res.m10,
+    m10OnsetHour: res.m10_onset_hour,
+    hourlyProfile: res.hourly_profile,
+    hoursObserved: res.hours_observed,
+    daysObserved: res.days_observed,
+  };
+}
+
+export interface CosinorComponent {
+  periodHours: number;
+  amplitude: number;
+  amplitudeCi: [number, number];
+  acrophaseRadians: number;
+  acrophaseHours: number;
+  acrophaseCiHours: [number, number];
+  zeroAmplitudeF: number;
+  zeroAmplitudeP: number;
+}
+
+export interface CosinorFit {
+  mesor: number;
+  mesorCi: [number, number];
+  components: CosinorComponent[];
+  rSquared: number;
+  adjustedRSquared: number;
+  residualStandardError: number;
+  fStatistic: number;
+  pValue: number;
+  samples: number;
+  degreesOfFreedom: number;
+  confidenceLevel: number;
+  referenceTime: number;
+}
+
+export async function fitCosinor(series: TimeSeries, periodsHours: number[] = [24], confidenceLevel = 0.95): Promise<CosinorFit> {
+  const res = await invokeFn("fit_cosinor_model", { series, periodsHours, confidenceLevel }) as {
+    mesor: number;
+    mesor_ci: [number, number];
+    components: {
+      period_hours: number;
+      amplitude: number;
+      amplitude_ci: [number, number];
+      acrophase_radians: number;
+      acrophase_hours: number;
+      acrophase_ci_hours: [number, number];
+      zero_amplitude_f: number;
+      zero_amplitude_p: number;
+    }[];
+    r_squared: number;
+    adjusted_r_squared: number;
+    residual_standard_error: number;
+    f_statistic: number;
+    p_value: number;
+    samples: number;
+    degrees_of_freedom: number;
+    confidence_level: number;
+    reference_time: number;
+  };
+  return {
+    mesor: res.mesor,
+    mesorCi: res.mesor_ci,
+    components: res.components.map(c => ({
+      periodHours: c.period_hours,
+      amplitude: c.amplitude,
+      amplitudeCi: c.amplitude_ci,
+      acrophaseRadians: c.acrophase_radians,
+      acrophaseHours: c.acrophase_hours,
+      acrophaseCiHours: c.acrophase_ci_hours,
+      zeroAmplitudeF: c.zero_amplitude_f,
+      zeroAmplitudeP: c.zero_amplitude_p,
+    })),
+    rSquared: res.r_squared,
+    adjustedRSquared: res.adjusted_r_squared,
+    residualStandardError: res.residual_standard_error,
+    fStatistic: res.f_statistic,
+    pValue: res.p_value,
+    samples: res.samples,
+    degreesOfFreedom: res.degrees_of_freedom,
+    confidenceLevel: res.confidence_level,
+    referenceTime: res.reference_time,
+  };
+}
+
+export interface PeriodBand {
+  name: string;
+  minPeriodMinutes: number;
+  maxPeriodMinutes: number;
+}
+
+export interface SpectralPeak {
+  periodMinutes: number;
+  power: number;
+  falseAlarmProbability: number;
+  significant: boolean;
+}
+
+export interface BandSpectrum {
+  band: PeriodBand;
+  periodsMinutes: number[];
+  power: number[];
+  peaks: SpectralPeak[];
+  independentFrequencies: number;
+}
+
+export async function calcPeriodogram(
+  series: TimeSeries,
+  options: { bands?: PeriodBand[]; oversampling?: number; significanceLevel?: number; maxPeaks?: number } = {},
+): Promise<{ bands: BandSpectrum[]; samples: number; spanMinutes: number }> {
+  type RawBand = { name: string; min_period_minutes: number; max_period_minutes: number };
+  const res = await invokeFn("calculate_periodogram", {
+    series,
+    config: {
+      bands: options.bands?.map(b => ({ name: b.name, min_period_minutes: b.minPeriodMinutes, max_period_minutes: b.maxPeriodMinutes })),
+      oversampling: options.oversampling,
+      significance_level: options.significanceLevel,
+      max_peaks: options.maxPeaks,
+    },
+  }) as {
+    bands: {
+      band: RawBand;
+      periods_minutes: number[];
+      power: number[];
+      peaks: { period_minutes: number; power: number; false_alarm_probability: number; significant: boolean }[];
+      independent_frequencies: number;
+    }[];
+    samples: number;
+    span_minutes: number;
+  };
+  return {
+    bands: res.bands.map(b => ({
+      band: { name: b.band.name, minPeriodMinutes: b.band.min_period_minutes, maxPeriodMinutes: b.band.max_period_minutes },
+      periodsMinutes: b.periods_minutes,
+      power: b.power,
+      peaks: b.peaks.map(p => ({
+        periodMinutes: p.period_minutes,
+        power: p.power,
+        falseAlarmProbability: p.false_alarm_probability,
+        significant: p.significant,
+      })),
+      independentFrequencies: b.independent_frequencies,
+    })),
+    samples: res.samples,
+    spanMinutes: res.span_minutes,
+  };
+}
+
+export interface WaveletConfig {
+  minPeriodMinutes?: number;
+  maxPeriodMinutes?: number;
+  voicesPerOctave?: number;
+  omega0?: number;
+  resampleMinutes?: number;
+  maxGapMinutes?: number;
+  ridgePenalty?: number;
+}
+
+export interface Scalogram {
+  times: number[];
+  periodsMinutes: number[];
+  /** power[period][time] over the series variance; white noise averages 1. */
+  power: number[][];
+  coneOfInfluenceMinutes: number[];
+  missing: boolean[];
+  ridge: {
+    periodMinutes: (number | null)[];
+    phaseRadians: (number | null)[];
+    amplitude: (number | null)[];
+  };
+  resampleMinutes: number;
+}
+
+export async function calcWaveletScalogram(series: TimeSeries, config: WaveletConfig = {}): Promise<Scalogram> {
+  const res = await invokeFn("calculate_wavelet_scalogram", {
+    series,
+    config: {
+      min_period_minutes: config.minPeriodMinutes,
+      max_period_minutes: config.maxPeriodMinutes,
+      voices_per_octave: config.voicesPerOctave,
+      omega0: config.omega0,
+      resample_minutes: config.resampleMinutes,
+      max_gap_minutes: config.maxGapMinutes,
+      ridge_penalty: config.ridgePenalty,
+    },
+  }) as {
+    times: number[];
+    periods_minutes: number[];
+    power: number[][];
+    cone_of_influence_minutes: number[];
+    missing: boolean[];
+    ridge: { period_minutes: (number | null)[]; phase_radians: (number | null)[]; amplitude: (number | null)[] };
+    resample_minutes: number;
+  };
+  return {
+    times: res.times,
+    periodsMinutes: res.periods_minutes,
+    power: res.power,
+    coneOfInfluenceMinutes: res.cone_of_influence_minutes,
+    missing: res.missing,
+    ridge: {
+      periodMinutes: res.ridge.period_minutes,
+      phaseRadians: res.ridge.phase_radians,
+      amplitude: res.ridge.amplitude,
+    },
+    resampleMinutes: res.resample_minutes,
+  };
+}
+
+export type SleepScoringAlgorithm = "cole_kripke" | "sadeh" | "oakley";
+export type EpochLabel = "sleep" | "wake" | "no_data";
+
+export interface SleepScoringConfig {
+  algorithm?: SleepScoringAlgorithm;
+  epochSeconds?: number;
+  rescore?: boolean;
+  oakleyThreshold?: number;
+}
+
+export interface SleepScoring {
+  algorithm: SleepScoringAlgorithm;
+  epochSeconds: number;
+  epochStarts: number[];
+  counts: number[];
+  scores: (number | null)[];
+  labels: EpochLabel[];
+  rescoredEpochs: number;
+  sleepEpochs: number;
+  wakeEpochs: number;
+}
+
+export async function scoreSleepWake(activity: TimeSeries, config: SleepScoringConfig = {}): Promise<SleepScoring> {
+  const res = await invokeFn("score_sleep_wake", {
+    activity,
+    config: {
+      algorithm: config.algorithm,
+      epoch_seconds: config.epochSeconds,
+      rescore: config.rescore,
+      oakley_threshold: config.oakleyThreshold,
+    },
+  }) as {
+    algorithm: SleepScoringAlgorithm;
+    epoch_seconds: number;
+    epoch_starts: number[];
+    counts: number[];
+    scores: (number | null)[];
+    labels: EpochLabel[];
+    rescored_epochs: number;
+    sleep_epochs: number;
+    wake_epochs: number;
+  };
+  return {
+    algorithm: res.algorithm,
+    epochSeconds: res.epoch_seconds,
+    epochStarts: res.epoch_starts,
+    counts: res.counts,
+    scores: res.scores,
+    labels: res.labels,
+    rescoredEpochs: res.rescored_epochs,
+    sleepEpochs: res.sleep_epochs,
+    wakeEpochs: res.wake_epochs,
+  };
+}
+
+export interface InBedWindow {
+  bedTime: number;
+  riseTime: number;
+}
+
+export interface SleepPeriodMetrics {
+  bedTime: number;
+  riseTime: number;
+  windowDetected: boolean;
+  sleepOnset: number | null;
+  finalWake: number | null;
+  sleepOnsetLatencyMinutes: number | null;
+  totalSleepMinutes: number;
+  timeInBedMinutes: number;
+  missingMinutes: number;
+  wasoMinutes: number;
+  terminalWakeMinutes: number;
+  awakenings: number;
+  meanAwakeningMinutes: number;
+  longestAwakeningMinutes: number;
+  sleepFragmentationIndex: number;
+  /** Percentage of observed time in bed spent asleep. */
+  sleepEfficiency: number;
+}
+
+/** Without a window the main rest interval is detected from the scoring. */
+export async function calcSleepPeriodMetrics(scoring: SleepScoring, window?: InBedWindow): Promise<SleepPeriodMetrics> {
+  const res = await invokeFn("calculate_sleep_period_metrics", {
+    scoring: {
+      epoch_seconds: scoring.epochSeconds,
+      epoch_starts: scoring.epochStarts,
+      labels: scoring.labels,
+    },
+    window: window && { bed_time: window.bedTime, rise_time: window.riseTime },
+  }) as {
+    bed_time: number;
+    rise_time: number;
+    window_detected: boolean;
+    sleep_onset: number | null;
+    final_wake: number | null;
+    sleep_onset_latency_minutes: number | null;
+    total_sleep_minutes: number;
+    time_in_bed_minutes: number;
+    missing_minutes: number;
+    waso_minutes: number;
+    terminal_wake_minutes: number;
+    awakenings: number;
+    mean_awakening_minutes: number;
+    longest_awakening_minutes: number;
+    sleep_fragmentation_index: number;
+    sleep_efficiency: number;
+  };
+  return {
+    bedTime: res.bed_time,
+    riseTime: res.rise_time,
+    windowDetected: res.window_detected,
+    sleepOnset: res.sleep_onset,
+    finalWake: res.final_wake,
+    sleepOnsetLatencyMinutes: res.sleep_onset_latency_minutes,
     totalSleepMinutes: res.total_sleep_minutes,
     timeInBedMinutes: res.time_in_bed_minutes,
+    missingMinutes: res.missing_minutes,
+    wasoMinutes: res.waso_minutes,
+    terminalWakeMinutes: res.terminal_wake_minutes,
+    awakenings: res.awakenings,
+    meanAwakeningMinutes: res.mean_awakening_minutes,
+    longestAwakeningMinutes: res.longest_awakening_minutes,
+    sleepFragmentationIndex: res.sleep_fragmentation_index,
+    sleepEfficiency: res.sleep_efficiency,
+  };
+}
+
+export type SleepDayStatus = "observed" | "no_sleep" | "missing";
+
+export interface SleepDay {
+  /** Noon-to-noon local day, named after the date of its evening. */
+  date: string;
+  status: SleepDayStatus;
+  coverage: number;
+  sleepRegularityIndex: number | null;
+  sleepMinutes: number;
+  bedTime: string | null;
+  wakeTime: string | null;
+  midsleep: string | null;
+}
+
+export interface SleepRegularity {
+  sleepRegularityIndex: number;
+  days: SleepDay[];
+  observedDays: number;
+  missingDays: number;
+  comparedEpochs: number;
+  bedTimeSdMinutes: number | null;
+  wakeTimeSdMinutes: number | null;
+  midsleepSdMinutes: number | null;
+}
+
+/** Pass either multi-day scored epochs or HealthKit sleep records. */
+export async function calcSleepRegularity(
+  source: { scoring: SleepScoring } | { sleepRecords: { startDate: Date; endDate: Date; sleepState: "asleep" | "inBed" | "awake" }[] },
+): Promise<SleepRegularity> {
+  const res = await invokeFn("calculate_sleep_regularity", "scoring" in source
+    ? {
+      scoring: {
+        epoch_seconds: source.scoring.epochSeconds,
+        epoch_starts: source.scoring.epochStarts,
+        labels: source.scoring.labels,
+      },
+    }
+    : {
+      sleepRecords: source.sleepRecords.map(r => ({
+        start: r.startDate.getTime(),
+        end: r.endDate.getTime(),
+        state: r.sleepState === "inBed" ? "in_bed" : r.sleepState,
+      })),
+    }) as {
+    sleep_regularity_index: number;
+    days: {
+      date: string;
+      status: SleepDayStatus;
+      coverage: number;
+      sleep_regularity_index: number | null;
+      sleep_minutes: number;
+      bed_time: string | null;
+      wake_time: string | null;
+      midsleep: string | null;
+    }[];
+    observed_days: number;
+    missing_days: number;
+    compared_epochs: number;
+    bed_time_sd_minutes: number | null;
+    wake_time_sd_minutes: number | null;
+    midsleep_sd_minutes: number | null;
+  };
+  return {
+    sleepRegularityIndex: res.sleep_regularity_index,
+    days: res.days.map(d => ({
+      date: d.date,
+      status: d.status,
+      coverage: d.coverage,
+      sleepRegularityIndex: d.sleep_regularity_index,
+      sleepMinutes: d.sleep_minutes,
+      bedTime: d.bed_time,
+      wakeTime: d.wake_time,
+      midsleep: d.midsleep,
+    })),
+    observedDays: res.observed_days,
+    missingDays: res.missing_days,
+    comparedEpochs: res.compared_epochs,
+    bedTimeSdMinutes: res.bed_time_sd_minutes,
+    wakeTimeSdMinutes: res.wake_time_sd_minutes,
+    midsleepSdMinutes: res.midsleep_sd_minutes,
+  };
+}
+
+export type ChronotypeCategory = "extremely_early" | "early" | "intermediate" | "late" | "extremely_late";
+
+export interface ChronotypeConfig {
+  /** Workdays from Monday to Sunday. */
+  workdayMask?: [boolean, boolean, boolean, boolean, boolean, boolean, boolean];
+  /** `YYYY-MM-DD` dates that are free regardless of the mask. */
+  freeDates?: string[];
+  workDates?: string[];
+}
+
+/** Clock times are local hours in [0, 24); durations are hours. */
+export interface Chronotype {
+  msw: number;
+  msf: number;
+  msfSc: number;
+  sleepDurationWork: number;
+  sleepDurationFree: number;
+  sleepDurationWeek: number;
+  socialJetLag: number;
+  category: ChronotypeCategory;
+  naturalSleepOnset: number;
+  naturalWakeTime: number;
+  workNights: number;
+  freeNights: number;
+}
+
+export async function calcChronotype(
+  sleepRecords: { startDate: Date; endDate: Date; sleepState: "asleep" | "inBed" | "awake" }[],
+  config: ChronotypeConfig = {},
+): Promise<Chronotype> {
+  const res = await invokeFn("calculate_chronotype", {
+    sleepRecords: sleepRecords.map(r => ({
+      start: r.startDate.getTime(),
+      end: r.endDate.getTime(),
+      state: r.sleepState === "inBed" ? "in_bed" : r.sleepState,
+    })),
+    config: {
+      workday_mask: config.workdayMask,
+      free_dates: config.freeDates,
+      work_dates: config.workDates,
+    },
+  }) as {
+    msw: number;
+    msf: number;
+    msf_sc: number;
+    sleep_duration_work: number;
+    sleep_duration_free: number;
+    sleep_duration_week: number;
+    social_jet_lag: number;
+    category: ChronotypeCategory;
+    natural_sleep_onset: number;
+    natural_wake_time: number;
+    work_nights: number;
+    free_nights: number;
+  };
+  return {
+    msw: res.msw,
+    msf: res.msf,
+    msfSc: res.msf_sc,
+    sleepDurationWork: res.sleep_duration_work,
+    sleepDurationFree: res.sleep_duration_free,
+    sleepDurationWeek: res.sleep_duration_week,
+    socialJetLag: res.social_jet_lag,
+    category: res.category,
+    naturalSleepOnset: res.natural_sleep_onset,
+    naturalWakeTime: res.natural_wake_time,
+    workNights: res.work_nights,
+    freeNights: res.free_nights,
+  };
+}
+
+export type SpectralMethod = "lomb" | "welch";
+export type ArtifactKind = "ectopic" | "missed" | "extra" | "long_short";
+
+export interface ArtifactConfig {
+  alpha?: number;
+  thresholdWindowBeats?: number;
+  medianWindowBeats?: number;
+  maxCorrectedPercent?: number;
+}
+
+export interface HrvConfig {
+  windowSeconds?: number;
+  stepSeconds?: number;
+  spectralMethod?: SpectralMethod;
+  resampleHz?: number;
+  welchSegmentSeconds?: number;
+  correctArtifacts?: boolean;
+  artifacts?: ArtifactConfig;
+}
+
+export interface RrArtifact {
+  index: number;
+  kind: ArtifactKind;
+}
+
+/** Milliseconds and ms² unless noted. */
+export interface HrvMetrics {
+  start: number;
+  end: number;
+  beats: number;
+  meanRr: number;
+  meanHeartRate: number;
+  sdnn: number;
+  rmssd: number;
+  sdsd: number;
+  nn50: number;
+  pnn50: number;
+  vlfPower: number;
+  lfPower: number;
+  hfPower: number;
+  lfHfRatio: number | null;
+  lfNu: number | null;
+  hfNu: number | null;
+  sd1: number;
+  sd2: number;
+  dfaAlpha1: number | null;
+  sampleEntropy: number | null;
+  correctedBeatsPercent: number;
+  reliable: boolean;
+}
+
+export interface HrvAnalysis {
+  overall: HrvMetrics;
+  windows: HrvMetrics[];
+  artifacts: RrArtifact[];
+}
+
+interface RawHrvMetrics {
+  start: number;
+  end: number;
+  beats: number;
+  mean_rr: number;
+  mean_heart_rate: number;
+  sdnn: number;
+  rmssd: number;
+  sdsd: number;
+  nn50: number;
+  pnn50: number;
+  vlf_power: number;
+  lf_power: number;
+  hf_power: number;
+  lf_hf_ratio: number | null;
+  lf_nu: number | null;
+  hf_nu: number | null;
+  sd1: number;
+  sd2: number;
+  dfa_alpha1: number | null;
+  sample_entropy: number | null;
+  corrected_beats_percent: number;
+  reliable: boolean;
+}
+
+function toHrvMetrics(res: RawHrvMetrics): HrvMetrics {
+  return {
+    start: res.start,
+    end: res.end,
+    beats: res.beats,
+    meanRr: res.mean_rr,
+    meanHeartRate: res.mean_heart_rate,
+    sdnn: res.sdnn,
+    rmssd: res.rmssd,
+    sdsd: res.sdsd,
+    nn50: res.nn50,
+    pnn50: res.pnn50,
+    vlfPower: res.vlf_power,
+    lfPower: res.lf_power,
+    hfPower: res.hf_power,
+    lfHfRatio: res.lf_hf_ratio,
+    lfNu: res.lf_nu,
+    hfNu: res.hf_nu,
+    sd1: res.sd1,
+    sd2: res.sd2,
+    dfaAlpha1: res.dfa_alpha1,
+    sampleEntropy: res.sample_entropy,
+    correctedBeatsPercent: res.corrected_beats_percent,
+    reliable: res.reliable,
+  };
+}
+
+function toArtifactConfig(config: ArtifactConfig) {
+  return {
+    alpha: config.alpha,
+    threshold_window_beats: config.thresholdWindowBeats,
+    median_window_beats: config.medianWindowBeats,
+    max_corrected_percent: config.maxCorrectedPercent,
+  };
+}
+
+function toHrvConfig(config: HrvConfig) {
+  return {
+    window_seconds: config.windowSeconds,
+    step_seconds: config.stepSeconds,
+    spectral_method: config.spectralMethod,
+    resample_hz: config.resampleHz,
+    welch_segment_seconds: config.welchSegmentSeconds,
+    correct_artifacts: config.correctArtifacts,
+    artifacts: toArtifactConfig(config.artifacts ?? {}),
+  };
+}
+
+/** `intervals` are consecutive RR intervals in ms, the first beat at `start`. */
+export async function calcHrv(start: number, intervals: number[], config: HrvConfig = {}): Promise<HrvAnalysis> {
+  const res = await invokeFn("calculate_hrv", {
+    series: { start, intervals },
+    config: toHrvConfig(config),
+  }) as { overall: RawHrvMetrics; windows: RawHrvMetrics[]; artifacts: RrArtifact[] };
+  return {
+    overall: toHrvMetrics(res.overall),
+    windows: res.windows.map(toHrvMetrics),
+    artifacts: res.artifacts,
+  };
+}
+
+/** Without `start` the intervals continue from the last beat pushed, so the first push needs one. */
+export async function pushRrIntervals(intervals: number[], start?: number): Promise<void> {
+  await invokeFn("push_rr_intervals", {
+    series: { start: start ?? 0, intervals },
+    continues: start === undefined,
+  });
+}
+
+export async function getLiveHrv(config: HrvConfig = {}): Promise<HrvMetrics> {
+  const res = await invokeFn("get_live_hrv", { config: toHrvConfig(config) }) as RawHrvMetrics;
+  return toHrvMetrics(res);
+}
+
+export interface RrCorrection {
+  start: number;
+  intervals: number[];
+  /** Whether each output interval was changed or created by correction. */
+  corrected: boolean[];
+  artifacts: RrArtifact[];
+  ectopic: number;
+  missed: number;
+  extra: number;
+  longShort: number;
+  correctedBeatsPercent: number;
+  reliable: boolean;
+}
+
+/** Detects ectopic, missed and extra beats and returns the corrected intervals. */
+export async function correctRrIntervals(start: number, intervals: number[], config: ArtifactConfig = {}): Promise<RrCorrection> {
+  const res = await invokeFn("correct_rr_intervals", {
+    series: { start, intervals },
+    config: toArtifactConfig(config),
+  }) as {
+    series: { start: number; intervals: number[] };
+    corrected: boolean[];
+    artifacts: RrArtifact[];
+    ectopic: number;
+    missed: number;
+    extra: number;
+    long_short: number;
+    corrected_beats_percent: number;
+    reliable: boolean;
   };
-} 
\ No newline at end of file
+  return {
+    start: res.series.start,
+    intervals: res.series.intervals,
+    corrected: res.corrected,
+    artifacts: res.artifacts,
+    ectopic: res.ectopic,
+    missed: res.missed,
+    extra: res.extra,
+    longShort: res.long_short,
+    correctedBeatsPercent: res.corrected_beats_percent,
+    reliable: res.reliable,
+  };
+}
+
+export type MarkerSignal = "wrist_temperature" | "heart_rate";
+
+export interface PhaseMarkerConfig {
+  confidenceLevel?: number;
+  activityLagMinutes?: number;
+  minSpanHours?: number;
+}
+
+export interface SignalPhase {
+  signal: MarkerSignal;
+  cbtMin: number;
+  cbtMinCi: [number, number];
+  amplitude: number;
+  pValue: number;
+  rhythmDetected: boolean;
+  activityEffect: number | null;
+  sleepEffect: number | null;
+  samples: number;
+}
+
+/** Instants are epoch ms; clock values are local hours in [0, 24). */
+export interface CircadianPhaseMarkers {
+  cbtMin: number;
+  cbtMinCi: [number, number];
+  cbtMinClock: number;
+  dlmo: number;
+  dlmoCi: [number, number];
+  dlmoClock: number;
+  signals: SignalPhase[];
+  confidenceLevel: number;
+}
+
+/**
+ * Core body temperature minimum and DLMO proxy from overnight wrist
+ * temperature and/or heart rate. Activity and sleep scoring let the estimate
+ * discount their masking effects.
+ */
+export async function estimateCircadianPhase(
+  input: { temperature?: TimeSeries; heartRate?: TimeSeries; activity?: TimeSeries; sleep?: SleepScoring },
+  config: PhaseMarkerConfig = {},
+): Promise<CircadianPhaseMarkers> {
+  const res = await invokeFn("estimate_circadian_phase", {
+    temperature: input.temperature,
+    heartRate: input.heartRate,
+    activity: input.activity,
+    sleep: input.sleep && {
+      epoch_seconds: input.sleep.epochSeconds,
+      epoch_starts: input.sleep.epochStarts,
+      labels: input.sleep.labels,
+    },
+    config: {
+      confidence_level: config.confidenceLevel,
+      activity_lag_minutes: config.activityLagMinutes,
+      min_span_hours: config.minSpanHours,
+    },
+  }) as {
+    cbt_min: number;
+    cbt_min_ci: [number, number];
+    cbt_min_clock: number;
+    dlmo: number;
+    dlmo_ci: [number, number];
+    dlmo_clock: number;
+    signals: {
+      signal: MarkerSignal;
+      cbt_min: number;
+      cbt_min_ci: [number, number];
+      amplitude: number;
+      p_value: number;
+      rhythm_detected: boolean;
+      activity_effect: number | null;
+      sleep_effect: number | null;
+      samples: number;
+    }[];
+    confidence_level: number;
+  };
+  return {
+    cbtMin: res.cbt_min,
+    cbtMinCi: res.cbt_min_ci,
+    cbtMinClock: res.cbt_min_clock,
+    dlmo: res.dlmo,
+    dlmoCi: res.dlmo_ci,
+    dlmoClock: res.dlmo_clock,
+    signals: res.signals.map(s => ({
+      signal: s.signal,
+      cbtMin: s.cbt_min,
+      cbtMinCi: s.cbt_min_ci,
+      amplitude: s.amplitude,
+      pValue: s.p_value,
+      rhythmDetected: s.rhythm_detected,
+      activityEffect: s.activity_effect,
+      sleepEffect: s.sleep_effect,
+      samples: s.samples,
+    })),
+    confidenceLevel: res.confidence_level,
+  };
+}
+
+export type AwakeningStatus = "detected" | "not_detected" | "no_data";
+
+export interface AwakeningConfig {
+  /** Local `HH:MM` bounds of the window an awakening may start in. */
+  windowStart?: string;
+  windowEnd?: string;
+  sustainedMinutes?: number;
+  minActiveFraction?: number;
+  /** Derived from the data's rest and active levels when omitted. */
+  threshold?: number;
+  /** Local `HH:MM`; defaults to the wake time in settings. */
+  targetWake?: string;
+}
+
+export interface MorningAwakening {
+  /** `YYYY-MM-DD` */
+  date: string;
+  status: AwakeningStatus;
+  awakeningTime: number | null;
+  /** 0-100 */
+  quality: number | null;
+  /** Hours after the target wake time; negative when earlier. */
+  phaseDelayHours: number | null;
+  targetWake: number;
+  coverage: number;
+}
+
+export interface AwakeningAnalysis {
+  threshold: number;
+  sampleIntervalMinutes: number;
+  mornings: MorningAwakening[];
+}
+
+/** One result per local morning in the data, including mornings with no awakening found. */
+export async function detectAwakening(activity: TimeSeries, config: AwakeningConfig = {}): Promise<AwakeningAnalysis> {
+  const res = await invokeFn("detect_awakening", {
+    activity,
+    config: {
+      window_start: config.windowStart,
+      window_end: config.windowEnd,
+      sustained_minutes: config.sustainedMinutes,
+      min_active_fraction: config.minActiveFraction,
+      threshold: config.threshold,
+      target_wake: config.targetWake,
+    },
+  }) as {
+    threshold: number;
+    sample_interval_minutes: number;
+    mornings: {
+      date: string;
+      status: AwakeningStatus;
+      awakening_time: number | null;
+      quality: number | null;
+      phase_delay_hours: number | null;
+      target_wake: number;
+      coverage: number;
+    }[];
+  };
+  return {
+    threshold: res.threshold,
+    sampleIntervalMinutes: res.sample_interval_minutes,
+    mornings: res.mornings.map(m => ({
+      date: m.date,
+      status: m.status,
+      awakeningTime: m.awakening_time,
+      quality: m.quality,
+      phaseDelayHours: m.phase_delay_hours,
+      targetWake: m.target_wake,
+      coverage: m.coverage,
+    })),
+  };
+}
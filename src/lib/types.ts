@@ -4,7 +4,7 @@
   These types sit at the core of the analytics engine so that both
   frontend TypeScript and backend Rust (via serde) can share a common
   vocabulary. Wherever possible we keep them generic and minimal –
-  concrete feature-specific result interfaces (e.g. AwakeningResult)
+  concrete feature-specific result interfaces (e.g. AwakeningAnalysis)
   will extend these primitives in their respective modules.
 */
 
@@ -42,7 +42,7 @@ export interface DerivedMetric<TValue = unknown> {
 export interface CircadianAnalysis {
   intradailyVariability?: DerivedMetric<number>;
   sleepEfficiency?: DerivedMetric<number>;
-  temperaturePhaseDelay?: DerivedMetric<number>; // hours
+  temperaturePhaseDelay?: DerivedMetric<number>; // hours CBTmin falls after its reference
   adhdPatternScore?: DerivedMetric<number>;      // 0-1
   ultradian?: DerivedMetric<UltradianAnalysis>;
   // Additional results added incrementally as modules mature
@@ -65,8 +65,10 @@ export interface UltradianAnalysis {
    * Filtered out by the JSON.stringify replacer in the debug component.
    */
   _debug?: {
+    sampleIntervalMinutes?: number;
     smoothedActivity?: number[];
     candidatePeaks?: number[];
     filteredPeaks?: number[];
+    reducedPeaks?: number[];
   }
 } 
\ No newline at end of file
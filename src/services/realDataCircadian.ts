@@ -1,4 +1,5 @@
 import { HealthDataParser, ParsedHealthData, HealthRecord, SleepRecord } from './healthDataParser';
+import { calcChronotype, type Chronotype } from '../lib/tauriBridge';
 
 export interface RealDataCircadianPhase {
   name: string;
@@ -18,6 +19,7 @@ export interface CircadianAnalysis {
   phases: RealDataCircadianPhase[];
   personalizedWakeTime: number;
   personalizedSleepTime: number;
+  chronotype?: Chronotype;
   energyPeaks: number[];
   energyTroughs: number[];
   sleepEfficiency: number;
@@ -31,23 +33,16 @@ export interface CircadianAnalysis {
 
 export class RealDataCircadianEngine {
   private healthData: ParsedHealthData;
-  private basePhases: RealDataCircadianPhase[] = [
-    { name: 'Deep Rest', description: 'Deep sleep and recovery', start: 0, end: 6, color: '#1e1b4b', intensity: 0.1 },
-    { name: 'Dawn Awakening', description: 'Natural wake-up period', start: 6, end: 8, color: '#3730a3', intensity: 0.3 },
-    { name: 'Morning Peak', description: 'High alertness and energy', start: 8, end: 12, color: '#2563eb', intensity: 0.9 },
-    { name: 'Afternoon Dip', description: 'Natural energy decline', start: 12, end: 14, color: '#1d4ed8', intensity: 0.5 },
-    { name: 'Second Peak', description: 'Renewed energy and focus', start: 14, end: 18, color: '#2563eb', intensity: 0.8 },
-    { name: 'Evening Wind-down', description: 'Preparing for rest', start: 18, end: 22, color: '#3730a3', intensity: 0.4 },
-    { name: 'Sleep Preparation', description: 'Natural sleep onset', start: 22, end: 24, color: '#1e1b4b', intensity: 0.2 }
-  ];
-
   constructor(healthData: ParsedHealthData) {
     this.healthData = healthData;
   }
 
   async analyzePersonalCircadianRhythm(): Promise<CircadianAnalysis> {
-    const personalizedWakeTime = await this.calculatePersonalWakeTime();
-    const personalizedSleepTime = await this.calculatePersonalSleepTime();
+    // The chronotype's natural sleep window replaces fixed clock assumptions;
+    // the median sleep record times remain a fallback for sparse data.
+    const chronotype = await this.calculateChronotype();
+    const personalizedWakeTime = chronotype?.naturalWakeTime ?? await this.calculatePersonalWakeTime();
+    const personalizedSleepTime = chronotype?.naturalSleepOnset ?? await this.calculatePersonalSleepTime();
     const avgHeartRateByHour = await this.calculateHourlyHeartRatePattern();
     const energyPeaks = await this.identifyEnergyPeaks(avgHeartRateByHour);
     const energyTroughs = await this.identifyEnergyTroughs(avgHeartRateByHour);
@@ -75,6 +70,7 @@ export class RealDataCircadianEngine {
       phases: personalizedPhases,
       personalizedWakeTime,
       personalizedSleepTime,
+      chronotype,
       energyPeaks,
       energyTroughs,
       sleepEfficiency,
@@ -83,6 +79,14 @@ export class RealDataCircadianEngine {
     };
   }
 
+  private async calculateChronotype(): Promise<Chronotype | undefined> {
+    try {
+      return await calcChronotype(this.getRecentSleepData(30));
+    } catch {
+      return undefined; // Needs both work and free nights, and the desktop app
+    }
+  }
+
   private async calculatePersonalWakeTime(): Promise<number> {
     // Analyze last 30 days of sleep data to find average wake time
     const recentSleep = this.getRecentSleepData(30);